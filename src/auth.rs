@@ -37,6 +37,7 @@ mod tests {
     use super::*;
     use crate::model::SessionStoreData;
     use crate::store::JsonFileStore;
+    use std::collections::BTreeMap;
     use tempfile::tempdir;
 
     fn sample_session() -> Session {
@@ -51,6 +52,18 @@ mod tests {
             has_stored_password: true,
             passwd_unsafe_mode: Some(PasswdUnsafeMode::Bare),
             stored_password: Some("secret".to_string()),
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         }
     }
 
@@ -0,0 +1,140 @@
+use crate::model::Session;
+use crate::runner;
+use std::collections::BTreeMap;
+
+/// Names that usually mean a form or import left a placeholder behind.
+const PLACEHOLDER_NAMES: &[&str] = &[
+    "host",
+    "hostname",
+    "name",
+    "new",
+    "new-session",
+    "session",
+    "untitled",
+];
+
+/// One problem reported by `se doctor`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Finding {
+    /// Session (or sessions) the finding is about
+    pub subject: String,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// OpenSSH client programs that are not on `PATH`. Sessions using ControlMaster,
+/// a jump host, or extra options, `se exec`, and most scp transfers need them.
+pub fn check_tools() -> Vec<Finding> {
+    ["ssh", "scp"]
+        .into_iter()
+        .filter(|program| runner::find_on_path(program).is_none())
+        .map(|program| {
+            Finding::new(
+                program,
+                format!("not found on PATH; {}", runner::INSTALL_HINT),
+            )
+        })
+        .collect()
+}
+
+/// Every check `se doctor` runs over the stored sessions, in report order.
+pub fn check_sessions(sessions: &[Session]) -> Vec<Finding> {
+    let mut findings = suspicious_names(sessions);
+    findings.extend(duplicate_targets(sessions));
+    findings
+}
+
+/// Names that are blank, punctuation only, or a known placeholder.
+fn suspicious_names(sessions: &[Session]) -> Vec<Finding> {
+    sessions
+        .iter()
+        .filter_map(|session| {
+            let name = session.name.trim();
+            if !name.chars().any(char::is_alphanumeric) {
+                Some(Finding::new(
+                    format!("'{}'", session.name),
+                    "name is empty or has no letters or digits",
+                ))
+            } else if PLACEHOLDER_NAMES.contains(&name.to_lowercase().as_str()) {
+                Some(Finding::new(
+                    &session.name,
+                    "name looks like a placeholder; rename it with `se update --new-name`",
+                ))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Several names pointing at the same `user@host:port`, typically left over from
+/// repeated imports.
+fn duplicate_targets(sessions: &[Session]) -> Vec<Finding> {
+    let mut by_target: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for session in sessions {
+        by_target
+            .entry(format!("{}:{}", session.target(), session.port))
+            .or_default()
+            .push(&session.name);
+    }
+    by_target
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(target, names)| {
+            Finding::new(
+                names.join(", "),
+                format!("{} sessions share the target {}", names.len(), target),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, host: &str) -> Session {
+        Session::builder(name, host, "me").build()
+    }
+
+    #[test]
+    fn flags_placeholder_and_blank_names() {
+        let sessions = vec![
+            session("Host", "a.example.com"),
+            session(" - ", "b.example.com"),
+            session("web", "web.example.com"),
+            session("new-session", "c.example.com"),
+        ];
+        let subjects: Vec<String> = check_sessions(&sessions)
+            .into_iter()
+            .map(|finding| finding.subject)
+            .collect();
+        assert_eq!(subjects, ["Host", "' - '", "new-session"]);
+    }
+
+    #[test]
+    fn flags_names_sharing_a_target() {
+        let mut other_port = session("web-alt", "web.example.com");
+        other_port.port = 2222;
+        let sessions = vec![
+            session("web", "web.example.com"),
+            session("web-2", "web.example.com"),
+            other_port,
+        ];
+        assert_eq!(
+            check_sessions(&sessions),
+            vec![Finding::new(
+                "web, web-2",
+                "2 sessions share the target me@web.example.com:22"
+            )]
+        );
+    }
+}
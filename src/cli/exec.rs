@@ -0,0 +1,194 @@
+use crate::model::Session;
+use crate::openssh;
+use crate::parallel::run_bounded;
+use crate::runner::CommandRunner;
+use crossterm::style::{Color, Stylize};
+use std::io::{self, Write};
+use std::process::Output;
+use std::sync::Mutex;
+
+/// How `se exec` lays out each session's output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStyle {
+    /// Every line starts with `[name]`; stderr stays on stderr
+    Prefix,
+    /// All of a session's output under one header, stderr after stdout
+    Block,
+}
+
+/// What a session's command printed, ready to be written in one piece.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Rendered {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run `remote_command` on every session, at most `parallel` at a time. Each
+/// session's output is captured and printed as a unit once its command finishes, so
+/// lines from different hosts never interleave. Returns the names that failed.
+pub fn run_exec(
+    sessions: Vec<Session>,
+    runner: &dyn CommandRunner,
+    remote_command: &[String],
+    style: OutputStyle,
+    parallel: usize,
+    color: Option<Color>,
+) -> Vec<String> {
+    let print_lock = Mutex::new(());
+    run_bounded(sessions, parallel, |session| {
+        let mut command = openssh::exec_command(&session, remote_command);
+        log::info!("running {:?}", command);
+        let result = runner.output(&mut command);
+        let success = matches!(&result, Ok(output) if output.status.success());
+        let rendered = render(&session, &result, style, color);
+
+        let _guard = print_lock.lock().expect("print lock poisoned");
+        let _ = io::stdout().lock().write_all(rendered.stdout.as_bytes());
+        let _ = io::stderr().lock().write_all(rendered.stderr.as_bytes());
+        (!success).then_some(session.name)
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+pub fn render(
+    session: &Session,
+    result: &io::Result<Output>,
+    style: OutputStyle,
+    color: Option<Color>,
+) -> Rendered {
+    let name = match color {
+        Some(color) => format!("{}", session.name.as_str().with(color).bold()),
+        None => session.name.clone(),
+    };
+    let (stdout, stderr, failure) = match result {
+        Ok(output) => (
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+            (!output.status.success()).then(|| match output.status.code() {
+                Some(code) => format!("exited with status {}", code),
+                None => "killed by a signal".to_string(),
+            }),
+        ),
+        Err(err) => (
+            String::new(),
+            String::new(),
+            Some(format!("failed to run ssh: {}", err)),
+        ),
+    };
+
+    let mut rendered = Rendered::default();
+    match style {
+        OutputStyle::Prefix => {
+            push_prefixed(&mut rendered.stdout, &name, &stdout);
+            push_prefixed(&mut rendered.stderr, &name, &stderr);
+            if let Some(failure) = failure {
+                push_prefixed(&mut rendered.stderr, &name, &failure);
+            }
+        }
+        OutputStyle::Block => {
+            let status = failure.map(|f| format!(" ({})", f)).unwrap_or_default();
+            rendered.stdout = format!("=== {} {}{} ===\n", name, session.target(), status);
+            for text in [stdout, stderr] {
+                rendered.stdout.push_str(&text);
+                if !text.is_empty() && !text.ends_with('\n') {
+                    rendered.stdout.push('\n');
+                }
+            }
+            rendered.stdout.push('\n');
+        }
+    }
+    rendered
+}
+
+fn push_prefixed(out: &mut String, name: &str, text: &str) {
+    for line in text.lines() {
+        out.push_str(&format!("[{}] {}\n", name, line));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::fake::FakeRunner;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn output(code: i32, stdout: &str, stderr: &str) -> io::Result<Output> {
+        Ok(Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        })
+    }
+
+    fn web() -> Session {
+        Session::builder("web", "web.example.com", "deploy").build()
+    }
+
+    #[test]
+    fn prefix_style_tags_every_line() {
+        let rendered = render(
+            &web(),
+            &output(2, "up 3 days\nload 0.1", "warning: low disk\n"),
+            OutputStyle::Prefix,
+            None,
+        );
+        assert_eq!(rendered.stdout, "[web] up 3 days\n[web] load 0.1\n");
+        assert_eq!(
+            rendered.stderr,
+            "[web] warning: low disk\n[web] exited with status 2\n"
+        );
+    }
+
+    #[test]
+    fn block_style_groups_output_under_a_header() {
+        let rendered = render(
+            &web(),
+            &output(0, "up 3 days\n", "note"),
+            OutputStyle::Block,
+            None,
+        );
+        assert_eq!(
+            rendered.stdout,
+            "=== web deploy@web.example.com ===\nup 3 days\nnote\n\n"
+        );
+        assert!(rendered.stderr.is_empty());
+
+        let failed = render(
+            &web(),
+            &Err(io::Error::from(io::ErrorKind::NotFound)),
+            OutputStyle::Block,
+            None,
+        );
+        assert!(
+            failed
+                .stdout
+                .starts_with("=== web deploy@web.example.com (failed to run ssh: ")
+        );
+    }
+
+    #[test]
+    fn run_exec_reports_failed_sessions() {
+        let runner = FakeRunner::exiting(255, "Permission denied");
+        let sessions = vec![
+            web(),
+            Session::builder("db", "db.example.com", "me").build(),
+        ];
+        let remote = ["uptime".to_string()];
+
+        let mut failed = run_exec(sessions, &runner, &remote, OutputStyle::Prefix, 2, None);
+        failed.sort();
+        assert_eq!(failed, ["db", "web"]);
+        let mut commands = runner.commands.lock().unwrap().clone();
+        commands.sort();
+        assert_eq!(
+            commands,
+            [
+                "ssh -p 22 -o BatchMode=yes deploy@web.example.com uptime",
+                "ssh -p 22 -o BatchMode=yes me@db.example.com uptime"
+            ]
+        );
+    }
+}
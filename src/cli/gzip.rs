@@ -0,0 +1,77 @@
+//! Transparent gzip for `se import --input x.gz` and `se export --output x.gz`.
+
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Whether `path` names a gzip file by its `.gz` extension.
+pub fn is_gzip_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Read `path` as text, decompressing it first when it has a `.gz` extension or
+/// starts with the gzip magic bytes.
+pub fn read_input(path: &Path) -> Result<String> {
+    let raw = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if !is_gzip_path(path) && !raw.starts_with(&GZIP_MAGIC) {
+        return String::from_utf8(raw)
+            .with_context(|| format!("{} is not valid UTF-8", path.display()));
+    }
+    let mut content = String::new();
+    GzDecoder::new(raw.as_slice())
+        .read_to_string(&mut content)
+        .with_context(|| format!("failed to decompress {}", path.display()))?;
+    Ok(content)
+}
+
+/// Write `content` to `path`, gzip-compressed when it has a `.gz` extension.
+pub fn write_output(path: &Path, content: &str) -> Result<()> {
+    let bytes = if is_gzip_path(path) {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(content.as_bytes())
+            .and_then(|()| encoder.finish())
+            .context("failed to compress export")?
+    } else {
+        content.as_bytes().to_vec()
+    };
+    std::fs::write(path, bytes).with_context(|| format!("failed to write to {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn round_trips_through_a_gzip_file() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.json.gz");
+        let content = r#"{"sessions": []}"#;
+
+        write_output(&path, content).expect("write");
+        let raw = std::fs::read(&path).expect("read");
+        assert!(raw.starts_with(&GZIP_MAGIC));
+        assert_eq!(read_input(&path).expect("read back"), content);
+
+        // Recognized by its magic bytes even without the extension.
+        let renamed = dir.path().join("sessions.bak");
+        std::fs::rename(&path, &renamed).expect("rename");
+        assert_eq!(read_input(&renamed).expect("read renamed"), content);
+    }
+
+    #[test]
+    fn plain_files_are_unchanged() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.json");
+        write_output(&path, "[]").expect("write");
+        assert_eq!(std::fs::read(&path).expect("read"), b"[]");
+        assert_eq!(read_input(&path).expect("read back"), "[]");
+    }
+}
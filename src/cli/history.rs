@@ -0,0 +1,153 @@
+use anyhow::{Result, anyhow};
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Parse a `--since`/`--until` value into epoch seconds (UTC).
+///
+/// Accepts a calendar date (`2024-01-31`) or an age relative to `now` (`12h`, `7d`,
+/// `2w`). With `end_of_day`, a calendar date means the end of that day, so
+/// `--until 2024-01-31` still includes connections made on the 31st.
+pub fn parse_time_bound(input: &str, now: i64, end_of_day: bool) -> Result<i64> {
+    let input = input.trim();
+    let invalid = || {
+        anyhow!(
+            "invalid date '{}': expected YYYY-MM-DD or an age like 12h, 7d, 2w",
+            input
+        )
+    };
+
+    if let Some(unit) = input.chars().last()
+        && let Some(unit_secs) = match unit {
+            'h' => Some(3600),
+            'd' => Some(SECS_PER_DAY),
+            'w' => Some(7 * SECS_PER_DAY),
+            _ => None,
+        }
+    {
+        let amount: i64 = input[..input.len() - 1].parse().map_err(|_| invalid())?;
+        return Ok(now - amount.checked_mul(unit_secs).ok_or_else(invalid)?);
+    }
+
+    let parts: Vec<&str> = input.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    let (Ok(year), Ok(month), Ok(day)) = (year.parse(), month.parse(), day.parse()) else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return Err(invalid());
+    }
+    let start = days_from_civil(year, month, day) * SECS_PER_DAY;
+    Ok(if end_of_day {
+        start + SECS_PER_DAY - 1
+    } else {
+        start
+    })
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC.
+pub fn format_timestamp(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(SECS_PER_DAY);
+    let secs = timestamp.rem_euclid(SECS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Calendar conversions from Howard Hinnant's `days_from_civil`/`civil_from_days`.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_calendar_dates_as_utc_days() {
+        assert_eq!(parse_time_bound("1970-01-01", 0, false).unwrap(), 0);
+        assert_eq!(
+            parse_time_bound("2024-02-01", 0, false).unwrap(),
+            1_706_745_600
+        );
+        assert_eq!(
+            parse_time_bound("2024-02-01", 0, true).unwrap(),
+            1_706_745_600 + SECS_PER_DAY - 1
+        );
+        assert_eq!(
+            parse_time_bound("2024-02-29", 0, false).unwrap(),
+            1_709_164_800
+        );
+    }
+
+    #[test]
+    fn parses_relative_ages() {
+        let now = 1_000_000;
+        assert_eq!(parse_time_bound("7d", now, false).unwrap(), now - 604_800);
+        assert_eq!(parse_time_bound("12h", now, true).unwrap(), now - 43_200);
+        assert_eq!(parse_time_bound("2w", now, false).unwrap(), now - 1_209_600);
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for input in [
+            "",
+            "yesterday",
+            "2024-13-01",
+            "2023-02-29",
+            "2024-01",
+            "xd",
+            "7y",
+        ] {
+            let err = parse_time_bound(input, 0, false).unwrap_err().to_string();
+            assert!(err.contains("expected YYYY-MM-DD"), "{input}: {err}");
+        }
+    }
+
+    #[test]
+    fn formats_timestamps_round_trip() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(1_709_164_800 + 3_660), "2024-02-29 01:01");
+        for date in ["1999-12-31", "2000-03-01", "2038-01-19"] {
+            let timestamp = parse_time_bound(date, 0, false).unwrap();
+            assert_eq!(format_timestamp(timestamp), format!("{date} 00:00"));
+        }
+    }
+}
@@ -1,20 +1,61 @@
+mod doctor;
+mod exec;
+mod gzip;
+mod history;
 mod output;
+mod retry;
 mod theme;
 mod theme_cmd;
+mod validate;
 
-use crate::model::PasswdUnsafeMode;
-use crate::ssh::{AuthConfig, SshConnection};
+/// Print an informational message to stdout unless `--quiet` was given.
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        if !output::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
 
-use crate::model::Session;
+use crate::model::PasswdUnsafeMode;
+use crate::ssh::{AuthConfig, SshConnection, probe_tcp};
+
+use crate::clock::now_epoch_seconds;
+use crate::config_check;
+use crate::dirs::{self, CONFIG_DIR_ENV};
+use crate::export::{
+    CsvColumn, ExportFormat, ExportGroupBy, ExportSort, SshConfigStyle, SshConfigTemplate,
+    anonymize_export_sessions, export_to_csv, export_to_csv_columns, export_to_json,
+    export_to_ssh_config_with, export_to_yaml, push_ssh_config_host_with, sanitize_export_sessions,
+};
+use crate::import::{
+    import_from_json, import_from_json_lenient, import_from_ssh_config, import_from_yaml,
+};
+use crate::logging;
+use crate::model::{
+    Session, expand_home, format_duration, session_name_from_host, unique_session_name,
+};
+use crate::openssh::{self, ScpOptions};
+use crate::parallel::resolve_parallelism;
 use crate::password;
-use crate::store::{JsonFileStore, resolve_store_path};
+use crate::runner::{CommandRunner, InterruptGuard, SystemRunner};
+use crate::store::{
+    ConnectionEvent, ConnectionKind, DEFAULT_PROFILE, EnvSessionStore, SESSIONS_ENV,
+    STORE_FORMAT_ENV, SessionStore, StoreConfig, StoreFormat, StoreOpener, default_store_file,
+    resolve_store_path,
+};
 use crate::ui;
 use anyhow::{Context, Result, anyhow};
 use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::{Shell, generate};
+use retry::Retry;
+use std::collections::HashSet;
 use std::io;
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Parser)]
 #[command(
@@ -31,29 +72,86 @@ struct Cli {
     ui_config: Option<PathBuf>,
     #[arg(long, env = "SSHER_CLI_CONFIG")]
     cli_config: Option<PathBuf>,
+    /// Fail on unrecognized keys in ui.json/cli.json instead of ignoring them
+    #[arg(long, global = true)]
+    strict_config: bool,
+    /// Directory holding sessions.json, ui.json, and cli.json (per-file overrides still win)
+    #[arg(long, global = true, value_name = "DIR", env = CONFIG_DIR_ENV)]
+    config_dir: Option<PathBuf>,
+    /// Use the named session profile instead of the default store
+    #[arg(long, global = true, value_name = "NAME", env = "SSHER_PROFILE")]
+    profile: Option<String>,
+    /// Suppress informational messages (data output and errors are unaffected)
+    #[arg(long, short = 'q', global = true)]
+    quiet: bool,
+    /// Log verbosity on stderr: off, error, warn, info, debug, trace (falls back to RUST_LOG)
+    #[arg(long, global = true, value_name = "LEVEL", value_parser = parse_log_level)]
+    log_level: Option<log::LevelFilter>,
+    /// Maximum number of concurrent network operations (default: number of CPUs)
+    #[arg(long, global = true, value_name = "N", env = "SSHER_PARALLEL")]
+    parallel: Option<usize>,
+    /// After an ssh session ends, print which session it was and how long it lasted
+    #[arg(long, visible_alias = "oneline", global = true, env = "SSHER_SUMMARY")]
+    summary: bool,
+    /// Store backend (json or sqlite), overriding the store file's extension; without
+    /// --store-path it also names the default store (sessions.json or sessions.db)
+    #[arg(long, global = true, value_enum, value_name = "FORMAT")]
+    store_format: Option<StoreFormat>,
+    /// Encrypt the store file with a passphrase (from SSHER_PASSPHRASE or a prompt);
+    /// stores that are already encrypted are detected without it
+    #[arg(long, global = true)]
+    encrypted: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     Add(AddArgs),
     Update(UpdateArgs),
-    List,
+    /// Add or remove tags without replacing the rest
+    Tag(TagArgs),
+    /// Copy a session under a new name, without its connection history
+    Clone(CloneArgs),
+    List(ListArgs),
     Export(ExportArgs),
     Import(ImportArgs),
     Remove(RemoveArgs),
     RemovePassword(RemovePasswordArgs),
-    Tui,
+    Tui(TuiArgs),
+    /// Connect to a session over ssh without opening the TUI
+    #[command(visible_alias = "connect")]
     Go(GoArgs),
+    /// Connect to the most recently connected session
+    Last,
     Scp(ScpArgs),
+    /// Open ad-hoc port forwards to a session without saving them
+    Tunnel(TunnelArgs),
+    /// Run a command over ssh on several sessions at once
+    Exec(ExecArgs),
+    /// Close shared ControlMaster connections
+    Disconnect(DisconnectArgs),
+    /// Show how long connections to each session last, longest average first
+    Stats,
+    /// List sessions by when they were last connected to
+    History(HistoryArgs),
+    /// Check stored sessions for placeholder names and duplicates
+    Doctor,
+    /// Show a session's identity file and the other files in its directory
+    Identity(IdentityArgs),
     Completions(CompletionsArgs),
     Theme(ThemeArgs),
     Config(ConfigArgs),
+    Store(StoreArgs),
+    Profile(ProfileArgs),
+    /// Show which store, config, and completion files ssher uses
+    #[command(visible_alias = "whereis")]
+    Paths,
 }
 
 #[derive(Args)]
 struct AddArgs {
+    /// Session name (defaults to the first label of --host, made unique with -2, -3, ...)
     #[arg(long)]
-    name: String,
+    name: Option<String>,
     #[arg(long)]
     host: String,
     #[arg(long)]
@@ -76,6 +174,102 @@ struct AddArgs {
     /// Password storage mode: normal (keyring), bare (plaintext), simple (XOR encoded)
     #[arg(long, value_name = "MODE", value_enum)]
     passwd_mode: Option<PasswdModeArg>,
+    /// Check that the host answers on its SSH port before saving (warns on failure)
+    #[arg(long)]
+    test: bool,
+    /// Like --test, but refuse to save an unreachable session
+    #[arg(long)]
+    require_reachable: bool,
+    /// Share one connection per host via ssh ControlMaster (uses the OpenSSH client)
+    #[arg(long)]
+    control_master: bool,
+    /// ControlPath template (default ~/.ssh/ssher-%r@%h:%p)
+    #[arg(long, value_name = "TEMPLATE", requires = "control_master")]
+    control_path: Option<String>,
+    /// Jump host to connect through, as [user@]host[:port] (uses the OpenSSH client)
+    #[arg(long, value_name = "HOST")]
+    proxy_jump: Option<String>,
+    /// Local folder used as the default scp local path
+    #[arg(long, value_name = "DIR")]
+    default_local_dir: Option<String>,
+    /// Compress this session's scp transfers by default
+    #[arg(long)]
+    scp_compress: bool,
+    /// Extra ssh option passed as `-o KEY=VALUE` (repeatable; uses the OpenSSH client)
+    #[arg(long = "option", value_name = "KEY=VALUE", value_parser = parse_ssh_option)]
+    options: Vec<(String, String)>,
+    /// Raw argument passed to ssh before the destination, one per flag, e.g.
+    /// `--ssh-arg=-A` (repeatable; uses the OpenSSH client)
+    #[arg(long = "ssh-arg", value_name = "ARG", allow_hyphen_values = true)]
+    ssh_args: Vec<String>,
+    /// Local port forward, as [BIND:]PORT:HOST:HOSTPORT (repeatable; uses the OpenSSH client)
+    #[arg(long = "local-forward", value_name = "SPEC", value_parser = parse_forward_spec)]
+    local_forwards: Vec<String>,
+    /// Remote port forward, as [BIND:]PORT:HOST:HOSTPORT (repeatable; uses the OpenSSH client)
+    #[arg(long = "remote-forward", value_name = "SPEC", value_parser = parse_forward_spec)]
+    remote_forwards: Vec<String>,
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct HistoryArgs {
+    #[command(subcommand)]
+    command: Option<HistoryCommand>,
+    /// Only connections at or after this date (YYYY-MM-DD, UTC) or age (12h, 7d, 2w)
+    #[arg(long, value_name = "WHEN")]
+    since: Option<String>,
+    /// Only connections up to the end of this date, or older than this age
+    #[arg(long, value_name = "WHEN")]
+    until: Option<String>,
+    /// List every logged connect/scp instead of each session's latest
+    /// (needs `se config set log_connections true`)
+    #[arg(long)]
+    log: bool,
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Trim the connection log (including rotated files) to its newest entries
+    Prune {
+        /// Number of entries to keep
+        #[arg(long, value_name = "N")]
+        keep: usize,
+    },
+}
+
+#[derive(Args)]
+struct IdentityArgs {
+    #[arg(long)]
+    name: String,
+}
+
+#[derive(Args)]
+struct ExecArgs {
+    /// Run on every session with this tag
+    #[arg(long, value_name = "TAG", required_unless_present = "names")]
+    tag: Option<String>,
+    /// Run on this session (repeat for several)
+    #[arg(long = "name", value_name = "NAME")]
+    names: Vec<String>,
+    /// Start every output line with [name] (the default)
+    #[arg(long, conflicts_with = "block")]
+    prefix: bool,
+    /// Print each session's output together under a header
+    #[arg(long)]
+    block: bool,
+    /// Command to run, after `--`
+    #[arg(last = true, required = true, value_name = "COMMAND")]
+    command: Vec<String>,
+}
+
+#[derive(Args)]
+struct DisconnectArgs {
+    /// Session whose shared connection to close
+    #[arg(long, required_unless_present = "all", conflicts_with = "all")]
+    name: Option<String>,
+    /// Close the shared connections of every ControlMaster session
+    #[arg(long)]
+    all: bool,
 }
 
 /// CLI-friendly password mode enum
@@ -112,12 +306,36 @@ struct RemovePasswordArgs {
 struct GoArgs {
     #[arg(long)]
     name: String,
+    /// Try again up to N times while the host cannot be reached (not on auth failures)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    retry: u32,
+    /// Seconds to wait before the first retry; the wait doubles after each one
+    #[arg(long, value_name = "SECS", default_value_t = 2)]
+    retry_delay: u64,
+}
+
+#[derive(Args)]
+struct TunnelArgs {
+    #[arg(long)]
+    name: String,
+    /// Local forward, as [BIND:]PORT:HOST:HOSTPORT (repeatable)
+    #[arg(long, short = 'L', value_name = "SPEC", value_parser = parse_forward_spec)]
+    local: Vec<String>,
+    /// Remote forward, as [BIND:]PORT:HOST:HOSTPORT (repeatable)
+    #[arg(long, short = 'R', value_name = "SPEC", value_parser = parse_forward_spec)]
+    remote: Vec<String>,
+    /// Only forward ports, without a remote shell (ssh -N); stop with Ctrl-C
+    #[arg(short = 'N', long = "no-shell")]
+    no_shell: bool,
 }
 
 #[derive(Args)]
 struct UpdateArgs {
     #[arg(long)]
     name: String,
+    /// Rename the session (other updates are applied in the same call)
+    #[arg(long, value_name = "NAME")]
+    new_name: Option<String>,
     #[arg(long)]
     host: Option<String>,
     #[arg(long)]
@@ -140,6 +358,113 @@ struct UpdateArgs {
     /// Password storage mode: normal (keyring), bare (plaintext), simple (XOR encoded)
     #[arg(long, value_name = "MODE", value_enum)]
     passwd_mode: Option<PasswdModeArg>,
+    /// Turn ControlMaster connection sharing on or off
+    #[arg(long, value_name = "BOOL")]
+    control_master: Option<bool>,
+    /// ControlPath template used when ControlMaster is on
+    #[arg(long, value_name = "TEMPLATE")]
+    control_path: Option<String>,
+    /// Jump host to connect through ("" clears it)
+    #[arg(long, value_name = "HOST")]
+    proxy_jump: Option<String>,
+    /// Local folder used as the default scp local path ("" clears it)
+    #[arg(long, value_name = "DIR")]
+    default_local_dir: Option<String>,
+    /// Turn default scp compression on or off
+    #[arg(long, value_name = "BOOL")]
+    scp_compress: Option<bool>,
+    /// Add or replace an extra ssh option, as KEY=VALUE (repeatable)
+    #[arg(long = "option", value_name = "KEY=VALUE", value_parser = parse_ssh_option)]
+    options: Vec<(String, String)>,
+    /// Drop an extra ssh option by KEY (repeatable)
+    #[arg(long = "remove-option", value_name = "KEY")]
+    remove_options: Vec<String>,
+    /// Replace the raw ssh arguments, one per flag (repeatable; a single "" clears them)
+    #[arg(long = "ssh-arg", value_name = "ARG", allow_hyphen_values = true)]
+    ssh_args: Vec<String>,
+    /// Replace the local port forwards (repeatable; a single "" clears them)
+    #[arg(long = "local-forward", value_name = "SPEC", value_parser = parse_forward_spec)]
+    local_forwards: Vec<String>,
+    /// Replace the remote port forwards (repeatable; a single "" clears them)
+    #[arg(long = "remote-forward", value_name = "SPEC", value_parser = parse_forward_spec)]
+    remote_forwards: Vec<String>,
+    /// Replace a session already named --new-name, keeping its connection history
+    #[arg(long, requires = "new_name")]
+    force: bool,
+}
+
+#[derive(Args)]
+struct TagArgs {
+    #[command(subcommand)]
+    command: TagCommand,
+}
+
+#[derive(Subcommand)]
+enum TagCommand {
+    /// Add tags to a session, skipping ones it already has
+    Add(TagEditArgs),
+    /// Remove tags from a session, ignoring ones it does not have
+    Remove(TagEditArgs),
+}
+
+#[derive(Args)]
+struct TagEditArgs {
+    #[arg(long)]
+    name: String,
+    #[arg(
+        long = "tag",
+        value_name = "TAG",
+        value_delimiter = ',',
+        required = true
+    )]
+    tags: Vec<String>,
+}
+
+#[derive(Args)]
+struct CloneArgs {
+    /// Session to copy
+    #[arg(long)]
+    name: String,
+    /// Name of the new session
+    #[arg(long, value_name = "NAME")]
+    new_name: String,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    /// Separate columns with tabs instead of aligning them
+    #[arg(long)]
+    tabs: bool,
+    /// Order rows by name, most recent connection, or connection count
+    #[arg(long, value_enum, default_value = "name")]
+    sort: ListSort,
+    /// List sessions under a header per tag
+    #[arg(long, value_enum, conflicts_with = "tabs")]
+    group_by: Option<ExportGroupBy>,
+    /// With --group-by tag, list each session under its first tag only
+    #[arg(long, requires = "group_by")]
+    first_tag_only: bool,
+    /// Only list sessions carrying this tag (repeat to require several)
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ListSort {
+    Name,
+    Recent,
+    Frequency,
+}
+
+impl ListSort {
+    /// The TUI ordering this sort shares, so both rank sessions the same way.
+    fn order_mode(self) -> ui::config::SessionOrderMode {
+        match self {
+            ListSort::Name => ui::config::SessionOrderMode::Alphabetical,
+            ListSort::Recent => ui::config::SessionOrderMode::LatestFirst,
+            ListSort::Frequency => ui::config::SessionOrderMode::FrequencyBased,
+        }
+    }
 }
 
 #[derive(Args)]
@@ -148,6 +473,85 @@ struct ExportArgs {
     format: ExportFormat,
     #[arg(long, value_name = "PATH")]
     output: Option<PathBuf>,
+    /// Write one file per session into this directory (ssh-config, json and yaml only)
+    #[arg(long, value_name = "DIR", conflicts_with = "output")]
+    output_dir: Option<PathBuf>,
+    /// Group ssh-config hosts under comment headers (ssh-config only)
+    #[arg(long, value_enum, value_name = "KEY")]
+    group_by: Option<ExportGroupBy>,
+    /// Sort hosts within each group by this key; a bare --sort sorts by name
+    /// (ssh-config only)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "KEY",
+        num_args = 0..=1,
+        default_missing_value = "name"
+    )]
+    sort: Option<ExportSort>,
+    /// Append hosts to an existing ssh-config --output file, skipping names already present
+    #[arg(long, requires = "output")]
+    append: bool,
+    /// Overwrite the --output file if it already exists (with --append: replace existing hosts)
+    #[arg(long, short = 'f')]
+    force: bool,
+    /// Replace hosts, users, paths, forwards, and ssh option values with placeholders
+    #[arg(long)]
+    anonymize: bool,
+    /// Comma-separated CSV columns to include, in order (csv only)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+    columns: Vec<CsvColumn>,
+    /// Put the export on the system clipboard instead of stdout
+    #[arg(long, conflicts_with_all = ["output", "output_dir"])]
+    clipboard: bool,
+    /// Host stanza layout: full, or minimal without defaults and comments (ssh-config only)
+    #[arg(long, value_enum, default_value = "full")]
+    template: SshConfigTemplate,
+    /// Comment line at the top of the file; "" writes none (ssh-config only)
+    #[arg(long, value_name = "TEXT")]
+    header: Option<String>,
+    /// Leave out the `# Tags:` comment under each host (ssh-config only)
+    #[arg(long)]
+    no_tags_comment: bool,
+    /// Write `Port 22` even with --template minimal (ssh-config only)
+    #[arg(long)]
+    include_port_always: bool,
+    /// Write `HostName` even when it equals the Host name, with --template minimal
+    /// (ssh-config only)
+    #[arg(long)]
+    include_hostname_always: bool,
+}
+
+impl ExportArgs {
+    fn ssh_config_style(&self) -> SshConfigStyle {
+        let mut style = SshConfigStyle::from(self.template);
+        if let Some(header) = &self.header {
+            style.header = (!header.is_empty()).then(|| header.clone());
+        }
+        if self.no_tags_comment {
+            style.tags_comment = false;
+        }
+        if self.include_port_always {
+            style.always_port = true;
+        }
+        if self.include_hostname_always {
+            style.always_hostname = true;
+        }
+        style
+    }
+}
+
+#[derive(Args, Default)]
+struct TuiArgs {
+    /// Start with the session list filtered by this search text
+    #[arg(long, value_name = "TEXT")]
+    filter: Option<String>,
+    /// Show only sessions carrying this exact tag (press T in the TUI to clear)
+    #[arg(long, value_name = "TAG")]
+    tag: Option<String>,
+    /// Start with this session selected, so Enter connects right away
+    #[arg(long, value_name = "NAME")]
+    select: Option<String>,
 }
 
 #[derive(Args)]
@@ -162,18 +566,18 @@ struct ImportArgs {
         help = "Force override existing sessions with same name"
     )]
     force: bool,
-}
-
-#[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
-enum ExportFormat {
-    Json,
-    Csv,
-    SshConfig,
+    /// Accept JSON objects missing `user` or `port`, filling in $USER and 22
+    #[arg(long)]
+    lenient: bool,
+    /// Import into this profile's store instead of the active one
+    #[arg(long, value_name = "NAME")]
+    into_profile: Option<String>,
 }
 
 #[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
 enum ImportFormat {
     Json,
+    Yaml,
     SshConfig,
 }
 
@@ -181,14 +585,25 @@ enum ImportFormat {
 struct ScpArgs {
     #[arg(long)]
     name: String,
+    /// Local path (defaults to the session's default local dir); repeat to upload
+    /// several files into the --remote directory
     #[arg(long, value_name = "PATH")]
-    local: PathBuf,
+    local: Vec<PathBuf>,
     #[arg(long, value_name = "PATH")]
     remote: PathBuf,
     #[arg(long, value_enum, default_value = "to")]
     direction: ScpDirection,
     #[arg(long)]
     recursive: bool,
+    /// Keep modification times and modes (scp -p)
+    #[arg(long)]
+    preserve: bool,
+    /// Compress the transfer (scp -C); on by default for sessions with --scp-compress
+    #[arg(long)]
+    compress: bool,
+    /// Cap bandwidth at this many KB/s (scp -l)
+    #[arg(long, value_name = "KBPS", value_parser = clap::value_parser!(u32).range(1..))]
+    limit: Option<u32>,
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -242,72 +657,229 @@ struct ConfigArgs {
 enum ConfigCommand {
     /// Set a configuration value
     Set {
-        /// Configuration key (passwd_unsafe_mode, passwd_unsafe_key)
+        /// Configuration key (passwd_unsafe_mode, passwd_unsafe_key, log_connections,
+        /// log_max_entries, log_max_bytes)
         key: String,
         /// Configuration value
         value: String,
     },
     /// Get a configuration value
     Get {
-        /// Configuration key (passwd_unsafe_mode, passwd_unsafe_key)
+        /// Configuration key (passwd_unsafe_mode, passwd_unsafe_key, log_connections,
+        /// log_max_entries, log_max_bytes)
         key: String,
     },
     /// Show all configuration values
     List,
+    /// Check the UI and CLI config files for unknown keys, bad values, and unknown colors
+    Validate {
+        /// UI config to check (default: the resolved ui.json)
+        #[arg(long, value_name = "PATH")]
+        ui_config: Option<PathBuf>,
+        /// CLI config to check (default: the resolved cli.json)
+        #[arg(long, value_name = "PATH")]
+        cli_config: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct StoreArgs {
+    #[command(subcommand)]
+    command: StoreCommand,
+}
+
+#[derive(Subcommand)]
+enum StoreCommand {
+    /// Remove duplicate session names, keeping the first entry
+    Repair,
+}
+
+#[derive(Args)]
+struct ProfileArgs {
+    #[command(subcommand)]
+    command: ProfileCommand,
+}
+
+#[derive(Subcommand)]
+enum ProfileCommand {
+    /// Copy one profile's store to another profile name
+    Clone {
+        /// Source profile
+        #[arg(long)]
+        from: String,
+        /// Target profile
+        #[arg(long)]
+        to: String,
+        /// Overwrite the target profile if it already exists
+        #[arg(long, short = 'f')]
+        force: bool,
+    },
+}
+
+fn parse_ssh_option(value: &str) -> std::result::Result<(String, String), String> {
+    openssh::parse_option(value)
+        .ok_or_else(|| format!("invalid ssh option '{}' (expected KEY=VALUE)", value))
+}
+
+/// A `-L`/`-R` spec; the empty string is let through so `update` can clear the list.
+fn parse_forward_spec(value: &str) -> std::result::Result<String, String> {
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+    openssh::validate_forward(value)
+        .map(|()| value.to_string())
+        .map_err(|err| format!("invalid forward '{}': {}", value, err))
+}
+
+fn parse_log_level(value: &str) -> std::result::Result<log::LevelFilter, String> {
+    logging::parse_level(value).ok_or_else(|| format!("invalid log level '{}'", value))
 }
 
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
+    output::set_quiet(cli.quiet);
+    output::set_connection_summary(cli.summary);
+    logging::init(cli.log_level);
+    config_check::set_strict(cli.strict_config);
+    if let Some(dir) = cli.config_dir.clone() {
+        dirs::set_config_dir_override(dir);
+    }
+    // Resolved up front but only checked by commands that use the store
+    let store_base = base_store_path(&cli);
 
     match cli.command {
         Some(Commands::Completions(args)) => {
             generate_completions(args.shell);
             Ok(())
         }
+        Some(Commands::Profile(args)) => handle_profile_command(Some(store_base?), args),
+        Some(Commands::Paths) => print_paths(&cli),
+        Some(Commands::Config(ConfigArgs {
+            command:
+                ConfigCommand::Validate {
+                    ui_config,
+                    cli_config,
+                },
+        })) => validate_configs(ui_config.or(cli.ui_config), cli_config.or(cli.cli_config)),
         _ => {
-            let store_path = resolve_store_path(cli.store_path)?;
-            let store = JsonFileStore::new(store_path);
+            let store_base = store_base?;
+            let store_path = resolve_store_path(Some(store_base.clone()), cli.profile.as_deref())?;
+            let opener = StoreOpener {
+                format: cli.store_format,
+                encrypted: cli.encrypted,
+                prompt: true,
+                ..StoreOpener::default()
+            };
+            let store: Box<dyn SessionStore> = match std::env::var(SESSIONS_ENV) {
+                Ok(content) => {
+                    log::debug!("using read-only sessions from {}", SESSIONS_ENV);
+                    Box::new(EnvSessionStore::from_json(&content)?)
+                }
+                Err(_) => opener.open(store_path)?,
+            };
+            let store = store.as_ref();
+            // Profiles sit next to the base store; an env store has none to switch to
+            let profiles = match std::env::var(SESSIONS_ENV) {
+                Ok(_) => None,
+                Err(_) => Some(TuiProfiles {
+                    base: store_base.clone(),
+                    active: cli.profile.clone(),
+                    opener: opener.clone(),
+                }),
+            };
 
             match cli.command {
-                Some(Commands::Add(args)) => add_session(&store, args),
-                Some(Commands::Update(args)) => update_session(&store, args),
-                Some(Commands::List) => list_sessions(&store, cli.cli_config),
-                Some(Commands::Export(args)) => export_sessions(&store, args),
-                Some(Commands::Import(args)) => import_sessions(&store, args),
-                Some(Commands::Remove(args)) => remove_session(&store, &args.name),
-                Some(Commands::RemovePassword(args)) => remove_password(&store, &args.name),
-                Some(Commands::Tui) | None => {
-                    let ui_config = ui::load_ui_config(cli.ui_config)?;
-                    run_tui(&store, &ui_config)
-                }
-                Some(Commands::Go(args)) => run_go(&store, args),
-                Some(Commands::Scp(args)) => run_scp(&store, args),
+                Some(Commands::Add(args)) => add_session(store, args),
+                Some(Commands::Tag(args)) => handle_tag_command(store, args),
+                Some(Commands::Clone(args)) => clone_session(store, args),
+                Some(Commands::Update(args)) => update_session(store, args),
+                Some(Commands::List(args)) => list_sessions(store, cli.cli_config, args),
+                Some(Commands::Export(args)) => export_sessions(store, args),
+                Some(Commands::Import(args)) => match args.into_profile.clone() {
+                    Some(profile) => import_into_profile(Some(store_base), &profile, opener, args),
+                    None => import_sessions(store, args).map(|_| ()),
+                },
+                Some(Commands::Remove(args)) => remove_session(store, &args.name),
+                Some(Commands::RemovePassword(args)) => remove_password(store, &args.name),
+                Some(Commands::Tui(args)) => run_tui(
+                    store,
+                    cli.ui_config,
+                    resolve_parallelism(cli.parallel),
+                    profiles,
+                    args,
+                ),
+                None => run_tui(
+                    store,
+                    cli.ui_config,
+                    resolve_parallelism(cli.parallel),
+                    profiles,
+                    TuiArgs::default(),
+                ),
+                Some(Commands::Go(args)) => run_go(store, &SystemRunner, args),
+                Some(Commands::Last) => run_last(store, &SystemRunner),
+                Some(Commands::Scp(args)) => run_scp(store, &SystemRunner, args),
+                Some(Commands::Tunnel(args)) => run_tunnel(store, &SystemRunner, args),
+                Some(Commands::Exec(args)) => run_exec(
+                    store,
+                    &SystemRunner,
+                    cli.cli_config,
+                    resolve_parallelism(cli.parallel),
+                    args,
+                ),
+                Some(Commands::Disconnect(args)) => disconnect_sessions(store, &SystemRunner, args),
+                Some(Commands::Stats) => print_stats(store),
+                Some(Commands::History(args)) => print_history(store, args),
+                Some(Commands::Doctor) => run_doctor(store),
+                Some(Commands::Identity(args)) => show_identity(store, &args.name),
                 Some(Commands::Theme(args)) => handle_theme_command(args),
-                Some(Commands::Config(args)) => handle_config_command(&store, args),
-                Some(Commands::Completions(_)) => unreachable!(),
+                Some(Commands::Config(args)) => handle_config_command(store, args),
+                Some(Commands::Store(args)) => handle_store_command(store, args),
+                Some(Commands::Completions(_))
+                | Some(Commands::Profile(_))
+                | Some(Commands::Paths) => unreachable!(),
             }
         }
     }
 }
 
-fn add_session(store: &JsonFileStore, args: AddArgs) -> Result<()> {
+/// Drop the `""` entries that `update` takes as "clear this list".
+fn non_empty(values: Vec<String>) -> Vec<String> {
+    values
+        .into_iter()
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+fn add_session(store: &dyn SessionStore, args: AddArgs) -> Result<()> {
+    let name = match args.name {
+        Some(name) => name,
+        None => unique_session_name(&session_name_from_host(&args.host), &store.list()?),
+    };
     let mut session = Session {
-        name: args.name.clone(),
-        host: args.host,
-        user: args.user,
-        port: args.port,
         identity_file: args.identity_file,
-        tags: normalize_tags(args.tags),
-        last_connected_at: None,
-        has_stored_password: false,
-        passwd_unsafe_mode: None,
-        stored_password: None,
+        proxy_jump: args.proxy_jump.filter(|jump| !jump.is_empty()),
+        scp_compress: args.scp_compress,
+        default_local_dir: args.default_local_dir,
+        control_master: args.control_master,
+        control_path: args.control_path,
+        options: args.options.into_iter().collect(),
+        extra_args: args.ssh_args,
+        local_forwards: non_empty(args.local_forwards),
+        remote_forwards: non_empty(args.remote_forwards),
+        ..Session::builder(name, args.host, args.user)
+            .port(args.port)
+            .tags(normalize_tags(args.tags))
+            .build()
     };
 
     if args.password && args.no_password {
         return Err(anyhow!("Cannot specify both --password and --no-password"));
     }
 
+    if args.test || args.require_reachable {
+        check_reachable(&session, args.require_reachable)?;
+    }
+
     if args.no_password {
         session.has_stored_password = false;
     } else if args.password {
@@ -343,125 +915,362 @@ fn add_session(store: &JsonFileStore, args: AddArgs) -> Result<()> {
     }
 
     store.add(session.clone())?;
-    println!("Added session: {}", session.name);
+    notice!("Added session: {}", session.name);
     Ok(())
 }
 
-fn list_sessions(store: &JsonFileStore, cli_config: Option<PathBuf>) -> Result<()> {
-    let sessions = store.list()?;
+fn check_reachable(session: &Session, required: bool) -> Result<()> {
+    match probe_tcp(&session.host, session.port, PROBE_TIMEOUT) {
+        Ok(result) => {
+            match result.banner {
+                Some(banner) => notice!(
+                    "{}:{} reachable in {} ms ({})",
+                    session.host,
+                    session.port,
+                    result.latency.as_millis(),
+                    banner
+                ),
+                None => eprintln!(
+                    "Warning: {}:{} accepted a connection but sent no SSH banner",
+                    session.host, session.port
+                ),
+            }
+            Ok(())
+        }
+        Err(err) if required => Err(err.context("refusing to save unreachable session")),
+        Err(err) => {
+            eprintln!("Warning: {:#}", err);
+            Ok(())
+        }
+    }
+}
+
+fn list_sessions(
+    store: &dyn SessionStore,
+    cli_config: Option<PathBuf>,
+    args: ListArgs,
+) -> Result<()> {
+    let mut sessions = store.list()?;
+    sessions.retain(|session| args.tags.iter().all(|tag| session.tags.contains(tag)));
+    ui::sort_sessions(&mut sessions, args.sort.order_mode(), now_epoch_seconds());
     let theme = theme::load_cli_theme(cli_config)?;
-    output::print_sessions(&sessions, &theme);
+    match args.group_by {
+        Some(ExportGroupBy::Tag) => {
+            output::print_sessions_by_tag(&sessions, &theme, args.first_tag_only)
+        }
+        None => {
+            let layout = if args.tabs {
+                output::TableLayout::Tabs
+            } else {
+                output::TableLayout::Aligned
+            };
+            output::print_sessions(&sessions, &theme, layout)
+        }
+    }
     Ok(())
 }
 
-fn export_sessions(store: &JsonFileStore, args: ExportArgs) -> Result<()> {
+fn export_sessions(store: &dyn SessionStore, args: ExportArgs) -> Result<()> {
     let sessions = store.list()?;
-    let sanitized_sessions = sanitize_export_sessions(&sessions);
+    let mut sanitized_sessions = sanitize_export_sessions(&sessions);
+    if args.anonymize {
+        sanitized_sessions = anonymize_export_sessions(&sanitized_sessions);
+    }
+    if !args.columns.is_empty() && args.format != ExportFormat::Csv {
+        return Err(anyhow!("--columns supports only the csv format"));
+    }
+    let style = args.ssh_config_style();
+    if let Some(dir) = &args.output_dir {
+        return export_sessions_to_dir(&sanitized_sessions, &args.format, dir, &style);
+    }
+    if args.append {
+        if args.format != ExportFormat::SshConfig {
+            return Err(anyhow!("--append supports only the ssh-config format"));
+        }
+        if let Some(path) = &args.output {
+            if gzip::is_gzip_path(path) {
+                return Err(anyhow!("--append cannot write to a gzip file"));
+            }
+            return append_to_ssh_config(&sanitized_sessions, path, args.force, &style);
+        }
+    }
     let output = match args.format {
         ExportFormat::Json => export_to_json(&sanitized_sessions)?,
-        ExportFormat::Csv => export_to_csv(&sanitized_sessions),
-        ExportFormat::SshConfig => export_to_ssh_config(&sanitized_sessions),
+        ExportFormat::Yaml => export_to_yaml(&sanitized_sessions)?,
+        ExportFormat::Csv if args.columns.is_empty() => export_to_csv(&sanitized_sessions),
+        ExportFormat::Csv => export_to_csv_columns(&sanitized_sessions, &args.columns),
+        ExportFormat::SshConfig => export_to_ssh_config_with(
+            &sanitized_sessions,
+            args.group_by.as_ref(),
+            args.sort,
+            &style,
+        ),
     };
 
+    if args.clipboard {
+        match ui::clipboard::copy_to_clipboard(&output) {
+            Ok(helper) => notice!(
+                "Copied {} session{} to the clipboard (via {})",
+                sessions.len(),
+                if sessions.len() == 1 { "" } else { "s" },
+                helper
+            ),
+            Err(err) => {
+                eprintln!("Warning: {}; printing the export instead", err);
+                print!("{}", output);
+            }
+        }
+        return Ok(());
+    }
+
     if let Some(path) = args.output {
-        std::fs::write(&path, output)
-            .with_context(|| format!("failed to write to {}", path.display()))?;
-        println!("Exported {} sessions to {}", sessions.len(), path.display());
+        if path.exists() && !args.force && !confirm_overwrite(&path)? {
+            return Err(anyhow!(
+                "{} already exists (use --force to overwrite)",
+                path.display()
+            ));
+        }
+        gzip::write_output(&path, &output)?;
+        notice!("Exported {} sessions to {}", sessions.len(), path.display());
     } else {
         print!("{}", output);
     }
     Ok(())
 }
 
-fn sanitize_export_sessions(sessions: &[Session]) -> Vec<Session> {
-    sessions
-        .iter()
-        .cloned()
-        .map(|mut session| {
-            session.stored_password = None;
-            session
-        })
-        .collect()
+fn confirm_overwrite(path: &Path) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    // On stderr, so `se export > file` gets only the export.
+    eprint!("{} already exists. Overwrite? [y/N]: ", path.display());
+    io::Write::flush(&mut io::stderr())?;
+    let mut response = String::new();
+    io::stdin()
+        .read_line(&mut response)
+        .context("failed to read confirmation from stdin")?;
+    Ok(matches!(
+        response.trim().to_lowercase().as_str(),
+        "y" | "yes"
+    ))
 }
 
-fn export_to_json(sessions: &[Session]) -> Result<String> {
-    serde_json::to_string_pretty(sessions).context("failed to serialize sessions to JSON")
-}
+fn export_sessions_to_dir(
+    sessions: &[Session],
+    format: &ExportFormat,
+    dir: &Path,
+    style: &SshConfigStyle,
+) -> Result<()> {
+    let extension = match format {
+        ExportFormat::SshConfig => "conf",
+        ExportFormat::Json => "json",
+        ExportFormat::Yaml => "yaml",
+        ExportFormat::Csv => {
+            return Err(anyhow!(
+                "--output-dir supports only the ssh-config, json and yaml formats"
+            ));
+        }
+    };
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create directory {}", dir.display()))?;
 
-fn export_to_csv(sessions: &[Session]) -> String {
-    let mut csv = String::new();
-    csv.push_str("name,host,user,port,identity_file,tags\n");
     for session in sessions {
-        let identity = session
-            .identity_file
-            .as_ref()
-            .map(|p| p.display().to_string())
-            .unwrap_or_default();
-        let tags = session.tags.join(";");
-        csv.push_str(&format!(
-            "{},{},{},{},{},{}\n",
-            escape_csv(&session.name),
-            escape_csv(&session.host),
-            escape_csv(&session.user),
-            session.port,
-            escape_csv(&identity),
-            escape_csv(&tags)
-        ));
+        let single = std::slice::from_ref(session);
+        let content = match format {
+            ExportFormat::Json => export_to_json(single)?,
+            ExportFormat::Yaml => export_to_yaml(single)?,
+            _ => export_to_ssh_config_with(single, None, None, style),
+        };
+        let file_name = format!("{}.{}", session.name.replace(['/', '\\'], "_"), extension);
+        let path = dir.join(file_name);
+        std::fs::write(&path, content)
+            .with_context(|| format!("failed to write to {}", path.display()))?;
     }
-    csv
+    notice!("Exported {} sessions to {}", sessions.len(), dir.display());
+    Ok(())
 }
 
-fn escape_csv(s: &str) -> String {
-    if s.contains(',') || s.contains('"') || s.contains('\n') {
-        format!("\"{}\"", s.replace('"', "\"\""))
-    } else {
-        s.to_string()
-    }
-}
+fn append_to_ssh_config(
+    sessions: &[Session],
+    path: &Path,
+    replace: bool,
+    style: &SshConfigStyle,
+) -> Result<()> {
+    let mut content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+    let existing = ssh_config_host_names(&content);
 
-fn export_to_ssh_config(sessions: &[Session]) -> String {
-    let mut config = String::new();
-    config.push_str("# Generated by ssher\n\n");
+    let mut appended = String::new();
+    let mut added = Vec::new();
+    let mut updated = Vec::new();
+    let mut skipped = Vec::new();
     for session in sessions {
-        config.push_str(&format!("Host {}\n", session.name));
-        config.push_str(&format!("    HostName {}\n", session.host));
-        config.push_str(&format!("    User {}\n", session.user));
-        config.push_str(&format!("    Port {}\n", session.port));
-        if let Some(identity) = &session.identity_file {
-            config.push_str(&format!("    IdentityFile {}\n", identity.display()));
+        if existing.contains(&session.name) {
+            // Only single-name stanzas are replaced; shared `Host a b` lines are left alone.
+            match replace
+                .then(|| remove_ssh_config_host(&content, &session.name))
+                .flatten()
+            {
+                Some(rest) => {
+                    content = rest;
+                    updated.push(session.name.as_str());
+                }
+                None => {
+                    skipped.push(session.name.as_str());
+                    continue;
+                }
+            }
+        } else {
+            added.push(session.name.as_str());
         }
-        if !session.tags.is_empty() {
-            config.push_str(&format!("    # Tags: {}\n", session.tags.join(", ")));
+        push_ssh_config_host_with(&mut appended, session, style);
+    }
+
+    if !appended.is_empty() {
+        if !content.is_empty() {
+            let trimmed = content.trim_end_matches('\n').len();
+            content.truncate(trimmed);
+            content.push_str("\n\n");
         }
-        config.push('\n');
+        content.push_str(&appended);
+        std::fs::write(path, content)
+            .with_context(|| format!("failed to write to {}", path.display()))?;
+    }
+
+    notice!("Appended {} hosts to {}", added.len(), path.display());
+    if !updated.is_empty() {
+        notice!("Updated existing hosts: {}", updated.join(", "));
     }
-    config
+    if !skipped.is_empty() {
+        notice!(
+            "Skipped existing hosts: {}{}",
+            skipped.join(", "),
+            if replace {
+                " (shared Host lines are not replaced)"
+            } else {
+                " (use --force to replace them)"
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Splits an ssh_config line into its lowercased keyword and value.
+fn ssh_config_directive(line: &str) -> Option<(String, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (keyword, value) = line.split_once(|c: char| c.is_whitespace() || c == '=')?;
+    Some((
+        keyword.to_lowercase(),
+        value.trim_start_matches(|c: char| c.is_whitespace() || c == '='),
+    ))
+}
+
+fn ssh_config_host_names(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .filter_map(ssh_config_directive)
+        .filter(|(keyword, _)| keyword == "host")
+        .flat_map(|(_, value)| value.split_whitespace())
+        .filter(|pattern| !pattern.starts_with('!'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Removes the `Host <name>` stanza (a Host line naming only `name`), keeping any
+/// trailing comment block that belongs to the next stanza.
+fn remove_ssh_config_host(content: &str, name: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let is_section = |line: &str| {
+        ssh_config_directive(line)
+            .is_some_and(|(keyword, _)| keyword == "host" || keyword == "match")
+    };
+    let start = lines.iter().position(|line| {
+        ssh_config_directive(line).is_some_and(|(keyword, value)| {
+            keyword == "host" && value.split_whitespace().eq(std::iter::once(name))
+        })
+    })?;
+    let next = lines[start + 1..]
+        .iter()
+        .position(|line| is_section(line))
+        .map_or(lines.len(), |offset| start + 1 + offset);
+
+    let is_blank_or_comment = |line: &str| {
+        let line = line.trim();
+        line.is_empty() || line.starts_with('#')
+    };
+    let mut end = next;
+    while end > start + 1 && is_blank_or_comment(lines[end - 1]) {
+        end -= 1;
+    }
+    // Comments directly under the directives (e.g. `# Tags:`) belong to this stanza.
+    while end < next && lines[end].trim().starts_with('#') {
+        end += 1;
+    }
+    while end < next && lines[end].trim().is_empty() {
+        end += 1;
+    }
+
+    let mut rest: Vec<&str> = lines[..start].to_vec();
+    rest.extend_from_slice(&lines[end..]);
+    let mut output = rest.join("\n");
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    Some(output)
+}
+
+/// Run the normal import against another profile's store, leaving the active one
+/// untouched.
+fn import_into_profile(
+    store_path: Option<PathBuf>,
+    profile: &str,
+    opener: StoreOpener,
+    args: ImportArgs,
+) -> Result<()> {
+    let path = resolve_store_path(store_path, Some(profile))?;
+    if import_sessions(opener.open(path.clone())?.as_ref(), args)? > 0 {
+        notice!("Wrote profile '{}' ({})", profile, path.display());
+    }
+    Ok(())
 }
 
-fn import_sessions(store: &JsonFileStore, args: ImportArgs) -> Result<()> {
-    let input_content = std::fs::read_to_string(&args.input)
-        .with_context(|| format!("failed to read {}", args.input.display()))?;
+/// Returns how many sessions were written, which is zero when every conflict was
+/// skipped and nothing else was new.
+fn import_sessions(store: &dyn SessionStore, args: ImportArgs) -> Result<usize> {
+    let input_content = gzip::read_input(&args.input)?;
 
     let imported_sessions = match args.format {
+        ImportFormat::Json if args.lenient => import_from_json_lenient(&input_content)?,
         ImportFormat::Json => import_from_json(&input_content)?,
+        ImportFormat::Yaml => import_from_yaml(&input_content)?,
         ImportFormat::SshConfig => import_from_ssh_config(&input_content)?,
     };
 
     let existing_sessions = store.list()?;
     let existing_names: std::collections::HashSet<String> =
         existing_sessions.iter().map(|s| s.name.clone()).collect();
+    let mut written = 0;
 
     if args.force {
         // Force mode: override existing sessions
         for session in &imported_sessions {
             if existing_names.contains(&session.name) {
-                println!("Overriding existing session: {}", session.name);
+                notice!("Overriding existing session: {}", session.name);
             }
         }
         for session in &imported_sessions {
             store.add(session.clone())?;
         }
-        println!("Imported {} sessions", imported_sessions.len());
+        written = imported_sessions.len();
+        notice!("Imported {} sessions", written);
     } else {
         // Interactive mode: handle conflicts
         let mut conflicts: Vec<Session> = Vec::new();
@@ -478,7 +1287,8 @@ fn import_sessions(store: &JsonFileStore, args: ImportArgs) -> Result<()> {
         // Import non-conflicting sessions
         for session in &to_import {
             store.add(session.clone())?;
-            println!("Imported: {}", session.name);
+            written += 1;
+            notice!("Imported: {}", session.name);
         }
 
         // Handle conflicts
@@ -507,6 +1317,7 @@ fn import_sessions(store: &JsonFileStore, args: ImportArgs) -> Result<()> {
                 "o" => {
                     for session in &conflicts {
                         store.add(session.clone())?;
+                        written += 1;
                         println!("Overridden: {}", session.name);
                     }
                 }
@@ -527,6 +1338,7 @@ fn import_sessions(store: &JsonFileStore, args: ImportArgs) -> Result<()> {
                         match response.as_str() {
                             "y" | "yes" => {
                                 store.add(session.clone())?;
+                                written += 1;
                                 println!("Overridden: {}", session.name);
                             }
                             "s" => {
@@ -553,136 +1365,48 @@ fn import_sessions(store: &JsonFileStore, args: ImportArgs) -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(written)
 }
 
-fn import_from_json(content: &str) -> Result<Vec<Session>> {
-    serde_json::from_str(content).context("failed to parse JSON")
+fn remove_session(store: &dyn SessionStore, name: &str) -> Result<()> {
+    store.remove(name)?;
+    notice!("Removed session: {}", name);
+    Ok(())
 }
 
-fn import_from_ssh_config(content: &str) -> Result<Vec<Session>> {
-    let mut sessions = Vec::new();
-    let mut current_host: Option<String> = None;
-    let mut current_user = "root".to_string();
-    let mut current_hostname: Option<String> = None;
-    let mut current_port = 22u16;
-    let mut current_identity: Option<PathBuf> = None;
-
-    for line in content.lines() {
-        let line = line.trim();
-
-        // Skip comments and empty lines
-        if line.is_empty() || line.starts_with('#') {
-            continue;
+fn remove_password(store: &dyn SessionStore, name: &str) -> Result<()> {
+    let config = store.get_config()?;
+    let mut had_password = false;
+    store.modify(name, &mut |session| {
+        had_password = session.has_stored_password;
+        if !had_password {
+            return Ok(());
         }
 
-        let parts: Vec<&str> = line.splitn(2, ' ').collect();
-        if parts.len() < 2 {
-            continue;
-        }
+        // Determine the effective mode to know where to delete from
+        let effective_mode = session
+            .passwd_unsafe_mode
+            .as_ref()
+            .unwrap_or(&config.passwd_unsafe_mode);
 
-        let keyword = parts[0].to_lowercase();
-        let value = parts[1].trim();
-
-        match keyword.as_str() {
-            "host" => {
-                // Save previous host if exists
-                if let Some(name) = current_host.take()
-                    && let Some(hostname) = current_hostname.take()
-                {
-                    sessions.push(Session {
-                        name,
-                        host: hostname,
-                        user: current_user.clone(),
-                        port: current_port,
-                        identity_file: current_identity.take(),
-                        tags: vec![],
-                        last_connected_at: None,
-                        has_stored_password: false,
-                        passwd_unsafe_mode: None,
-                        stored_password: None,
-                    });
-                }
-                current_host = Some(value.to_string());
-                current_user = "root".to_string();
-                current_port = 22;
-                current_identity = None;
-            }
-            "user" => {
-                current_user = value.to_string();
-            }
-            "hostname" => {
-                current_hostname = Some(value.to_string());
-            }
-            "port" => {
-                current_port = value.parse().unwrap_or(22);
+        match effective_mode {
+            PasswdUnsafeMode::Normal => {
+                password::delete_password(name)?;
             }
-            "identityfile" => {
-                current_identity = Some(PathBuf::from(value));
+            PasswdUnsafeMode::Bare | PasswdUnsafeMode::Simple => {
+                // Just clear the stored_password field
+                session.stored_password = None;
             }
-            _ => {}
         }
-    }
 
-    // Save last host
-    if let Some(name) = current_host.take()
-        && let Some(hostname) = current_hostname.take()
-    {
-        sessions.push(Session {
-            name,
-            host: hostname,
-            user: current_user.clone(),
-            port: current_port,
-            identity_file: current_identity.take(),
-            tags: vec![],
-            last_connected_at: None,
-            has_stored_password: false,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        });
+        session.has_stored_password = false;
+        Ok(())
+    })?;
+    if had_password {
+        notice!("Removed password for session: {}", name);
+    } else {
+        notice!("Session '{}' does not have a stored password", name);
     }
-
-    Ok(sessions)
-}
-
-fn remove_session(store: &JsonFileStore, name: &str) -> Result<()> {
-    store.remove(name)?;
-    println!("Removed session: {}", name);
-    Ok(())
-}
-
-fn remove_password(store: &JsonFileStore, name: &str) -> Result<()> {
-    let mut sessions = store.list()?;
-    let session = sessions
-        .iter_mut()
-        .find(|s| s.name == name)
-        .ok_or_else(|| anyhow!("session '{}' not found", name))?;
-
-    if !session.has_stored_password {
-        println!("Session '{}' does not have a stored password", name);
-        return Ok(());
-    }
-
-    // Determine the effective mode to know where to delete from
-    let config = store.get_config()?;
-    let effective_mode = session
-        .passwd_unsafe_mode
-        .as_ref()
-        .unwrap_or(&config.passwd_unsafe_mode);
-
-    match effective_mode {
-        PasswdUnsafeMode::Normal => {
-            password::delete_password(name)?;
-        }
-        PasswdUnsafeMode::Bare | PasswdUnsafeMode::Simple => {
-            // Just clear the stored_password field
-            session.stored_password = None;
-        }
-    }
-
-    session.has_stored_password = false;
-    store.update(session.clone())?;
-    println!("Removed password for session: {}", name);
     Ok(())
 }
 
@@ -701,37 +1425,148 @@ fn try_store_password(session_name: &str, password_value: &str) -> Result<bool>
     }
 }
 
-fn update_session(store: &JsonFileStore, args: UpdateArgs) -> Result<()> {
-    let mut sessions = store.list()?;
-    let session = sessions
-        .iter_mut()
+fn update_session(store: &dyn SessionStore, args: UpdateArgs) -> Result<()> {
+    if args.password && args.no_password {
+        return Err(anyhow!("Cannot specify both --password and --no-password"));
+    }
+    let config = store.get_config()?;
+    // Asked before the store is locked, so other `se` runs do not wait on typing.
+    let new_password = if args.password {
+        Some(prompt_password_value(format!(
+            "Enter new password for {}: ",
+            args.name
+        ))?)
+    } else {
+        None
+    };
+
+    let Some(new_name) = args
+        .new_name
+        .clone()
+        .filter(|new_name| *new_name != args.name)
+    else {
+        let session = store.modify(&args.name, &mut |session| {
+            apply_update(session, &args, &config, new_password.as_deref())
+        })?;
+        notice!("Updated session: {}", session.name);
+        return Ok(());
+    };
+
+    // The rename itself checks both names again inside its locked write.
+    let sessions = store.list()?;
+    let replaced = sessions.iter().find(|s| s.name == new_name).cloned();
+    if replaced.is_some() && !args.force {
+        return Err(anyhow!(
+            "session '{}' already exists (use --force to replace it)",
+            new_name
+        ));
+    }
+    let mut session = sessions
+        .into_iter()
         .find(|s| s.name == args.name)
         .ok_or_else(|| anyhow!("session '{}' not found", args.name))?;
+    apply_update(&mut session, &args, &config, new_password.as_deref())?;
+    session.name = new_name;
+    match &replaced {
+        Some(target) => {
+            notice!("Replacing existing session: {}", target.name);
+            merge_history(&mut session, target);
+            // One store write swaps the sessions; the target's password goes only
+            // once that succeeded, so a failed rename loses nothing.
+            store.rename_replacing(&args.name, session.clone())?;
+            let target_mode = target
+                .passwd_unsafe_mode
+                .as_ref()
+                .unwrap_or(&config.passwd_unsafe_mode);
+            if target.has_stored_password
+                && *target_mode == PasswdUnsafeMode::Normal
+                && let Err(err) = password::delete_password(&target.name)
+            {
+                eprintln!(
+                    "Warning: could not remove the stored password of '{}': {:#}",
+                    target.name, err
+                );
+            }
+        }
+        None => store.rename(&args.name, session.clone())?,
+    }
+    let effective_mode = session
+        .passwd_unsafe_mode
+        .clone()
+        .unwrap_or(config.passwd_unsafe_mode);
+    if session.has_stored_password
+        && effective_mode == PasswdUnsafeMode::Normal
+        && let Err(err) = move_keyring_password(&args.name, &session.name)
+    {
+        eprintln!(
+            "Warning: could not move the stored password to '{}': {:#}",
+            session.name, err
+        );
+    }
+    notice!("Renamed session: {} -> {}", args.name, session.name);
+    Ok(())
+}
 
-    if let Some(host) = args.host {
-        session.host = host;
+/// Apply the field and password changes of `se update` to `session`. `password` is
+/// the new password read for `--password`.
+fn apply_update(
+    session: &mut Session,
+    args: &UpdateArgs,
+    config: &StoreConfig,
+    password: Option<&str>,
+) -> Result<()> {
+    if let Some(host) = &args.host {
+        session.host = host.clone();
     }
-    if let Some(user) = args.user {
-        session.user = user;
+    if let Some(user) = &args.user {
+        session.user = user.clone();
     }
     if let Some(port) = args.port {
         session.port = port;
     }
     if args.identity_file.is_some() {
-        session.identity_file = args.identity_file;
+        session.identity_file = args.identity_file.clone();
     }
     if !args.tags.is_empty() {
-        session.tags = normalize_tags(args.tags);
+        session.tags = normalize_tags(args.tags.clone());
     }
-
-    // Handle password update
-    if args.password && args.no_password {
-        return Err(anyhow!("Cannot specify both --password and --no-password"));
+    if let Some(control_master) = args.control_master {
+        session.control_master = control_master;
+    }
+    if args.control_path.is_some() {
+        session.control_path = args.control_path.clone();
+    }
+    if let Some(proxy_jump) = &args.proxy_jump {
+        session.proxy_jump = Some(proxy_jump.clone()).filter(|jump| !jump.is_empty());
+    }
+    if let Some(dir) = &args.default_local_dir {
+        session.default_local_dir = Some(dir.clone()).filter(|dir| !dir.is_empty());
+    }
+    if let Some(scp_compress) = args.scp_compress {
+        session.scp_compress = scp_compress;
+    }
+    for key in &args.remove_options {
+        if session.options.remove(key).is_none() {
+            return Err(anyhow!(
+                "session '{}' has no ssh option '{}'",
+                args.name,
+                key
+            ));
+        }
+    }
+    session.options.extend(args.options.iter().cloned());
+    if !args.ssh_args.is_empty() {
+        session.extra_args = non_empty(args.ssh_args.clone());
+    }
+    if !args.local_forwards.is_empty() {
+        session.local_forwards = non_empty(args.local_forwards.clone());
+    }
+    if !args.remote_forwards.is_empty() {
+        session.remote_forwards = non_empty(args.remote_forwards.clone());
     }
 
     if args.no_password {
         // Remove stored password
-        let config = store.get_config()?;
         let effective_mode = session
             .passwd_unsafe_mode
             .as_ref()
@@ -746,12 +1581,8 @@ fn update_session(store: &JsonFileStore, args: UpdateArgs) -> Result<()> {
             }
         }
         session.has_stored_password = false;
-    } else if args.password {
+    } else if let Some(pwd) = password {
         // Update stored password
-        let pwd = prompt_password_value(format!("Enter new password for {}: ", args.name))?;
-
-        // Determine effective password mode
-        let config = store.get_config()?;
         let effective_mode = args.passwd_mode.map(|m| m.into()).unwrap_or_else(|| {
             session
                 .passwd_unsafe_mode
@@ -766,17 +1597,17 @@ fn update_session(store: &JsonFileStore, args: UpdateArgs) -> Result<()> {
 
         match effective_mode {
             PasswdUnsafeMode::Normal => {
-                session.has_stored_password = try_store_password(&args.name, &pwd)?;
+                session.has_stored_password = try_store_password(&args.name, pwd)?;
                 // Clear any previously stored unsafe password
                 session.stored_password = None;
             }
             PasswdUnsafeMode::Bare => {
-                session.stored_password = Some(pwd);
+                session.stored_password = Some(pwd.to_string());
                 session.has_stored_password = true;
             }
             PasswdUnsafeMode::Simple => {
                 let encoded = password::store_unsafe_password(
-                    &pwd,
+                    pwd,
                     &PasswdUnsafeMode::Simple,
                     config.passwd_unsafe_key.as_deref(),
                 )?;
@@ -785,21 +1616,150 @@ fn update_session(store: &JsonFileStore, args: UpdateArgs) -> Result<()> {
             }
         }
     }
+    Ok(())
+}
+
+fn handle_tag_command(store: &dyn SessionStore, args: TagArgs) -> Result<()> {
+    let (edit, adding) = match args.command {
+        TagCommand::Add(edit) => (edit, true),
+        TagCommand::Remove(edit) => (edit, false),
+    };
+    let tags = normalize_tags(edit.tags);
+    let session = store.modify(&edit.name, &mut |session| {
+        if adding {
+            for tag in &tags {
+                if !session.tags.contains(tag) {
+                    session.tags.push(tag.clone());
+                }
+            }
+        } else {
+            session.tags.retain(|tag| !tags.contains(tag));
+        }
+        Ok(())
+    })?;
+    notice!(
+        "Tags for {}: {}",
+        session.name,
+        if session.tags.is_empty() {
+            "-".to_string()
+        } else {
+            session.tags.join(", ")
+        }
+    );
+    Ok(())
+}
+
+fn clone_session(store: &dyn SessionStore, args: CloneArgs) -> Result<()> {
+    let sessions = store.list()?;
+    let source = sessions
+        .iter()
+        .find(|s| s.name == args.name)
+        .ok_or_else(|| anyhow!("session '{}' not found", args.name))?;
+    if sessions.iter().any(|s| s.name == args.new_name) {
+        return Err(anyhow!("session '{}' already exists", args.new_name));
+    }
 
-    store.update(session.clone())?;
-    println!("Updated session: {}", session.name);
+    let session = Session {
+        name: args.new_name,
+        last_connected_at: None,
+        connect_count: 0,
+        last_duration_secs: None,
+        avg_duration_secs: None,
+        ..source.clone()
+    };
+    store.add(session.clone())?;
+    let effective_mode = session
+        .passwd_unsafe_mode
+        .clone()
+        .unwrap_or(store.get_config()?.passwd_unsafe_mode);
+    if session.has_stored_password
+        && effective_mode == PasswdUnsafeMode::Normal
+        && let Err(err) = copy_keyring_password(&source.name, &session.name)
+    {
+        eprintln!(
+            "Warning: could not copy the stored password to '{}': {:#}",
+            session.name, err
+        );
+    }
+    notice!("Cloned session: {} -> {}", source.name, session.name);
     Ok(())
 }
 
-fn run_tui(store: &JsonFileStore, ui_config: &ui::UiConfig) -> Result<()> {
-    let selection = ui::run_tui(store, ui_config)?;
-    if let Some(session) = selection {
-        run_ssh(&session)?;
-        store.touch_last_connected(&session.name, now_epoch_seconds())?;
+/// Fold the connection history of a session being replaced by `--force` into the
+/// renamed one: the later last connection wins, counts add up, and durations are
+/// only taken over when the renamed session has none of its own.
+fn merge_history(session: &mut Session, replaced: &Session) {
+    session.last_connected_at = session.last_connected_at.max(replaced.last_connected_at);
+    session.connect_count = session.connect_count.saturating_add(replaced.connect_count);
+    if session.last_duration_secs.is_none() {
+        session.last_duration_secs = replaced.last_duration_secs;
+        session.avg_duration_secs = replaced.avg_duration_secs;
+    }
+}
+
+/// Keyring entries are keyed by session name, so a rename moves the password along.
+fn move_keyring_password(old_name: &str, new_name: &str) -> Result<()> {
+    if copy_keyring_password(old_name, new_name)? {
+        password::delete_password(old_name)?;
     }
     Ok(())
 }
 
+/// Returns whether `from` had a keyring password to copy.
+fn copy_keyring_password(from: &str, to: &str) -> Result<bool> {
+    match password::get_password(from)? {
+        Some(pwd) => {
+            password::store_password(to, &pwd)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Where the TUI's profile picker finds profiles, and which one is open.
+struct TuiProfiles {
+    base: PathBuf,
+    active: Option<String>,
+    /// Opens the stores of profiles picked in the TUI
+    opener: StoreOpener,
+}
+
+fn run_tui(
+    store: &dyn SessionStore,
+    ui_config_path: Option<PathBuf>,
+    parallel: usize,
+    profiles: Option<TuiProfiles>,
+    args: TuiArgs,
+) -> Result<()> {
+    let ui_config = ui::load_ui_config(ui_config_path)?;
+    let (profile_base, profile, opener) = match profiles {
+        Some(profiles) => (Some(profiles.base), profiles.active, profiles.opener),
+        None => (None, None, StoreOpener::default()),
+    };
+    let options = ui::TuiOptions {
+        filter: args.filter,
+        tag: args.tag,
+        select: args.select,
+        profile_base,
+        profile,
+        store_opener: opener.clone(),
+    };
+    let Some(selection) = ui::run_tui(store, &SystemRunner, &ui_config, parallel, &options)? else {
+        return Ok(());
+    };
+    // A profile switched to in the TUI records the connection in its own store,
+    // opened the way the TUI opened it
+    match selection.store_path {
+        Some(path) => connect(
+            opener.for_tui().open(path)?.as_ref(),
+            &SystemRunner,
+            &selection.session,
+            Retry::default(),
+        ),
+        None => connect(store, &SystemRunner, &selection.session, Retry::default()),
+    }
+}
+
 fn normalize_tags(tags: Vec<String>) -> Vec<String> {
     tags.into_iter()
         .map(|tag| tag.trim().to_string())
@@ -836,8 +1796,37 @@ fn prompt_password_value(prompt: String) -> Result<String> {
     Ok(password)
 }
 
-fn run_ssh(session: &Session) -> Result<()> {
+fn run_ssh(runner: &dyn CommandRunner, session: &Session) -> Result<()> {
+    if openssh::needs_openssh(session) {
+        let mut command = openssh::ssh_command(session);
+        log::info!("running {:?}", command);
+        let status = runner
+            .status(&mut command)
+            .context("failed to execute ssh")?;
+        // ssh exits with 255 for its own errors; other codes come from the remote shell.
+        // Probing the first hop tells an unreachable host apart from, say, a bad key.
+        // Without a hop to probe the failure is not retried.
+        if status.code() == Some(255) {
+            let failed = format!("ssh to {} failed", session.target());
+            let Some((host, port)) = retry::first_hop(session) else {
+                return Err(anyhow!(failed));
+            };
+            return Err(match probe_tcp(&host, port, PROBE_TIMEOUT) {
+                Ok(_) => anyhow!(failed),
+                Err(err) => err.context(failed),
+            });
+        }
+        return Ok(());
+    }
+
     let auth_config = auth_config_for_session(session);
+    log::info!(
+        "connecting to {}:{} as {} (identity: {:?})",
+        session.host,
+        session.port,
+        session.user,
+        auth_config.identity_file
+    );
 
     let mut connection =
         SshConnection::connect(&session.host, session.port, &session.user, &auth_config)?;
@@ -847,52 +1836,490 @@ fn run_ssh(session: &Session) -> Result<()> {
     Ok(())
 }
 
-fn run_go(store: &JsonFileStore, args: GoArgs) -> Result<()> {
+fn run_go(store: &dyn SessionStore, runner: &dyn CommandRunner, args: GoArgs) -> Result<()> {
+    let session = store
+        .list()?
+        .into_iter()
+        .find(|session| session.name == args.name)
+        .ok_or_else(|| anyhow!("session '{}' not found", args.name))?;
+
+    let retry = Retry {
+        retries: args.retry,
+        delay: Duration::from_secs(args.retry_delay),
+    };
+    connect(store, runner, &session, retry)
+}
+
+fn run_last(store: &dyn SessionStore, runner: &dyn CommandRunner) -> Result<()> {
+    let sessions = store.list()?;
+    let session = sessions
+        .iter()
+        .filter(|session| session.last_connected_at.is_some())
+        .max_by(|a, b| {
+            a.last_connected_at
+                .cmp(&b.last_connected_at)
+                .then_with(|| b.name.cmp(&a.name))
+        })
+        .ok_or_else(|| {
+            anyhow!("no session has been connected to yet; use `se go --name <NAME>`")
+        })?;
+
+    notice!("Connecting to {}", session.name);
+    connect(store, runner, session, Retry::default())
+}
+
+/// Open an interactive ssh session, then record it (and summarize it with `--summary`).
+/// Connection failures are retried as `retry` allows.
+fn connect(
+    store: &dyn SessionStore,
+    runner: &dyn CommandRunner,
+    session: &Session,
+    retry: Retry,
+) -> Result<()> {
+    let started = Instant::now();
+    retry::run_with_retry(
+        retry,
+        &session.target(),
+        || run_ssh(runner, session),
+        std::thread::sleep,
+    )?;
+    let elapsed = record_connection(store, session, ConnectionKind::Ssh, started);
+    if output::connection_summary() {
+        notice!(
+            "Connected to {} ({}:{}) for {}",
+            session.name,
+            session.target(),
+            session.port,
+            format_duration(elapsed)
+        );
+    }
+    Ok(())
+}
+
+/// Stamp `last_connected_at`, fold the time since `started` into the session's
+/// duration stats and append it to the connection log; returns the elapsed seconds.
+/// The connection already succeeded by now, so a store that cannot be written
+/// (e.g. it became read-only) only earns a warning.
+fn record_connection(
+    store: &dyn SessionStore,
+    session: &Session,
+    kind: ConnectionKind,
+    started: Instant,
+) -> u64 {
+    let elapsed = started.elapsed().as_secs();
+    let now = now_epoch_seconds();
+    if let Err(err) = store.record_connection(&session.name, kind, now, elapsed) {
+        eprintln!(
+            "Warning: could not record the connection to '{}': {:#}",
+            session.name, err
+        );
+    }
+    elapsed
+}
+
+/// Run ssh with one-off forwards, then record the connection like `se go` does.
+fn run_tunnel(
+    store: &dyn SessionStore,
+    runner: &dyn CommandRunner,
+    args: TunnelArgs,
+) -> Result<()> {
     let session = store
         .list()?
         .into_iter()
         .find(|session| session.name == args.name)
         .ok_or_else(|| anyhow!("session '{}' not found", args.name))?;
+    let local = non_empty(args.local);
+    let remote = non_empty(args.remote);
+    if local.is_empty() && remote.is_empty() {
+        return Err(anyhow!("give at least one --local or --remote forward"));
+    }
 
-    run_ssh(&session)?;
-    store.touch_last_connected(&session.name, now_epoch_seconds())?;
+    let mut command = openssh::tunnel_command(&session, &local, &remote, args.no_shell);
+    if args.no_shell {
+        notice!("Forwarding through {}; press Ctrl-C to stop", session.name);
+    }
+    log::info!("running {:?}", command);
+    let started = Instant::now();
+    let status = {
+        let _interrupt = InterruptGuard::install();
+        runner
+            .status(&mut command)
+            .context("failed to execute ssh")?
+    };
+    // 255 is ssh's own failure; Ctrl-C ends ssh by signal, which is the normal way out.
+    if status.code() == Some(255) {
+        return Err(anyhow!("ssh tunnel to {} failed", session.target()));
+    }
+    record_connection(store, &session, ConnectionKind::Ssh, started);
     Ok(())
 }
 
-fn run_scp(store: &JsonFileStore, args: ScpArgs) -> Result<()> {
+fn run_scp(store: &dyn SessionStore, runner: &dyn CommandRunner, args: ScpArgs) -> Result<()> {
     let session = store
         .list()?
         .into_iter()
         .find(|session| session.name == args.name)
         .ok_or_else(|| anyhow!("session '{}' not found", args.name))?;
 
+    // Without --local, transfer the file of the same name in the session's local dir.
+    let locals = if args.local.is_empty() {
+        let dir = session.local_dir().ok_or_else(|| {
+            anyhow!(
+                "--local is required: session '{}' has no default local dir",
+                session.name
+            )
+        })?;
+        let file_name = args
+            .remote
+            .file_name()
+            .ok_or_else(|| anyhow!("--remote {} has no file name", args.remote.display()))?;
+        vec![dir.join(file_name)]
+    } else {
+        args.local
+    };
+    let remote = args.remote.to_string_lossy().to_string();
+    if locals.len() > 1 && matches!(args.direction, ScpDirection::From) {
+        return Err(anyhow!(
+            "several --local paths only work with --direction to"
+        ));
+    }
+
     let auth_config = auth_config_for_session(&session);
+    log::info!(
+        "scp {:?} {}:{} local={:?} remote={}",
+        args.direction,
+        session.host,
+        session.port,
+        locals,
+        remote
+    );
+
+    let options = ScpOptions {
+        recursive: false,
+        preserve: args.preserve,
+        compress: args.compress || session.scp_compress,
+        limit_kbps: args.limit,
+    };
+    let started = Instant::now();
+    // The built-in client has no -p/-C/-l equivalents, so those go through scp too.
+    if openssh::needs_openssh(&session) || options != ScpOptions::default() {
+        // scp itself refuses several sources unless the destination is a directory.
+        let remote_spec = openssh::remote_spec(&session, &remote);
+        let mut command = openssh::scp_command(&session, options);
+        match args.direction {
+            ScpDirection::To => command.args(&locals).arg(remote_spec),
+            ScpDirection::From => command.arg(remote_spec).args(&locals),
+        };
+        log::info!("running {:?}", command);
+        let status = runner
+            .status(&mut command)
+            .context("failed to execute scp")?;
+        if !status.success() {
+            return Err(anyhow!("scp failed ({})", status));
+        }
+        record_connection(store, &session, ConnectionKind::Scp, started);
+        return Ok(());
+    }
 
     let connection =
         SshConnection::connect(&session.host, session.port, &session.user, &auth_config)?;
 
     match args.direction {
+        ScpDirection::To if locals.len() > 1 => {
+            if !connection.is_remote_dir(&remote)? {
+                return Err(anyhow!(
+                    "--remote {} must be an existing directory when uploading several files",
+                    remote
+                ));
+            }
+            for local in &locals {
+                let file_name = local
+                    .file_name()
+                    .ok_or_else(|| anyhow!("--local {} has no file name", local.display()))?;
+                let target = Path::new(&remote).join(file_name);
+                connection
+                    .upload(local, &target.to_string_lossy())
+                    .with_context(|| format!("failed to upload {}", local.display()))?;
+            }
+        }
         ScpDirection::To => {
             connection
-                .upload(&args.local, args.remote.to_str().unwrap())
+                .upload(&locals[0], &remote)
                 .context("failed to upload file")?;
         }
         ScpDirection::From => {
             connection
-                .download(args.remote.to_str().unwrap(), &args.local)
+                .download(&remote, &locals[0])
                 .context("failed to download file")?;
         }
     }
 
-    store.touch_last_connected(&session.name, now_epoch_seconds())?;
+    record_connection(store, &session, ConnectionKind::Scp, started);
+    Ok(())
+}
+
+fn run_exec(
+    store: &dyn SessionStore,
+    runner: &dyn CommandRunner,
+    cli_config: Option<PathBuf>,
+    parallel: usize,
+    args: ExecArgs,
+) -> Result<()> {
+    let sessions = store.list()?;
+    let mut targets: Vec<Session> = Vec::new();
+    for name in &args.names {
+        let session = sessions
+            .iter()
+            .find(|session| session.name == *name)
+            .ok_or_else(|| anyhow!("session '{}' not found", name))?;
+        targets.push(session.clone());
+    }
+    if let Some(tag) = &args.tag {
+        let tagged: Vec<&Session> = sessions
+            .iter()
+            .filter(|session| session.tags.contains(tag))
+            .collect();
+        if tagged.is_empty() {
+            return Err(anyhow!("no sessions are tagged '{}'", tag));
+        }
+        for session in tagged {
+            if !targets.iter().any(|target| target.name == session.name) {
+                targets.push(session.clone());
+            }
+        }
+    }
+
+    let style = if args.block {
+        exec::OutputStyle::Block
+    } else {
+        exec::OutputStyle::Prefix
+    };
+    let theme = theme::load_cli_theme(cli_config)?;
+    let color = (theme.enabled && io::stdout().is_terminal()).then_some(theme.name);
+    let total = targets.len();
+    let failed = exec::run_exec(targets, runner, &args.command, style, parallel, color);
+    if !failed.is_empty() {
+        return Err(anyhow!(
+            "{} of {} sessions failed: {}",
+            failed.len(),
+            total,
+            failed.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+fn disconnect_sessions(
+    store: &dyn SessionStore,
+    runner: &dyn CommandRunner,
+    args: DisconnectArgs,
+) -> Result<()> {
+    let sessions = store.list()?;
+    let targets: Vec<Session> = match args.name {
+        Some(name) => {
+            let session = sessions
+                .into_iter()
+                .find(|session| session.name == name)
+                .ok_or_else(|| anyhow!("session '{}' not found", name))?;
+            if !session.control_master {
+                notice!(
+                    "{} does not use ControlMaster; nothing to disconnect",
+                    session.name
+                );
+                return Ok(());
+            }
+            vec![session]
+        }
+        None => sessions
+            .into_iter()
+            .filter(|session| session.control_master)
+            .collect(),
+    };
+    if targets.is_empty() {
+        notice!("No sessions use ControlMaster; nothing to disconnect");
+        return Ok(());
+    }
+
+    let mut closed = Vec::new();
+    let mut not_open = Vec::new();
+    for session in &targets {
+        let mut command = openssh::exit_command(session);
+        log::info!("running {:?}", command);
+        // ssh complains on stderr when no master is running; that is the "not open" case.
+        let output = runner
+            .output(&mut command)
+            .context("failed to execute ssh")?;
+        if output.status.success() {
+            closed.push(session.name.as_str());
+        } else {
+            not_open.push(session.name.as_str());
+        }
+    }
+
+    if !closed.is_empty() {
+        notice!("Closed: {}", closed.join(", "));
+    }
+    if !not_open.is_empty() {
+        notice!("Not open: {}", not_open.join(", "));
+    }
+    Ok(())
+}
+
+/// List the key directory rather than opening anything: private keys should not be
+/// edited casually, so this only names the files next to the identity.
+fn show_identity(store: &dyn SessionStore, name: &str) -> Result<()> {
+    let session = store
+        .list()?
+        .into_iter()
+        .find(|session| session.name == name)
+        .ok_or_else(|| anyhow!("session '{}' not found", name))?;
+    let (Some(identity), Some(dir)) = (&session.identity_file, session.identity_dir()) else {
+        notice!(
+            "{} has no identity file (ssh uses its default keys)",
+            session.name
+        );
+        return Ok(());
+    };
+
+    let exists = expand_home(&identity.to_string_lossy()).exists();
+    println!(
+        "identity: {} ({})",
+        identity.display(),
+        if exists { "exists" } else { "missing" }
+    );
+    println!("directory: {}", dir.display());
+    let mut entries: Vec<String> = std::fs::read_dir(&dir)
+        .with_context(|| format!("unable to read {}", dir.display()))?
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    entries.sort();
+    for entry in entries {
+        let note = if entry.ends_with(".pub") {
+            " (public key)"
+        } else {
+            ""
+        };
+        println!("  {}{}", entry, note);
+    }
     Ok(())
 }
 
-fn now_epoch_seconds() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|duration| duration.as_secs() as i64)
-        .unwrap_or(0)
+fn print_stats(store: &dyn SessionStore) -> Result<()> {
+    let mut sessions = store.list()?;
+    sessions.sort_by(|a, b| {
+        b.avg_duration_secs
+            .cmp(&a.avg_duration_secs)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    let duration = |secs: Option<u64>| secs.map(format_duration).unwrap_or_else(|| "-".into());
+    let width = sessions
+        .iter()
+        .map(|session| session.name.len())
+        .chain(["NAME".len()])
+        .max()
+        .unwrap_or(0);
+    println!("{:<width$}  {:>8}  {:>8}", "NAME", "LAST", "AVERAGE");
+    for session in &sessions {
+        println!(
+            "{:<width$}  {:>8}  {:>8}",
+            session.name,
+            duration(session.last_duration_secs),
+            duration(session.avg_duration_secs)
+        );
+    }
+    Ok(())
+}
+
+fn print_history(store: &dyn SessionStore, args: HistoryArgs) -> Result<()> {
+    if let Some(HistoryCommand::Prune { keep }) = args.command {
+        let dropped = store.prune_connection_log(keep)?;
+        notice!(
+            "Pruned {} connection log entries (kept at most {})",
+            dropped,
+            keep
+        );
+        return Ok(());
+    }
+
+    let now = now_epoch_seconds();
+    let since = args
+        .since
+        .as_deref()
+        .map(|value| history::parse_time_bound(value, now, false))
+        .transpose()
+        .context("--since")?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|value| history::parse_time_bound(value, now, true))
+        .transpose()
+        .context("--until")?;
+
+    let in_window =
+        |at: i64| since.is_none_or(|since| at >= since) && until.is_none_or(|until| at <= until);
+
+    if args.log {
+        if !store.get_config()?.log_connections {
+            notice!(
+                "Connection logging is off; enable it with `se config set log_connections true`"
+            );
+        }
+        let mut events: Vec<ConnectionEvent> = store
+            .connection_log()?
+            .into_iter()
+            .filter(|event| in_window(event.timestamp))
+            .collect();
+        events.sort_by_key(|event| std::cmp::Reverse(event.timestamp));
+        for event in &events {
+            println!(
+                "{}  {}  {}  {}",
+                history::format_timestamp(event.timestamp),
+                event.kind.as_str(),
+                event.session,
+                format_duration(event.duration_secs)
+            );
+        }
+        return Ok(());
+    }
+
+    let mut entries: Vec<(i64, Session)> = store
+        .list()?
+        .into_iter()
+        .filter_map(|session| Some((session.last_connected_at?, session)))
+        .filter(|(at, _)| in_window(*at))
+        .collect();
+    entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+
+    for (at, session) in &entries {
+        println!(
+            "{}  {}  {}",
+            history::format_timestamp(*at),
+            session.name,
+            session.target()
+        );
+    }
+    if since.is_some() || until.is_some() {
+        notice!(
+            "(only each session's most recent connection is recorded, so older visits are not listed; see --log)"
+        );
+    }
+    Ok(())
+}
+
+fn run_doctor(store: &dyn SessionStore) -> Result<()> {
+    let mut findings = doctor::check_tools();
+    findings.extend(doctor::check_sessions(&store.list()?));
+    if findings.is_empty() {
+        notice!("No problems found");
+        return Ok(());
+    }
+    for finding in &findings {
+        println!("warning: {}: {}", finding.subject, finding.message);
+    }
+    notice!("{} warnings", findings.len());
+    Ok(())
 }
 
 fn generate_completions(shell: Shell) {
@@ -910,7 +2337,197 @@ fn handle_theme_command(args: ThemeArgs) -> Result<()> {
     }
 }
 
-fn handle_config_command(store: &JsonFileStore, args: ConfigArgs) -> Result<()> {
+fn handle_store_command(store: &dyn SessionStore, args: StoreArgs) -> Result<()> {
+    match args.command {
+        StoreCommand::Repair => {
+            let removed = store.repair()?;
+            if removed.is_empty() {
+                notice!("No duplicate sessions found");
+            } else {
+                for name in &removed {
+                    notice!("Removed duplicate session '{}'", name);
+                }
+                notice!("Repaired store: {} duplicate(s) removed", removed.len());
+            }
+            Ok(())
+        }
+    }
+}
+
+fn validate_configs(ui_config: Option<PathBuf>, cli_config: Option<PathBuf>) -> Result<()> {
+    // Explicit paths must exist; the default locations are optional.
+    let explicit_ui = ui_config.is_some();
+    let explicit_cli = cli_config.is_some();
+    let files = [
+        (
+            "ui config",
+            ui::config::ui_config_candidate_path(ui_config)?,
+            explicit_ui,
+            validate::validate_ui_config as fn(&Path) -> Result<Vec<String>>,
+        ),
+        (
+            "cli config",
+            theme::cli_theme_candidate_path(cli_config)?,
+            explicit_cli,
+            validate::validate_cli_config,
+        ),
+    ];
+
+    let mut problem_count = 0;
+    for (label, path, explicit, check) in files {
+        if !path.exists() {
+            if explicit {
+                println!("{}: {} not found", label, path.display());
+                problem_count += 1;
+            } else {
+                println!("{}: {} not present (defaults apply)", label, path.display());
+            }
+            continue;
+        }
+        let problems = check(&path)?;
+        if problems.is_empty() {
+            println!("{}: {} ok", label, path.display());
+        } else {
+            println!("{}: {}", label, path.display());
+            for problem in &problems {
+                println!("  {}", problem);
+            }
+            problem_count += problems.len();
+        }
+    }
+
+    if problem_count > 0 {
+        return Err(anyhow!(
+            "found {} problem(s) in config files",
+            problem_count
+        ));
+    }
+    Ok(())
+}
+
+/// The store file before any `--profile`: `--store-path`, or else `sessions.json` or
+/// `sessions.db` in the config dir. An explicit `--store-format` names the file;
+/// otherwise an existing store keeps its format and a new one gets the configured
+/// default (`SSHER_STORE_FORMAT`, then `store_format` in cli.json, then JSON).
+fn base_store_path(cli: &Cli) -> Result<PathBuf> {
+    if let Some(path) = &cli.store_path {
+        return Ok(path.clone());
+    }
+    let dir = dirs::resolve_config_dir()?;
+    if let Some(format) = cli.store_format {
+        return Ok(dir.join(format.default_file_name()));
+    }
+    let configured = match std::env::var(STORE_FORMAT_ENV) {
+        Ok(value) => Some(
+            <StoreFormat as clap::ValueEnum>::from_str(&value, true).map_err(|_| {
+                anyhow!("invalid {} '{}' (json or sqlite)", STORE_FORMAT_ENV, value)
+            })?,
+        ),
+        Err(_) => theme::configured_store_format(cli.cli_config.clone())?,
+    };
+    Ok(default_store_file(&dir, configured.unwrap_or_default()))
+}
+
+fn print_paths(cli: &Cli) -> Result<()> {
+    fn describe(path: &Path) -> String {
+        let state = if path.exists() { "exists" } else { "missing" };
+        format!("{} ({})", path.display(), state)
+    }
+
+    println!(
+        "se {} ({})",
+        env!("CARGO_PKG_VERSION"),
+        env!("GIT_COMMIT_HASH")
+    );
+    println!("config dir:  {}", describe(&dirs::resolve_config_dir()?));
+    let store_path = resolve_store_path(Some(base_store_path(cli)?), cli.profile.as_deref())?;
+    if std::env::var_os(SESSIONS_ENV).is_some() {
+        println!(
+            "store:       {} (read-only, overrides file store)",
+            SESSIONS_ENV
+        );
+    } else {
+        println!("store:       {}", describe(&store_path));
+    }
+    let ui_config = ui::config::ui_config_candidate_path(cli.ui_config.clone())?;
+    println!("ui config:   {}", describe(&ui_config));
+    let cli_config = theme::cli_theme_candidate_path(cli.cli_config.clone())?;
+    println!("cli config:  {}", describe(&cli_config));
+
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        let zdotdir = std::env::var_os("ZDOTDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.clone());
+        println!("completions:");
+        println!(
+            "  bash:      {}",
+            describe(&home.join(".local/share/bash-completion/completions/se"))
+        );
+        println!("  zsh:       {}", describe(&zdotdir.join(".zfunc/_se")));
+        println!(
+            "  fish:      {}",
+            describe(&home.join(".config/fish/completions/se.fish"))
+        );
+    }
+    Ok(())
+}
+
+fn handle_profile_command(store_path: Option<PathBuf>, args: ProfileArgs) -> Result<()> {
+    match args.command {
+        ProfileCommand::Clone { from, to, force } => {
+            let source = resolve_store_path(store_path.clone(), Some(&from))?;
+            let existing = resolve_store_path(store_path, Some(&to))?;
+            // The copy keeps the source's format, so it takes the source's extension.
+            let target = match source.extension() {
+                Some(ext) if to != DEFAULT_PROFILE => existing.with_extension(ext),
+                ext if ext != existing.extension() => {
+                    return Err(anyhow!(
+                        "profile '{}' ({}) has a different format than {}",
+                        from,
+                        source.display(),
+                        existing.display()
+                    ));
+                }
+                _ => existing.clone(),
+            };
+            if !source.exists() {
+                return Err(anyhow!(
+                    "profile '{}' not found ({})",
+                    from,
+                    source.display()
+                ));
+            }
+            if source == target {
+                return Err(anyhow!("cannot clone profile '{}' onto itself", from));
+            }
+            if existing.exists() && !force {
+                return Err(anyhow!(
+                    "profile '{}' already exists (use --force to overwrite)",
+                    to
+                ));
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create directory {}", parent.display()))?;
+            }
+            std::fs::copy(&source, &target).with_context(|| {
+                format!(
+                    "failed to copy {} to {}",
+                    source.display(),
+                    target.display()
+                )
+            })?;
+            if existing != target && existing.exists() {
+                std::fs::remove_file(&existing)
+                    .with_context(|| format!("failed to remove {}", existing.display()))?;
+            }
+            notice!("Cloned profile '{}' to '{}'", from, to);
+            Ok(())
+        }
+    }
+}
+
+fn handle_config_command(store: &dyn SessionStore, args: ConfigArgs) -> Result<()> {
     match args.command {
         ConfigCommand::Set { key, value } => {
             let mut config = store.get_config()?;
@@ -931,18 +2548,32 @@ fn handle_config_command(store: &JsonFileStore, args: ConfigArgs) -> Result<()>
                 }
                 "passwd_unsafe_key" => {
                     config.passwd_unsafe_key = Some(value.clone());
-                    println!("Set {} = {}", key, value);
+                    notice!("Set {} = {}", key, value);
                     return store.set_config(&config);
                 }
+                "log_connections" => {
+                    config.log_connections = match value.to_lowercase().as_str() {
+                        "true" => true,
+                        "false" => false,
+                        _ => {
+                            return Err(anyhow!(
+                                "Invalid value '{}' for log_connections. Valid values: true, false",
+                                value
+                            ));
+                        }
+                    };
+                }
+                "log_max_entries" => config.log_max_entries = parse_log_limit(&key, &value)?,
+                "log_max_bytes" => config.log_max_bytes = parse_log_limit(&key, &value)?,
                 _ => {
                     return Err(anyhow!(
-                        "Unknown config key '{}'. Valid keys: passwd_unsafe_mode, passwd_unsafe_key",
+                        "Unknown config key '{}'. Valid keys: passwd_unsafe_mode, passwd_unsafe_key, log_connections, log_max_entries, log_max_bytes",
                         key
                     ));
                 }
             }
             store.set_config(&config)?;
-            println!("Set {} = {}", key, value);
+            notice!("Set {} = {}", key, value);
             Ok(())
         }
         ConfigCommand::Get { key } => {
@@ -967,9 +2598,12 @@ fn handle_config_command(store: &JsonFileStore, args: ConfigArgs) -> Result<()>
                         None => println!("<not set>"),
                     }
                 }
+                "log_connections" => println!("{}", config.log_connections),
+                "log_max_entries" => println!("{}", format_log_limit(config.log_max_entries)),
+                "log_max_bytes" => println!("{}", format_log_limit(config.log_max_bytes)),
                 _ => {
                     return Err(anyhow!(
-                        "Unknown config key '{}'. Valid keys: passwd_unsafe_mode, passwd_unsafe_key",
+                        "Unknown config key '{}'. Valid keys: passwd_unsafe_mode, passwd_unsafe_key, log_connections, log_max_entries, log_max_bytes",
                         key
                     ));
                 }
@@ -998,7 +2632,72 @@ fn handle_config_command(store: &JsonFileStore, args: ConfigArgs) -> Result<()>
                     println!("passwd_unsafe_key: <not set>");
                 }
             }
+            println!("log_connections: {}", config.log_connections);
+            println!(
+                "log_max_entries: {}",
+                format_log_limit(config.log_max_entries)
+            );
+            println!("log_max_bytes: {}", format_log_limit(config.log_max_bytes));
             Ok(())
         }
+        ConfigCommand::Validate { .. } => unreachable!(),
+    }
+}
+
+/// Connection log caps are a positive number; `0` or `none` removes the cap.
+fn parse_log_limit(key: &str, value: &str) -> Result<Option<u64>> {
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    let limit: u64 = value.parse().map_err(|_| {
+        anyhow!(
+            "Invalid value '{}' for {}. Valid values: a number, or 0/none for no limit",
+            value,
+            key
+        )
+    })?;
+    Ok((limit > 0).then_some(limit))
+}
+
+fn format_log_limit(limit: Option<u64>) -> String {
+    limit.map_or_else(|| "<not set>".to_string(), |limit| limit.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::fake::FakeRunner;
+    use crate::store::JsonFileStore;
+    use tempfile::tempdir;
+
+    #[test]
+    fn run_tunnel_records_the_connection_when_ssh_is_interrupted() {
+        let dir = tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        store
+            .add(Session::builder("web", "web.example.com", "me").build())
+            .expect("add");
+        let runner = FakeRunner::killed_by(libc::SIGINT);
+
+        run_tunnel(
+            &store,
+            &runner,
+            TunnelArgs {
+                name: "web".to_string(),
+                local: vec!["8080:localhost:80".to_string()],
+                remote: Vec::new(),
+                no_shell: true,
+            },
+        )
+        .expect("tunnel");
+
+        assert_eq!(
+            *runner.commands.lock().unwrap(),
+            ["ssh -p 22 -L 8080:localhost:80 -N me@web.example.com"]
+        );
+        let session = &store.list().expect("list")[0];
+        assert_eq!(session.connect_count, 1);
+        assert!(session.last_connected_at.is_some());
+        assert_eq!(store.connection_log().expect("log").len(), 1);
     }
 }
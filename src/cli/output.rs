@@ -1,24 +1,200 @@
 use crate::cli::theme::CliTheme;
 use crate::model::Session;
 use crossterm::style::Stylize;
+use std::collections::BTreeMap;
 use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-pub fn print_sessions(sessions: &[Session], theme: &CliTheme) {
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Suppress informational messages for the rest of the process (`--quiet`).
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+static CONNECTION_SUMMARY: AtomicBool = AtomicBool::new(false);
+
+/// Print how long each interactive connection lasted once it ends (`--summary`).
+pub fn set_connection_summary(enabled: bool) {
+    CONNECTION_SUMMARY.store(enabled, Ordering::Relaxed);
+}
+
+pub fn connection_summary() -> bool {
+    CONNECTION_SUMMARY.load(Ordering::Relaxed)
+}
+
+const HEADERS: [&str; 7] = ["NAME", "TARGET", "PORT", "IDENTITY", "TAGS", "PWD", "CONNS"];
+const COLUMN_GAP: &str = "  ";
+const GROUP_INDENT: &str = "  ";
+/// Header for sessions without tags in `se list --group-by tag`.
+const UNTAGGED_GROUP: &str = "(none)";
+
+/// How `print_sessions` separates columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableLayout {
+    /// Pad every column to its widest cell, like `column -t`
+    Aligned,
+    /// Join cells with `\t` for scripts that split on tabs
+    Tabs,
+}
+
+pub fn print_sessions(sessions: &[Session], theme: &CliTheme, layout: TableLayout) {
+    if sessions.is_empty() {
+        println!("No sessions found.");
+        return;
+    }
+
+    let use_color = theme.enabled && std::io::stdout().is_terminal();
+    let rows = session_rows(sessions);
+    print!(
+        "{}",
+        render_table(&rows, layout, use_color.then_some(theme))
+    );
+}
+
+/// Like `print_sessions`, but with sessions listed under a header per tag.
+///
+/// A session with several tags shows up under each of them, or only under its
+/// first tag with `first_tag_only`; untagged sessions come last under "(none)".
+/// Always aligned: tab-separated output has no place for the headers.
+pub fn print_sessions_by_tag(sessions: &[Session], theme: &CliTheme, first_tag_only: bool) {
     if sessions.is_empty() {
         println!("No sessions found.");
         return;
     }
 
     let use_color = theme.enabled && std::io::stdout().is_terminal();
-    println!(
-        "{}\t{}\t{}\t{}\t{}\t{}",
-        colorize("NAME", theme.header, use_color),
-        colorize("TARGET", theme.header, use_color),
-        colorize("PORT", theme.header, use_color),
-        colorize("IDENTITY", theme.header, use_color),
-        colorize("TAGS", theme.header, use_color),
-        colorize("PWD", theme.header, use_color)
+    let groups = group_by_tag(sessions, first_tag_only);
+    print!(
+        "{}",
+        render_grouped_table(&groups, use_color.then_some(theme))
     );
+}
+
+/// Sessions keyed by tag in tag order, keeping the incoming order inside a group.
+/// Untagged sessions come last under `None`, apart from any tag named "(none)".
+fn group_by_tag(sessions: &[Session], first_tag_only: bool) -> Vec<(Option<&str>, Vec<&Session>)> {
+    let mut groups: BTreeMap<&str, Vec<&Session>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for session in sessions {
+        let tags = if first_tag_only {
+            &session.tags[..session.tags.len().min(1)]
+        } else {
+            &session.tags[..]
+        };
+        if tags.is_empty() {
+            untagged.push(session);
+        }
+        for tag in tags {
+            groups.entry(tag.as_str()).or_default().push(session);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(tag, members)| (Some(tag), members))
+        .chain((!untagged.is_empty()).then_some((None, untagged)))
+        .collect()
+}
+
+fn render_table(rows: &[Vec<String>], layout: TableLayout, theme: Option<&CliTheme>) -> String {
+    let widths = column_widths(rows);
+    let mut out = String::new();
+    for (row_index, row) in rows.iter().enumerate() {
+        push_row(&mut out, row, row_index == 0, &widths, layout, theme);
+    }
+    out
+}
+
+/// Columns stay aligned across groups: widths come from every row, not per group.
+fn render_grouped_table(
+    groups: &[(Option<&str>, Vec<&Session>)],
+    theme: Option<&CliTheme>,
+) -> String {
+    let group_rows: Vec<Vec<Vec<String>>> = groups
+        .iter()
+        .map(|(_, members)| session_rows(members.iter().copied()).split_off(1))
+        .collect();
+    let header: Vec<String> = HEADERS.iter().map(|h| h.to_string()).collect();
+    let widths = column_widths(std::iter::once(&header).chain(group_rows.iter().flatten()));
+
+    let mut out = String::new();
+    out.push_str(GROUP_INDENT);
+    push_row(
+        &mut out,
+        &header,
+        true,
+        &widths,
+        TableLayout::Aligned,
+        theme,
+    );
+    for ((tag, _), rows) in groups.iter().zip(&group_rows) {
+        let tag = tag.unwrap_or(UNTAGGED_GROUP);
+        match theme {
+            Some(theme) => out.push_str(&colorize(tag, theme.tags, true)),
+            None => out.push_str(tag),
+        }
+        out.push('\n');
+        for row in rows {
+            out.push_str(GROUP_INDENT);
+            push_row(&mut out, row, false, &widths, TableLayout::Aligned, theme);
+        }
+    }
+    out
+}
+
+fn push_row(
+    out: &mut String,
+    row: &[String],
+    header: bool,
+    widths: &[usize],
+    layout: TableLayout,
+    theme: Option<&CliTheme>,
+) {
+    let last = row.len().saturating_sub(1);
+    for (index, cell) in row.iter().enumerate() {
+        // Widths come from the plain text; color codes wrap the cell only.
+        match theme {
+            Some(theme) => {
+                let color = if header {
+                    theme.header
+                } else {
+                    cell_color(theme, index)
+                };
+                out.push_str(&colorize(cell, color, true));
+            }
+            None => out.push_str(cell),
+        }
+        if index < last {
+            match layout {
+                TableLayout::Tabs => out.push('\t'),
+                TableLayout::Aligned => {
+                    let pad = widths[index] - cell.chars().count();
+                    out.push_str(&" ".repeat(pad));
+                    out.push_str(COLUMN_GAP);
+                }
+            }
+        }
+    }
+    out.push('\n');
+}
+
+fn cell_color(theme: &CliTheme, column: usize) -> crossterm::style::Color {
+    match column {
+        0 => theme.name,
+        1 => theme.target,
+        2 | 6 => theme.port,
+        3 => theme.identity,
+        _ => theme.tags,
+    }
+}
+
+/// Header row followed by one row of display cells per session.
+fn session_rows<'a>(sessions: impl IntoIterator<Item = &'a Session>) -> Vec<Vec<String>> {
+    let mut rows = vec![HEADERS.iter().map(|h| h.to_string()).collect()];
     for session in sessions {
         let identity = session
             .identity_file
@@ -35,16 +211,32 @@ pub fn print_sessions(sessions: &[Session], theme: &CliTheme) {
         } else {
             "-"
         };
-        println!(
-            "{}\t{}\t{}\t{}\t{}\t{}",
-            colorize(&session.name, theme.name, use_color),
-            colorize(&session.target(), theme.target, use_color),
-            colorize(&session.port.to_string(), theme.port, use_color),
-            colorize(&identity, theme.identity, use_color),
-            colorize(&tags, theme.tags, use_color),
-            colorize(password_indicator, theme.tags, use_color)
-        );
+        rows.push(vec![
+            session.name.clone(),
+            session.target(),
+            session.port.to_string(),
+            identity,
+            tags,
+            password_indicator.to_string(),
+            session.connect_count.to_string(),
+        ]);
     }
+    rows
+}
+
+fn column_widths<'a>(rows: impl IntoIterator<Item = &'a Vec<String>>) -> Vec<usize> {
+    let mut widths = Vec::new();
+    for row in rows {
+        for (index, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(index) {
+                Some(existing) if *existing < width => *existing = width,
+                Some(_) => {}
+                None => widths.push(width),
+            }
+        }
+    }
+    widths
 }
 
 fn colorize(text: &str, color: crossterm::style::Color, enabled: bool) -> String {
@@ -60,7 +252,6 @@ mod tests {
     use super::*;
     use crossterm::style::Color;
 
-    #[allow(dead_code)]
     fn default_theme() -> CliTheme {
         CliTheme {
             enabled: false,
@@ -73,20 +264,8 @@ mod tests {
         }
     }
 
-    #[allow(dead_code)]
     fn session(name: &str, host: &str, user: &str, port: u16) -> Session {
-        Session {
-            name: name.to_string(),
-            host: host.to_string(),
-            user: user.to_string(),
-            port,
-            identity_file: None,
-            tags: vec![],
-            last_connected_at: None,
-            has_stored_password: false,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        }
+        Session::builder(name, host, user).port(port).build()
     }
 
     #[test]
@@ -102,6 +281,142 @@ mod tests {
         assert!(result.contains("test"));
     }
 
+    #[test]
+    fn padded_table_aligns_columns_without_tabs() {
+        let sessions = vec![
+            session("a", "example.com", "me", 22),
+            session("longer-name", "h", "u", 2222),
+        ];
+        let table = render_table(&session_rows(&sessions), TableLayout::Aligned, None);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(!table.contains('\t'));
+        let target_column = lines[0].find("TARGET").unwrap();
+        assert_eq!(lines[1].find("me@example.com").unwrap(), target_column);
+        assert_eq!(lines[2].find("u@h").unwrap(), target_column);
+        assert!(lines.iter().all(|line| !line.ends_with(' ')));
+    }
+
+    #[test]
+    fn colored_alignment_ignores_escape_codes() {
+        let sessions = vec![
+            session("a", "example.com", "me", 22),
+            session("longer-name", "h", "u", 2222),
+        ];
+        let mut theme = default_theme();
+        theme.enabled = true;
+        let colored = render_table(&session_rows(&sessions), TableLayout::Aligned, Some(&theme));
+        let plain = render_table(&session_rows(&sessions), TableLayout::Aligned, None);
+
+        let strip = |text: &str| {
+            let mut out = String::new();
+            let mut in_escape = false;
+            for ch in text.chars() {
+                match (in_escape, ch) {
+                    (false, '\x1b') => in_escape = true,
+                    (true, 'm') => in_escape = false,
+                    (true, _) => {}
+                    (false, ch) => out.push(ch),
+                }
+            }
+            out
+        };
+        assert_eq!(strip(&colored), plain);
+    }
+
+    #[test]
+    fn tabs_layout_joins_cells_with_tabs() {
+        let sessions = vec![session("a", "example.com", "me", 22)];
+        let table = render_table(&session_rows(&sessions), TableLayout::Tabs, None);
+        assert_eq!(
+            table.lines().nth(1),
+            Some("a\tme@example.com\t22\t-\t-\t-\t0")
+        );
+    }
+
+    #[test]
+    fn grouping_lists_sessions_under_each_tag() {
+        let tagged = |name: &str, tags: &[&str]| {
+            Session::builder(name, "h", "u")
+                .tags(tags.iter().map(|tag| tag.to_string()).collect())
+                .build()
+        };
+        let sessions = vec![
+            tagged("api", &["prod", "backend"]),
+            tagged("scratch", &[]),
+            tagged("web", &["prod"]),
+        ];
+
+        let names = |groups: Vec<(Option<&str>, Vec<&Session>)>| -> Vec<(String, Vec<String>)> {
+            groups
+                .into_iter()
+                .map(|(tag, members)| {
+                    let names = members.iter().map(|s| s.name.clone()).collect();
+                    (tag.unwrap_or("untagged").to_string(), names)
+                })
+                .collect()
+        };
+        assert_eq!(
+            names(group_by_tag(&sessions, false)),
+            [
+                ("backend".to_string(), vec!["api".to_string()]),
+                (
+                    "prod".to_string(),
+                    vec!["api".to_string(), "web".to_string()]
+                ),
+                ("untagged".to_string(), vec!["scratch".to_string()]),
+            ]
+        );
+        assert_eq!(
+            names(group_by_tag(&sessions, true)),
+            [
+                (
+                    "prod".to_string(),
+                    vec!["api".to_string(), "web".to_string()]
+                ),
+                ("untagged".to_string(), vec!["scratch".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_tag_named_none_stays_apart_from_untagged_sessions() {
+        let sessions = vec![
+            Session::builder("odd", "h", "u")
+                .tags(vec!["(none)".to_string()])
+                .build(),
+            Session::builder("scratch", "h", "u").build(),
+        ];
+        let groups = group_by_tag(&sessions, false);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, Some("(none)"));
+        assert_eq!(groups[0].1[0].name, "odd");
+        assert_eq!(groups[1].0, None);
+        assert_eq!(groups[1].1[0].name, "scratch");
+    }
+
+    #[test]
+    fn grouped_table_indents_rows_and_aligns_across_groups() {
+        let sessions = vec![
+            Session::builder("a", "example.com", "me")
+                .tags(vec!["prod".to_string()])
+                .build(),
+            session("longer-name", "h", "u", 2222),
+        ];
+        let groups = group_by_tag(&sessions, false);
+        let table = render_grouped_table(&groups, None);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("  NAME"));
+        assert_eq!(lines[1], "prod");
+        assert_eq!(lines[3], "(none)");
+        let target_column = lines[0].find("TARGET").unwrap();
+        assert_eq!(lines[2].find("me@example.com").unwrap(), target_column);
+        assert_eq!(lines[4].find("u@h").unwrap(), target_column);
+    }
+
     // Note: Testing print_sessions is difficult as it prints to stdout
     // The function is simple enough that manual testing covers the main cases
 }
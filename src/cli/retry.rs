@@ -0,0 +1,266 @@
+//! `se go --retry`: try ssh again while the host cannot be reached, e.g. while it
+//! reboots. Authentication failures and remote exit codes are never retried.
+
+use crate::model::Session;
+use anyhow::Result;
+use std::io;
+use std::time::Duration;
+
+/// Upper bound for the doubling delay between attempts.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// How many times to repeat a failed connection, and the first wait between tries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    pub retries: u32,
+    pub delay: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl Retry {
+    /// Wait before retry number `retry` (1-based): the delay doubles each time, up to
+    /// a minute.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.delay.saturating_mul(factor).min(MAX_RETRY_DELAY)
+    }
+}
+
+/// Run `attempt` until it succeeds, fails for a reason other than reaching the host,
+/// or `retry.retries` extra attempts have failed. Each retry is reported on stderr.
+pub fn run_with_retry(
+    retry: Retry,
+    target: &str,
+    mut attempt: impl FnMut() -> Result<()>,
+    mut sleep: impl FnMut(Duration),
+) -> Result<()> {
+    let total = retry.retries.saturating_add(1);
+    let mut number = 0;
+    loop {
+        number += 1;
+        let err = match attempt() {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        if number >= total || !is_connection_failure(&err) {
+            return Err(err);
+        }
+        let wait = retry.backoff(number);
+        eprintln!(
+            "Attempt {}/{}: could not reach {} ({:#}); retrying in {}s",
+            number,
+            total,
+            target,
+            err,
+            wait.as_secs()
+        );
+        sleep(wait);
+    }
+}
+
+/// Whether `err` means the server could not be reached, as opposed to ssh failing to
+/// start (binary missing) or the connection being refused by authentication.
+pub fn is_connection_failure(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<io::Error>())
+        .any(|io_err| {
+            matches!(
+                io_err.kind(),
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::HostUnreachable
+                    | io::ErrorKind::NetworkUnreachable
+                    | io::ErrorKind::AddrNotAvailable
+            )
+        })
+}
+
+/// ssh settings that change where the first connection goes.
+const ROUTING_OPTIONS: [&str; 4] = ["HostName", "Port", "ProxyCommand", "ProxyJump"];
+
+/// Host and port ssh dials first: the first `ProxyJump` hop, or the session itself.
+/// `None` when the session's ssh options or arguments reroute the connection, since
+/// probing the saved host would then say nothing about ssh's failure.
+pub fn first_hop(session: &Session) -> Option<(String, u16)> {
+    if reroutes(session) {
+        return None;
+    }
+    let Some(jump) = session.proxy_jump.as_deref() else {
+        return Some((unbracket(&session.host), session.port));
+    };
+    let hop = jump.split(',').next().unwrap_or(jump).trim();
+    let hop = hop.rsplit_once('@').map_or(hop, |(_, host)| host);
+    Some(match hop.rsplit_once(':') {
+        // A bare IPv6 address has several colons and no port.
+        Some((host, port)) if !host.contains(':') || host.ends_with(']') => {
+            (unbracket(host), port.parse().unwrap_or(22))
+        }
+        _ => (unbracket(hop), 22),
+    })
+}
+
+/// Whether `options` or `extra_args` set one of [`ROUTING_OPTIONS`], or pass `-J` or
+/// `-p` directly. Keywords match case-insensitively, as ssh reads them.
+fn reroutes(session: &Session) -> bool {
+    let routing = |keyword: &str| {
+        ROUTING_OPTIONS
+            .iter()
+            .any(|option| option.eq_ignore_ascii_case(keyword.trim()))
+    };
+    session.options.keys().any(|key| routing(key))
+        || session.extra_args.iter().any(|arg| {
+            if arg.starts_with("-J") || arg.starts_with("-p") {
+                return true;
+            }
+            // `-oKey=value`, or the `Key=value` / `Key value` after a separate `-o`
+            let option = arg.strip_prefix("-o").unwrap_or(arg);
+            option.split(['=', ' ', '\t']).next().is_some_and(routing)
+        })
+}
+
+fn unbracket(host: &str) -> String {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, anyhow};
+
+    fn refused() -> anyhow::Error {
+        Err::<(), _>(io::Error::from(io::ErrorKind::ConnectionRefused))
+            .context("failed to connect to SSH server")
+            .unwrap_err()
+    }
+
+    #[test]
+    fn retries_connection_failures_with_a_doubling_delay() {
+        let retry = Retry {
+            retries: 3,
+            delay: Duration::from_secs(1),
+        };
+        let mut calls = 0;
+        let mut waits = Vec::new();
+        let result = run_with_retry(
+            retry,
+            "me@web",
+            || {
+                calls += 1;
+                if calls < 3 { Err(refused()) } else { Ok(()) }
+            },
+            |wait| waits.push(wait),
+        );
+        assert!(result.is_ok());
+        assert_eq!(calls, 3);
+        assert_eq!(waits, [Duration::from_secs(1), Duration::from_secs(2)]);
+    }
+
+    #[test]
+    fn gives_up_after_the_last_retry() {
+        let retry = Retry {
+            retries: 1,
+            delay: Duration::ZERO,
+        };
+        let mut calls = 0;
+        let result = run_with_retry(
+            retry,
+            "me@web",
+            || {
+                calls += 1;
+                Err(refused())
+            },
+            |_| {},
+        );
+        assert!(is_connection_failure(&result.unwrap_err()));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn other_failures_are_not_retried() {
+        let mut calls = 0;
+        let result = run_with_retry(
+            Retry {
+                retries: 5,
+                delay: Duration::ZERO,
+            },
+            "me@web",
+            || {
+                calls += 1;
+                Err(anyhow!("SSH authentication failed"))
+            },
+            |_| {},
+        );
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+
+        let missing = Err::<(), _>(io::Error::from(io::ErrorKind::NotFound))
+            .context("failed to execute ssh")
+            .unwrap_err();
+        assert!(!is_connection_failure(&missing));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let retry = Retry {
+            retries: 10,
+            delay: Duration::from_secs(5),
+        };
+        assert_eq!(retry.backoff(1), Duration::from_secs(5));
+        assert_eq!(retry.backoff(3), Duration::from_secs(20));
+        assert_eq!(retry.backoff(8), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn first_hop_prefers_the_jump_host() {
+        let mut session = Session::builder("web", "[fd00::5]", "me")
+            .port(2222)
+            .build();
+        assert_eq!(first_hop(&session), Some(("fd00::5".to_string(), 2222)));
+
+        session.proxy_jump = Some("ops@bastion.example.com:2200,inner".to_string());
+        assert_eq!(
+            first_hop(&session),
+            Some(("bastion.example.com".to_string(), 2200))
+        );
+        session.proxy_jump = Some("bastion".to_string());
+        assert_eq!(first_hop(&session), Some(("bastion".to_string(), 22)));
+        session.proxy_jump = Some("[fd00::1]:2201".to_string());
+        assert_eq!(first_hop(&session), Some(("fd00::1".to_string(), 2201)));
+    }
+
+    #[test]
+    fn no_first_hop_when_options_reroute_ssh() {
+        let session = Session::builder("web", "web", "me").build();
+        let rerouted = |options: &[(&str, &str)], args: &[&str]| {
+            let mut session = session.clone();
+            session.options = options
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            session.extra_args = args.iter().map(|arg| arg.to_string()).collect();
+            first_hop(&session).is_none()
+        };
+        assert!(!rerouted(
+            &[("ServerAliveInterval", "30")],
+            &["-A", "-oUser=x"]
+        ));
+        assert!(rerouted(&[("proxycommand", "nc %h %p")], &[]));
+        assert!(rerouted(&[("HostName", "10.0.0.5")], &[]));
+        assert!(rerouted(&[], &["-oPort=2222"]));
+        assert!(rerouted(&[], &["-o", "HostName 10.0.0.5"]));
+        assert!(rerouted(&[], &["-J", "bastion"]));
+        assert!(rerouted(&[], &["-p2222"]));
+    }
+}
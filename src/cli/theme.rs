@@ -1,5 +1,7 @@
-use anyhow::{Context, Result, anyhow};
-use directories::ProjectDirs;
+use crate::config_check::ensure_known_keys;
+use crate::dirs::resolve_config_dir;
+use crate::store::StoreFormat;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -14,6 +16,8 @@ pub struct CliThemeConfig {
     pub port: String,
     pub identity: String,
     pub tags: String,
+    /// Format of the default store when it does not exist yet
+    pub store_format: Option<StoreFormat>,
 }
 
 impl Default for CliThemeConfig {
@@ -26,6 +30,7 @@ impl Default for CliThemeConfig {
             port: "Magenta".to_string(),
             identity: "Blue".to_string(),
             tags: "DarkGray".to_string(),
+            store_format: None,
         }
     }
 }
@@ -55,15 +60,25 @@ impl CliTheme {
 }
 
 pub fn load_cli_theme(override_path: Option<PathBuf>) -> Result<CliTheme> {
+    load_cli_config(override_path).map(CliTheme::from_config)
+}
+
+/// `store_format` from `cli.json`, the default format for a newly created store.
+pub fn configured_store_format(override_path: Option<PathBuf>) -> Result<Option<StoreFormat>> {
+    load_cli_config(override_path).map(|config| config.store_format)
+}
+
+fn load_cli_config(override_path: Option<PathBuf>) -> Result<CliThemeConfig> {
     let path = resolve_cli_theme_path(override_path)?;
     if let Some(path) = path {
+        log::debug!("loading CLI config from {}", path.display());
         let data = fs::read_to_string(&path)
             .with_context(|| format!("unable to read {}", path.display()))?;
-        let config = serde_json::from_str(&data)
-            .with_context(|| format!("unable to parse {}", path.display()))?;
-        return Ok(CliTheme::from_config(config));
+        ensure_known_keys::<CliThemeConfig>(&data, &path)?;
+        return serde_json::from_str(&data)
+            .with_context(|| format!("unable to parse {}", path.display()));
     }
-    Ok(CliTheme::from_config(CliThemeConfig::default()))
+    Ok(CliThemeConfig::default())
 }
 
 fn resolve_cli_theme_path(override_path: Option<PathBuf>) -> Result<Option<PathBuf>> {
@@ -71,9 +86,7 @@ fn resolve_cli_theme_path(override_path: Option<PathBuf>) -> Result<Option<PathB
         return Ok(Some(path));
     }
 
-    let project_dirs = ProjectDirs::from("", "", "ssher")
-        .ok_or_else(|| anyhow!("unable to resolve config directory"))?;
-    let candidate = project_dirs.config_dir().join("cli.json");
+    let candidate = cli_theme_candidate_path(None)?;
     if candidate.exists() {
         Ok(Some(candidate))
     } else {
@@ -81,6 +94,15 @@ fn resolve_cli_theme_path(override_path: Option<PathBuf>) -> Result<Option<PathB
     }
 }
 
+/// Path the CLI theme is read from, whether or not the file exists.
+pub fn cli_theme_candidate_path(override_path: Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(path) = override_path {
+        return Ok(path);
+    }
+
+    Ok(resolve_config_dir()?.join("cli.json"))
+}
+
 fn parse_color(name: &str) -> crossterm::style::Color {
     match name.to_lowercase().as_str() {
         "black" => crossterm::style::Color::Black,
@@ -129,6 +151,7 @@ mod tests {
             port: "Yellow".to_string(),
             identity: "Cyan".to_string(),
             tags: "White".to_string(),
+            store_format: None,
         };
         let theme = CliTheme::from_config(config);
         assert!(!theme.enabled);
@@ -1,7 +1,7 @@
 use crate::cli::theme::CliThemeConfig;
+use crate::dirs::resolve_config_dir;
 use crate::ui::{ThemeConfig, UiConfig};
 use anyhow::{Context, Result, anyhow};
-use directories::ProjectDirs;
 use serde::Deserialize;
 use std::fs;
 use std::path::PathBuf;
@@ -33,9 +33,7 @@ pub fn get_themes_dir() -> Result<PathBuf> {
     }
 
     // Fall back to system config directory
-    let project_dirs = ProjectDirs::from("", "", "ssher")
-        .ok_or_else(|| anyhow!("unable to resolve config directory"))?;
-    let system_themes = project_dirs.config_dir().join("themes");
+    let system_themes = resolve_config_dir()?.join("themes");
 
     if system_themes.exists() {
         return Ok(system_themes);
@@ -109,12 +107,10 @@ pub fn find_theme(name: &str) -> Result<ThemePreset> {
 
 /// Apply a CLI theme by writing cli.json to config directory
 pub fn apply_cli_theme(theme: &CliThemeConfig) -> Result<()> {
-    let project_dirs = ProjectDirs::from("", "", "ssher")
-        .ok_or_else(|| anyhow!("unable to resolve config directory"))?;
-    let config_dir = project_dirs.config_dir();
+    let config_dir = resolve_config_dir()?;
 
     // Create config directory if it doesn't exist
-    fs::create_dir_all(config_dir).with_context(|| {
+    fs::create_dir_all(&config_dir).with_context(|| {
         format!(
             "failed to create config directory: {}",
             config_dir.display()
@@ -145,12 +141,10 @@ pub fn apply_cli_theme(theme: &CliThemeConfig) -> Result<()> {
 
 /// Apply a UI theme by writing ui.json to config directory
 pub fn apply_ui_theme(theme: &ThemeConfig) -> Result<()> {
-    let project_dirs = ProjectDirs::from("", "", "ssher")
-        .ok_or_else(|| anyhow!("unable to resolve config directory"))?;
-    let config_dir = project_dirs.config_dir();
+    let config_dir = resolve_config_dir()?;
 
     // Create config directory if it doesn't exist
-    fs::create_dir_all(config_dir).with_context(|| {
+    fs::create_dir_all(&config_dir).with_context(|| {
         format!(
             "failed to create config directory: {}",
             config_dir.display()
@@ -179,12 +173,8 @@ pub fn apply_ui_theme(theme: &ThemeConfig) -> Result<()> {
         config
     } else {
         UiConfig {
-            logo: Default::default(),
-            layout: Default::default(),
             theme: theme.clone(),
-            input: Default::default(),
-            ordering: Default::default(),
-            highlights: Default::default(),
+            ..UiConfig::default()
         }
     };
 
@@ -200,9 +190,7 @@ pub fn apply_ui_theme(theme: &ThemeConfig) -> Result<()> {
 
 /// Detect the currently active theme by reading config files
 pub fn detect_current_theme() -> Result<(Option<String>, Option<String>)> {
-    let project_dirs = ProjectDirs::from("", "", "ssher")
-        .ok_or_else(|| anyhow!("unable to resolve config directory"))?;
-    let config_dir = project_dirs.config_dir();
+    let config_dir = resolve_config_dir()?;
 
     let cli_config_path = config_dir.join("cli.json");
     let ui_config_path = config_dir.join("ui.json");
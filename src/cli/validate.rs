@@ -0,0 +1,142 @@
+use crate::cli::theme::CliThemeConfig;
+use crate::config_check::{join_key, unknown_keys};
+use crate::ui::UiConfig;
+use crate::ui::config::{COLOR_NAMES, is_color_name};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Check a UI config file; returns one message per problem.
+pub fn validate_ui_config(path: &Path) -> Result<Vec<String>> {
+    let mut problems = validate_file::<UiConfig>(path, &["theme", "highlights"])?;
+    if let Some(config) = fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<UiConfig>(&data).ok())
+    {
+        problems.extend(config.layout.problems());
+        problems.extend(config.columns.problems());
+        problems.extend(config.input.problems());
+    }
+    Ok(problems)
+}
+
+/// Check a CLI theme file; returns one message per problem.
+pub fn validate_cli_config(path: &Path) -> Result<Vec<String>> {
+    validate_file::<CliThemeConfig>(path, &[""])
+}
+
+/// Parse `path` as `T`, reporting keys `T` does not know about and unknown color
+/// names in the string fields of `color_sections` (`""` is the top level). Only
+/// fields whose default is a string are colors.
+fn validate_file<T>(path: &Path, color_sections: &[&str]) -> Result<Vec<String>>
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    let data =
+        fs::read_to_string(path).with_context(|| format!("unable to read {}", path.display()))?;
+    let value: Value = match serde_json::from_str(&data) {
+        Ok(value) => value,
+        Err(err) => return Ok(vec![format!("invalid JSON: {}", err)]),
+    };
+    let mut problems = unknown_keys::<T>(&value)?;
+    let defaults = serde_json::to_value(T::default())?;
+    if let Err(err) = serde_json::from_value::<T>(value.clone()) {
+        problems.push(format!("invalid value: {}", err));
+    }
+    for section in color_sections {
+        let fields = if section.is_empty() {
+            Some(&value)
+        } else {
+            value.get(section)
+        };
+        let Some(Value::Object(fields)) = fields else {
+            continue;
+        };
+        let section_defaults = if section.is_empty() {
+            Some(&defaults)
+        } else {
+            defaults.get(section)
+        };
+        for (key, color) in fields {
+            let is_color_field = section_defaults
+                .and_then(|defaults| defaults.get(key))
+                .is_none_or(Value::is_string);
+            if let Value::String(color) = color
+                && is_color_field
+                && !is_color_name(color)
+            {
+                problems.push(format!(
+                    "{}: unknown color '{}' (expected one of: {})",
+                    join_key(section, key),
+                    color,
+                    COLOR_NAMES.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_config(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().expect("temp file");
+        file.write_all(contents.as_bytes()).expect("write config");
+        file
+    }
+
+    #[test]
+    fn reports_misspelled_keys_with_suggestion() {
+        let file = write_config(r#"{"layout": {"shw_logo": false}, "colours": {}}"#);
+        let problems = validate_ui_config(file.path()).unwrap();
+        assert_eq!(
+            problems,
+            vec![
+                "colours: unknown key".to_string(),
+                "layout.shw_logo: unknown key (did you mean 'show_logo'?)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_colors_and_bad_types() {
+        let file =
+            write_config(r#"{"theme": {"border": "Purple"}, "layout": {"logo_height": "tall"}}"#);
+        let problems = validate_ui_config(file.path()).unwrap();
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert!(problems[0].starts_with("invalid value:"));
+        assert!(problems[1].starts_with("theme.border: unknown color 'Purple'"));
+    }
+
+    #[test]
+    fn reports_narrow_columns() {
+        let file = write_config(r#"{"columns": {"port": 2, "tags": "auto"}}"#);
+        let problems = validate_ui_config(file.path()).unwrap();
+        assert_eq!(
+            problems,
+            vec!["columns.port: width 2 is below the minimum of 3".to_string()]
+        );
+    }
+
+    #[test]
+    fn accepts_valid_cli_config() {
+        let file = write_config(
+            r#"{"enabled": true, "header": "light_blue", "tags": "DarkGray", "store_format": "sqlite"}"#,
+        );
+        assert!(validate_cli_config(file.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_invalid_json() {
+        let file = write_config("{ not json");
+        let problems = validate_cli_config(file.path()).unwrap();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("invalid JSON:"));
+    }
+}
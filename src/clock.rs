@@ -0,0 +1,13 @@
+//! Wall-clock time, read in one place. Time-dependent logic (recency ordering,
+//! highlighting, "5m ago" labels) takes `now` as an argument instead of reading the
+//! clock itself, so tests can pin it.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch; 0 if the system clock is set before it.
+pub fn now_epoch_seconds() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
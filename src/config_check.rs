@@ -0,0 +1,124 @@
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable that turns on strict config loading (`1`, `true`, or `yes`).
+pub const STRICT_CONFIG_ENV: &str = "SSHER_STRICT_CONFIG";
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_strict(strict: bool) {
+    STRICT.store(strict, Ordering::Relaxed);
+}
+
+/// Whether config files must not contain keys ssher does not recognize.
+pub fn is_strict() -> bool {
+    STRICT.load(Ordering::Relaxed)
+        || std::env::var(STRICT_CONFIG_ENV)
+            .is_ok_and(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+}
+
+/// In strict mode, fail on the first key in `data` that `T` does not know about.
+/// Lenient (the default) accepts anything so newer config files keep loading.
+pub fn ensure_known_keys<T: Default + Serialize>(data: &str, path: &Path) -> Result<()> {
+    if !is_strict() {
+        return Ok(());
+    }
+    let value: Value = serde_json::from_str(data)
+        .with_context(|| format!("unable to parse {}", path.display()))?;
+    match unknown_keys::<T>(&value)?.into_iter().next() {
+        Some(problem) => Err(anyhow!(
+            "{} in {} (strict config mode)",
+            problem,
+            path.display()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Describe every key in `value` that has no counterpart in `T`'s serialized defaults,
+/// e.g. `layout.shw_logo: unknown key (did you mean 'show_logo'?)`.
+pub fn unknown_keys<T: Default + Serialize>(value: &Value) -> Result<Vec<String>> {
+    let known = serde_json::to_value(T::default()).context("failed to serialize defaults")?;
+    let mut problems = Vec::new();
+    collect_unknown_keys(value, &known, "", &mut problems);
+    Ok(problems)
+}
+
+fn collect_unknown_keys(value: &Value, known: &Value, prefix: &str, problems: &mut Vec<String>) {
+    let (Value::Object(fields), Value::Object(known_fields)) = (value, known) else {
+        return;
+    };
+    for (key, child) in fields {
+        let path = join_key(prefix, key);
+        match known_fields.get(key) {
+            Some(known_child) => collect_unknown_keys(child, known_child, &path, problems),
+            None => {
+                let hint = known_fields
+                    .keys()
+                    .map(|candidate| (edit_distance(key, candidate), candidate))
+                    .filter(|(distance, _)| *distance <= 2)
+                    .min()
+                    .map(|(_, candidate)| format!(" (did you mean '{}'?)", candidate))
+                    .unwrap_or_default();
+                problems.push(format!("{}: unknown key{}", path, hint));
+            }
+        }
+    }
+}
+
+pub fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::UiConfig;
+
+    #[test]
+    fn unknown_keys_suggests_close_matches() {
+        let value: Value =
+            serde_json::from_str(r#"{"highligts": {}, "layout": {"show_logo": true}}"#).unwrap();
+        assert_eq!(
+            unknown_keys::<UiConfig>(&value).unwrap(),
+            vec!["highligts: unknown key (did you mean 'highlights'?)".to_string()]
+        );
+    }
+
+    #[test]
+    fn ensure_known_keys_only_fails_in_strict_mode() {
+        let data = r#"{"theme": {"bordr": "Red"}}"#;
+        let path = Path::new("ui.json");
+        temp_env::with_var(STRICT_CONFIG_ENV, None::<&str>, || {
+            assert!(ensure_known_keys::<UiConfig>(data, path).is_ok());
+        });
+        temp_env::with_var(STRICT_CONFIG_ENV, Some("1"), || {
+            let err = ensure_known_keys::<UiConfig>(data, path).unwrap_err();
+            assert!(
+                err.to_string().contains("theme.bordr: unknown key"),
+                "{err}"
+            );
+        });
+    }
+}
@@ -0,0 +1,25 @@
+use anyhow::{Result, anyhow};
+use directories::ProjectDirs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Environment variable that relocates every ssher config file at once.
+pub const CONFIG_DIR_ENV: &str = "SSHER_CONFIG_DIR";
+
+static CONFIG_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` instead of the platform config directory for the rest of the process.
+pub fn set_config_dir_override(dir: PathBuf) {
+    let _ = CONFIG_DIR_OVERRIDE.set(dir);
+}
+
+/// Directory holding `sessions.json`, `ui.json`, `cli.json`, and other ssher state:
+/// the `--config-dir` override if set, otherwise `~/.config/ssher` (platform equivalent).
+pub fn resolve_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = CONFIG_DIR_OVERRIDE.get() {
+        return Ok(dir.clone());
+    }
+    let project_dirs = ProjectDirs::from("", "", "ssher")
+        .ok_or_else(|| anyhow!("unable to resolve config directory"))?;
+    Ok(project_dirs.config_dir().to_path_buf())
+}
@@ -0,0 +1,748 @@
+//! Session export formatters shared by `se export` and the TUI export selection.
+
+use crate::model::Session;
+use crate::openssh;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Yaml,
+    Csv,
+    SshConfig,
+}
+
+impl ExportFormat {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Json => Self::Yaml,
+            Self::Yaml => Self::Csv,
+            Self::Csv => Self::SshConfig,
+            Self::SshConfig => Self::Json,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Csv => "csv",
+            Self::SshConfig => "ssh-config",
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
+pub enum ExportGroupBy {
+    Tag,
+}
+
+/// Key that orders ssh-config hosts within each group; ties keep name order.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportSort {
+    Name,
+    Host,
+    User,
+    /// Most recently connected first, never-connected hosts last
+    Recent,
+}
+
+impl ExportSort {
+    fn sort(self, sessions: &mut [&Session]) {
+        sessions.sort_by(|a, b| a.name.cmp(&b.name));
+        match self {
+            ExportSort::Name => {}
+            ExportSort::Host => sessions.sort_by(|a, b| a.host.cmp(&b.host)),
+            ExportSort::User => sessions.sort_by(|a, b| a.user.cmp(&b.user)),
+            ExportSort::Recent => {
+                sessions.sort_by_key(|session| std::cmp::Reverse(session.last_connected_at))
+            }
+        }
+    }
+}
+
+/// Starting layout for ssh-config stanzas; `SshConfigStyle` fine-tunes it.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SshConfigTemplate {
+    /// Header, every field, and a `# Tags:` comment
+    #[default]
+    Full,
+    /// No header or tags comment; `HostName` and `Port` only when they add something
+    Minimal,
+}
+
+/// How `export_to_ssh_config_with` lays out the file and each `Host` stanza. Which
+/// fields appear is up to the style; their order is fixed (`HostName`, `User`,
+/// `Port`, `IdentityFile`, then the OpenSSH-only settings).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SshConfigStyle {
+    /// Comment line at the top of the file; `None` writes no header
+    pub header: Option<String>,
+    /// Write `# Tags: ...` under hosts that have tags
+    pub tags_comment: bool,
+    /// Write `Port 22` too, not only other ports
+    pub always_port: bool,
+    /// Write `HostName` even when it matches the `Host` name
+    pub always_hostname: bool,
+}
+
+pub const SSH_CONFIG_HEADER: &str = "Generated by ssher";
+
+impl Default for SshConfigStyle {
+    fn default() -> Self {
+        Self::from(SshConfigTemplate::Full)
+    }
+}
+
+impl From<SshConfigTemplate> for SshConfigStyle {
+    fn from(template: SshConfigTemplate) -> Self {
+        let full = template == SshConfigTemplate::Full;
+        Self {
+            header: full.then(|| SSH_CONFIG_HEADER.to_string()),
+            tags_comment: full,
+            always_port: full,
+            always_hostname: full,
+        }
+    }
+}
+
+/// A field `se export --format csv --columns ...` can include.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[value(rename_all = "snake_case")]
+pub enum CsvColumn {
+    Name,
+    Host,
+    User,
+    Port,
+    IdentityFile,
+    Tags,
+    ProxyJump,
+    LastConnectedAt,
+    LastDurationSecs,
+    AvgDurationSecs,
+    DefaultLocalDir,
+    ConnectCount,
+}
+
+impl CsvColumn {
+    /// The columns exported when `--columns` is not given.
+    pub const DEFAULT: &'static [CsvColumn] = &[
+        Self::Name,
+        Self::Host,
+        Self::User,
+        Self::Port,
+        Self::IdentityFile,
+        Self::Tags,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Host => "host",
+            Self::User => "user",
+            Self::Port => "port",
+            Self::IdentityFile => "identity_file",
+            Self::Tags => "tags",
+            Self::ProxyJump => "proxy_jump",
+            Self::LastConnectedAt => "last_connected_at",
+            Self::LastDurationSecs => "last_duration_secs",
+            Self::AvgDurationSecs => "avg_duration_secs",
+            Self::DefaultLocalDir => "default_local_dir",
+            Self::ConnectCount => "connect_count",
+        }
+    }
+
+    /// The unescaped cell for `session`; missing values are empty.
+    fn value(self, session: &Session) -> String {
+        fn opt<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(ToString::to_string).unwrap_or_default()
+        }
+        match self {
+            Self::Name => session.name.clone(),
+            Self::Host => session.host.clone(),
+            Self::User => session.user.clone(),
+            Self::Port => session.port.to_string(),
+            Self::IdentityFile => session
+                .identity_file
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            Self::Tags => session.tags.join(";"),
+            Self::ProxyJump => opt(&session.proxy_jump),
+            Self::LastConnectedAt => opt(&session.last_connected_at),
+            Self::LastDurationSecs => opt(&session.last_duration_secs),
+            Self::AvgDurationSecs => opt(&session.avg_duration_secs),
+            Self::DefaultLocalDir => opt(&session.default_local_dir),
+            Self::ConnectCount => session.connect_count.to_string(),
+        }
+    }
+}
+
+/// Render `sessions` in `format` with stored passwords stripped.
+pub fn render_export(sessions: &[Session], format: ExportFormat) -> Result<String> {
+    let sanitized = sanitize_export_sessions(sessions);
+    Ok(match format {
+        ExportFormat::Json => export_to_json(&sanitized)?,
+        ExportFormat::Yaml => export_to_yaml(&sanitized)?,
+        ExportFormat::Csv => export_to_csv(&sanitized),
+        ExportFormat::SshConfig => export_to_ssh_config(&sanitized, None, None),
+    })
+}
+
+pub fn sanitize_export_sessions(sessions: &[Session]) -> Vec<Session> {
+    sessions
+        .iter()
+        .cloned()
+        .map(|mut session| {
+            session.stored_password = None;
+            session
+        })
+        .collect()
+}
+
+/// Replace hosts, users, jump hosts, identity files and local dirs with numbered
+/// placeholders (`host1.example.com`, `user1`, ...) so a store can be shared in a bug
+/// report. Forwards keep their ports, ssh options and arguments keep their keys and
+/// flags; the hosts and values in them are replaced too.
+/// Equal values map to the same placeholder, so names, ports, tags, ordering and
+/// which sessions share a host all survive.
+pub fn anonymize_export_sessions(sessions: &[Session]) -> Vec<Session> {
+    let mut hosts = Placeholders::default();
+    let mut users = Placeholders::default();
+    let mut identities = Placeholders::default();
+    let mut local_dirs = Placeholders::default();
+    let mut jumps = Placeholders::default();
+    let mut values = Placeholders::default();
+    sessions
+        .iter()
+        .cloned()
+        .map(|mut session| {
+            session.host = hosts.get(&session.host, |n| format!("host{n}.example.com"));
+            session.user = users.get(&session.user, |n| format!("user{n}"));
+            session.identity_file = session.identity_file.map(|path| {
+                identities
+                    .get(&path.to_string_lossy(), |n| format!("~/.ssh/id_key{n}"))
+                    .into()
+            });
+            session.default_local_dir = session
+                .default_local_dir
+                .map(|dir| local_dirs.get(&dir, |n| format!("~/dir{n}")));
+            session.proxy_jump = session
+                .proxy_jump
+                .map(|jump| jumps.get(&jump, |n| format!("jump{n}.example.com")));
+            for forwards in [&mut session.local_forwards, &mut session.remote_forwards] {
+                for spec in forwards.iter_mut() {
+                    *spec = anonymize_forward(spec, &mut hosts);
+                }
+            }
+            for value in session.options.values_mut() {
+                *value = values.get(value, |n| format!("value{n}"));
+            }
+            for arg in &mut session.extra_args {
+                *arg = anonymize_arg(arg, &mut values);
+            }
+            session
+        })
+        .collect()
+}
+
+/// A forward spec with its bind address and target host replaced; the ports stay.
+fn anonymize_forward(spec: &str, hosts: &mut Placeholders) -> String {
+    let mut host = |value: &str| hosts.get(value, |n| format!("host{n}.example.com"));
+    match openssh::split_forward(spec).as_slice() {
+        [port, target, target_port] => {
+            let target = host(target);
+            format!("{port}:{target}:{target_port}")
+        }
+        [bind, port, target, target_port] => {
+            let bind = host(bind);
+            let target = host(target);
+            format!("{bind}:{port}:{target}:{target_port}")
+        }
+        _ => host(spec),
+    }
+}
+
+/// An ssh argument with anything that could name a host replaced: flags such as
+/// `-A` stay, `-oKey=value` and `Key=value` keep their key, and any other argument
+/// becomes a placeholder.
+fn anonymize_arg(arg: &str, values: &mut Placeholders) -> String {
+    if let Some(flags) = arg.strip_prefix('-')
+        && !flags.is_empty()
+        && flags.chars().all(|ch| ch.is_ascii_alphabetic())
+    {
+        return arg.to_string();
+    }
+    if let Some((key, value)) = arg.split_once('=') {
+        let keyword = key.strip_prefix("-o").unwrap_or(key);
+        if !keyword.is_empty() && keyword.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+            return format!("{key}={}", values.get(value, |n| format!("value{n}")));
+        }
+    }
+    values.get(arg, |n| format!("value{n}"))
+}
+
+/// Hands out placeholders numbered from 1 in order of first appearance.
+#[derive(Default)]
+struct Placeholders(HashMap<String, String>);
+
+impl Placeholders {
+    fn get(&mut self, value: &str, make: impl Fn(usize) -> String) -> String {
+        let next = self.0.len() + 1;
+        self.0
+            .entry(value.to_string())
+            .or_insert_with(|| make(next))
+            .clone()
+    }
+}
+
+pub fn export_to_json(sessions: &[Session]) -> Result<String> {
+    serde_json::to_string_pretty(sessions).context("failed to serialize sessions to JSON")
+}
+
+/// The same session list as [`export_to_json`], as a YAML sequence for hand editing.
+pub fn export_to_yaml(sessions: &[Session]) -> Result<String> {
+    serde_yaml::to_string(sessions).context("failed to serialize sessions to YAML")
+}
+
+pub fn export_to_csv(sessions: &[Session]) -> String {
+    export_to_csv_columns(sessions, CsvColumn::DEFAULT)
+}
+
+/// CSV with only `columns`, in the given order; the header row names them the same
+/// way `--columns` does.
+pub fn export_to_csv_columns(sessions: &[Session], columns: &[CsvColumn]) -> String {
+    let mut csv = String::new();
+    let header: Vec<&str> = columns.iter().map(|column| column.label()).collect();
+    csv.push_str(&header.join(","));
+    csv.push('\n');
+    for session in sessions {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| escape_csv(&column.value(session)))
+            .collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn export_to_ssh_config(
+    sessions: &[Session],
+    group_by: Option<&ExportGroupBy>,
+    sort: Option<ExportSort>,
+) -> String {
+    export_to_ssh_config_with(sessions, group_by, sort, &SshConfigStyle::default())
+}
+
+/// [`export_to_ssh_config`] with a chosen header and stanza layout.
+pub fn export_to_ssh_config_with(
+    sessions: &[Session],
+    group_by: Option<&ExportGroupBy>,
+    sort: Option<ExportSort>,
+    style: &SshConfigStyle,
+) -> String {
+    let mut config = String::new();
+    if let Some(header) = &style.header {
+        config.push_str(&format!("# {}\n\n", header));
+    }
+
+    let mut ordered: Vec<&Session> = sessions.iter().collect();
+    if let Some(sort) = sort {
+        sort.sort(&mut ordered);
+    }
+
+    match group_by {
+        None => {
+            for session in ordered {
+                push_ssh_config_host_with(&mut config, session, style);
+            }
+        }
+        Some(ExportGroupBy::Tag) => {
+            // Group by primary (first) tag; untagged sessions trail under "misc".
+            let mut groups: BTreeMap<&str, Vec<&Session>> = BTreeMap::new();
+            let mut misc = Vec::new();
+            for session in ordered {
+                match session.tags.first() {
+                    Some(tag) => groups.entry(tag.as_str()).or_default().push(session),
+                    None => misc.push(session),
+                }
+            }
+            let sections = groups
+                .into_iter()
+                .chain((!misc.is_empty()).then_some(("misc", misc)));
+            for (tag, members) in sections {
+                config.push_str(&format!("# === {} ===\n\n", tag));
+                for session in members {
+                    push_ssh_config_host_with(&mut config, session, style);
+                }
+            }
+        }
+    }
+    config
+}
+
+pub fn push_ssh_config_host(config: &mut String, session: &Session) {
+    push_ssh_config_host_with(config, session, &SshConfigStyle::default());
+}
+
+pub fn push_ssh_config_host_with(config: &mut String, session: &Session, style: &SshConfigStyle) {
+    config.push_str(&format!("Host {}\n", session.name));
+    if style.always_hostname || session.host != session.name {
+        config.push_str(&format!("    HostName {}\n", session.host));
+    }
+    config.push_str(&format!("    User {}\n", session.user));
+    if style.always_port || session.port != 22 {
+        config.push_str(&format!("    Port {}\n", session.port));
+    }
+    if let Some(identity) = &session.identity_file {
+        config.push_str(&format!("    IdentityFile {}\n", identity.display()));
+    }
+    if session.control_master {
+        config.push_str("    ControlMaster auto\n");
+        config.push_str(&format!(
+            "    ControlPath {}\n",
+            openssh::control_path(session)
+        ));
+    }
+    if let Some(proxy_jump) = &session.proxy_jump {
+        config.push_str(&format!("    ProxyJump {}\n", proxy_jump));
+    }
+    for spec in &session.local_forwards {
+        config.push_str(&format!(
+            "    LocalForward {}\n",
+            openssh::forward_config_value(spec)
+        ));
+    }
+    for spec in &session.remote_forwards {
+        config.push_str(&format!(
+            "    RemoteForward {}\n",
+            openssh::forward_config_value(spec)
+        ));
+    }
+    for (key, value) in &session.options {
+        config.push_str(&format!("    {} {}\n", key, value));
+    }
+    let (options, unmapped) = extra_args_as_options(&session.extra_args);
+    for (key, value) in options {
+        config.push_str(&format!("    {} {}\n", key, value));
+    }
+    if !unmapped.is_empty() {
+        config.push_str(&format!("    # ssh args: {}\n", unmapped.join(" ")));
+    }
+    if style.tags_comment && !session.tags.is_empty() {
+        config.push_str(&format!("    # Tags: {}\n", session.tags.join(", ")));
+    }
+    config.push('\n');
+}
+
+/// Split ssh arguments into the `-o Key=Value` / `-oKey=Value` options ssh_config can
+/// express and the remaining arguments it cannot.
+fn extra_args_as_options(args: &[String]) -> (Vec<(String, String)>, Vec<&str>) {
+    let mut options = Vec::new();
+    let mut unmapped = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let raw = if arg == "-o" {
+            match iter.next() {
+                Some(value) => value.as_str(),
+                None => {
+                    unmapped.push(arg.as_str());
+                    continue;
+                }
+            }
+        } else if let Some(value) = arg.strip_prefix("-o") {
+            value
+        } else {
+            unmapped.push(arg.as_str());
+            continue;
+        };
+        match openssh::parse_option(raw) {
+            Some(option) => options.push(option),
+            None => {
+                unmapped.push("-o");
+                unmapped.push(raw);
+            }
+        }
+    }
+    (options, unmapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn session(name: &str) -> Session {
+        Session {
+            name: name.to_string(),
+            host: "example.com".to_string(),
+            user: "alice".to_string(),
+            port: 22,
+            identity_file: None,
+            tags: vec!["prod".to_string()],
+            last_connected_at: None,
+            has_stored_password: true,
+            passwd_unsafe_mode: None,
+            stored_password: Some("secret".to_string()),
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
+        }
+    }
+
+    #[test]
+    fn render_export_strips_stored_passwords() {
+        let output = render_export(&[session("web")], ExportFormat::Json).unwrap();
+        assert!(output.contains("\"web\""));
+        assert!(!output.contains("secret"));
+    }
+
+    #[test]
+    fn csv_escapes_commas_and_quotes() {
+        let mut odd = session("a,b");
+        odd.user = "say \"hi\"".to_string();
+        let csv = export_to_csv(&[odd]);
+        assert!(csv.contains("\"a,b\",example.com,\"say \"\"hi\"\"\",22"));
+    }
+
+    #[test]
+    fn csv_columns_pick_fields_and_header() {
+        let mut web = session("web,1");
+        web.last_connected_at = Some(1_700_000_000);
+        let columns = [
+            CsvColumn::Name,
+            CsvColumn::LastConnectedAt,
+            CsvColumn::ProxyJump,
+        ];
+        let csv = export_to_csv_columns(&[web, session("db")], &columns);
+        assert_eq!(
+            csv,
+            "name,last_connected_at,proxy_jump\n\"web,1\",1700000000,\ndb,,\n"
+        );
+    }
+
+    #[test]
+    fn csv_default_columns_match_the_original_header() {
+        let csv = export_to_csv(&[]);
+        assert_eq!(csv, "name,host,user,port,identity_file,tags\n");
+    }
+
+    #[test]
+    fn ssh_config_includes_control_master_settings() {
+        let mut shared = session("web");
+        shared.control_master = true;
+        let mut config = String::new();
+        push_ssh_config_host(&mut config, &shared);
+        assert!(config.contains("    ControlMaster auto\n"));
+        assert!(config.contains(&format!(
+            "    ControlPath {}\n",
+            openssh::DEFAULT_CONTROL_PATH
+        )));
+
+        let mut config = String::new();
+        push_ssh_config_host(&mut config, &session("db"));
+        assert!(!config.contains("ControlMaster"));
+        assert!(!config.contains("ProxyJump"));
+    }
+
+    #[test]
+    fn ssh_config_includes_proxy_jump() {
+        let mut internal = session("internal");
+        internal.proxy_jump = Some("ops@bastion.example.com".to_string());
+        let mut config = String::new();
+        push_ssh_config_host(&mut config, &internal);
+        assert!(config.contains("    ProxyJump ops@bastion.example.com\n"));
+    }
+
+    #[test]
+    fn minimal_ssh_config_leaves_out_what_ssh_would_assume() {
+        let web = session("web");
+        let mut alias = session("db.internal");
+        alias.host = "db.internal".to_string();
+        alias.port = 2222;
+        let mut style = SshConfigStyle::from(SshConfigTemplate::Minimal);
+
+        let config = export_to_ssh_config_with(&[web.clone(), alias], None, None, &style);
+        assert!(config.starts_with("Host web\n"));
+        assert!(!config.contains("# Tags"));
+        assert!(!config.contains("Port 22\n"));
+        assert!(config.contains("Host db.internal\n    User"));
+        assert!(config.contains("    Port 2222\n"));
+
+        style.always_port = true;
+        style.header = Some("team hosts".to_string());
+        let config = export_to_ssh_config_with(&[web], None, None, &style);
+        assert!(config.starts_with("# team hosts\n\nHost web\n"));
+        assert!(config.contains("    Port 22\n"));
+    }
+
+    #[test]
+    fn ssh_config_writes_one_line_per_forward() {
+        let mut db = session("db");
+        db.local_forwards = vec![
+            "5432:localhost:5432".to_string(),
+            "6379:cache:6379".to_string(),
+        ];
+        db.remote_forwards = vec!["9000:localhost:3000".to_string()];
+        let mut config = String::new();
+        push_ssh_config_host(&mut config, &db);
+        assert!(
+            config.contains(
+                "    LocalForward 5432 localhost:5432\n    LocalForward 6379 cache:6379\n"
+            )
+        );
+        assert!(config.contains("    RemoteForward 9000 localhost:3000\n"));
+    }
+
+    #[test]
+    fn ssh_config_writes_extra_args_as_options_where_it_can() {
+        let mut web = session("web");
+        web.extra_args = [
+            "-o",
+            "ServerAliveInterval=60",
+            "-oStrictHostKeyChecking=no",
+            "-A",
+        ]
+        .map(String::from)
+        .to_vec();
+        let mut config = String::new();
+        push_ssh_config_host(&mut config, &web);
+        assert!(config.contains("    ServerAliveInterval 60\n"));
+        assert!(config.contains("    StrictHostKeyChecking no\n"));
+        assert!(config.contains("    # ssh args: -A\n"));
+    }
+
+    #[test]
+    fn export_format_cycles_through_all_formats() {
+        let mut format = ExportFormat::Json;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            seen.push(format.label());
+            format = format.next();
+        }
+        assert_eq!(seen, ["json", "yaml", "csv", "ssh-config"]);
+        assert_eq!(format, ExportFormat::Json);
+        assert_eq!(format, ExportFormat::Json);
+    }
+
+    #[test]
+    fn ssh_config_hosts_sort_by_the_chosen_key() {
+        let mut alpha = session("alpha");
+        alpha.host = "zeta.example.com".to_string();
+        alpha.last_connected_at = Some(10);
+        let mut beta = session("beta");
+        beta.host = "eta.example.com".to_string();
+        let mut gamma = session("gamma");
+        gamma.host = "eta.example.com".to_string();
+        gamma.last_connected_at = Some(20);
+        let sessions = [gamma, alpha, beta];
+        let hosts = |sort: Option<ExportSort>| {
+            export_to_ssh_config(&sessions, None, sort)
+                .lines()
+                .filter_map(|line| line.strip_prefix("Host "))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(hosts(None), ["gamma", "alpha", "beta"]);
+        assert_eq!(hosts(Some(ExportSort::Name)), ["alpha", "beta", "gamma"]);
+        assert_eq!(hosts(Some(ExportSort::Host)), ["beta", "gamma", "alpha"]);
+        assert_eq!(hosts(Some(ExportSort::Recent)), ["gamma", "alpha", "beta"]);
+    }
+
+    #[test]
+    fn anonymize_keeps_structure_but_hides_targets() {
+        let mut web = session("web");
+        web.identity_file = Some("/home/alice/.ssh/corp_rsa".into());
+        web.default_local_dir = Some("/home/alice/work".to_string());
+        web.proxy_jump = Some("alice@bastion.corp".to_string());
+        web.local_forwards = vec!["5432:db.internal.corp:5432".to_string()];
+        web.remote_forwards = vec!["10.0.0.5:9000:localhost:9000".to_string()];
+        web.options = BTreeMap::from([(
+            "ProxyCommand".to_string(),
+            "ssh -W %h:%p gw.corp".to_string(),
+        )]);
+        web.extra_args = vec![
+            "-A".to_string(),
+            "-oHostName=10.1.2.3".to_string(),
+            "-o".to_string(),
+            "HostKeyAlias=web.corp".to_string(),
+            "gw.corp".to_string(),
+        ];
+        let mut web_alt = session("web-alt");
+        web_alt.port = 2222;
+        let mut db = session("db");
+        db.host = "db.internal.corp".to_string();
+        db.user = "postgres".to_string();
+
+        let anonymized = anonymize_export_sessions(&[web, web_alt, db]);
+        let targets: Vec<String> = anonymized
+            .iter()
+            .map(|session| format!("{} {}:{}", session.name, session.target(), session.port))
+            .collect();
+        assert_eq!(
+            targets,
+            [
+                "web user1@host1.example.com:22",
+                "web-alt user1@host1.example.com:2222",
+                "db user2@host2.example.com:22"
+            ]
+        );
+        assert_eq!(
+            anonymized[0].identity_file.as_deref(),
+            Some(Path::new("~/.ssh/id_key1"))
+        );
+        assert_eq!(anonymized[0].default_local_dir.as_deref(), Some("~/dir1"));
+        assert_eq!(
+            anonymized[0].proxy_jump.as_deref(),
+            Some("jump1.example.com")
+        );
+        assert_eq!(anonymized[0].tags, ["prod"]);
+        assert!(anonymized[1].identity_file.is_none());
+        assert_eq!(
+            anonymized[0].local_forwards,
+            ["5432:host2.example.com:5432"]
+        );
+        assert_eq!(
+            anonymized[0].remote_forwards,
+            ["host3.example.com:9000:host4.example.com:9000"]
+        );
+        assert_eq!(
+            anonymized[0].options,
+            BTreeMap::from([("ProxyCommand".to_string(), "value1".to_string())])
+        );
+        assert_eq!(
+            anonymized[0].extra_args,
+            [
+                "-A",
+                "-oHostName=value2",
+                "-o",
+                "HostKeyAlias=value3",
+                "value4"
+            ]
+        );
+        let json = export_to_json(&anonymized).expect("json");
+        for secret in ["corp", "10.", "localhost"] {
+            assert!(!json.contains(secret), "{secret} leaked: {json}");
+        }
+    }
+}
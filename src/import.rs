@@ -0,0 +1,463 @@
+//! Session import parsers shared by `se import` and library users.
+
+use crate::model::Session;
+use crate::openssh;
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Parse a JSON array in the store's own session format.
+pub fn import_from_json(content: &str) -> Result<Vec<Session>> {
+    serde_json::from_str(content).context("failed to parse JSON")
+}
+
+/// Parse a YAML sequence of sessions, using the same field names as the JSON store.
+pub fn import_from_yaml(content: &str) -> Result<Vec<Session>> {
+    serde_yaml::from_str(content).context("failed to parse YAML")
+}
+
+/// Session object as found in hand-written or shared JSON, where `user`
+/// and `port` may be left out.
+#[derive(Deserialize)]
+struct PartialSession {
+    #[serde(alias = "alias")]
+    name: String,
+    #[serde(alias = "hostname", alias = "host_name", alias = "address")]
+    host: String,
+    #[serde(alias = "username", alias = "user_name", alias = "login")]
+    user: Option<String>,
+    #[serde(alias = "port_number")]
+    port: Option<u16>,
+    #[serde(
+        default,
+        alias = "identityfile",
+        alias = "identity",
+        alias = "key_file",
+        alias = "private_key"
+    )]
+    identity_file: Option<PathBuf>,
+    #[serde(default, alias = "labels")]
+    tags: Vec<String>,
+    #[serde(default)]
+    last_connected_at: Option<i64>,
+}
+
+/// Like [`import_from_json`], but objects may leave out `user` (filled from `$USER`)
+/// and `port` (22); a warning names each defaulted field.
+pub fn import_from_json_lenient(content: &str) -> Result<Vec<Session>> {
+    let partials: Vec<PartialSession> =
+        serde_json::from_str(content).context("failed to parse JSON")?;
+    let default_user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok();
+
+    partials
+        .into_iter()
+        .map(|partial| {
+            let mut defaulted = Vec::new();
+            let user = match (partial.user, &default_user) {
+                (Some(user), _) => user,
+                (None, Some(user)) => {
+                    defaulted.push(format!("user={}", user));
+                    user.clone()
+                }
+                (None, None) => {
+                    return Err(anyhow!(
+                        "session '{}' has no user and $USER is not set",
+                        partial.name
+                    ));
+                }
+            };
+            let port = partial.port.unwrap_or_else(|| {
+                defaulted.push("port=22".to_string());
+                22
+            });
+            if !defaulted.is_empty() {
+                eprintln!(
+                    "Warning: session '{}' is missing fields, using {}",
+                    partial.name,
+                    defaulted.join(", ")
+                );
+            }
+            Ok(Session {
+                name: partial.name,
+                host: partial.host,
+                user,
+                port,
+                identity_file: partial.identity_file,
+                tags: partial.tags,
+                last_connected_at: partial.last_connected_at,
+                has_stored_password: false,
+                passwd_unsafe_mode: None,
+                stored_password: None,
+                local_forwards: Vec::new(),
+                remote_forwards: Vec::new(),
+                extra_args: Vec::new(),
+                options: BTreeMap::new(),
+                connect_count: 0,
+                proxy_jump: None,
+                scp_compress: false,
+                last_duration_secs: None,
+                avg_duration_secs: None,
+                default_local_dir: None,
+                control_master: false,
+                control_path: None,
+            })
+        })
+        .collect()
+}
+
+/// Read `Host` blocks from an OpenSSH client config. A block without a `HostName`
+/// connects to its alias, as `ssh` does. Wildcard patterns and `Match` blocks are
+/// skipped, and their options never leak into the `Host` above.
+pub fn import_from_ssh_config(content: &str) -> Result<Vec<Session>> {
+    let mut sessions = Vec::new();
+    let mut current_host: Option<String> = None;
+    let mut current_user = "root".to_string();
+    let mut current_hostname: Option<String> = None;
+    let mut current_port = 22u16;
+    let mut current_identity: Option<PathBuf> = None;
+    let mut current_control_master = false;
+    let mut current_control_path: Option<String> = None;
+    let mut current_proxy_jump: Option<String> = None;
+    let mut current_options = BTreeMap::new();
+    let mut current_local_forwards = Vec::new();
+    let mut current_remote_forwards = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+
+        // Skip comments and empty lines
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.splitn(2, ' ').collect();
+        if parts.len() < 2 {
+            continue;
+        }
+
+        let keyword = parts[0].to_lowercase();
+        let value = parts[1].trim();
+
+        match keyword.as_str() {
+            "host" | "match" => {
+                // Save previous host if exists
+                if let Some(name) = current_host.take() {
+                    let hostname = current_hostname.take().unwrap_or_else(|| name.clone());
+                    sessions.push(Session {
+                        name,
+                        host: hostname,
+                        user: current_user.clone(),
+                        port: current_port,
+                        identity_file: current_identity.take(),
+                        tags: vec![],
+                        last_connected_at: None,
+                        has_stored_password: false,
+                        passwd_unsafe_mode: None,
+                        stored_password: None,
+                        local_forwards: std::mem::take(&mut current_local_forwards),
+                        remote_forwards: std::mem::take(&mut current_remote_forwards),
+                        extra_args: Vec::new(),
+                        options: std::mem::take(&mut current_options),
+                        connect_count: 0,
+                        proxy_jump: current_proxy_jump.take(),
+                        scp_compress: false,
+                        last_duration_secs: None,
+                        avg_duration_secs: None,
+                        default_local_dir: None,
+                        control_master: current_control_master,
+                        control_path: current_control_path.take(),
+                    });
+                }
+                // Match blocks end the previous Host but are not sessions themselves,
+                // so their options are read and dropped.
+                current_host = if keyword == "match" {
+                    eprintln!("Warning: skipping ssh_config block 'Match {}'", value);
+                    None
+                } else {
+                    session_name(value)
+                };
+                current_hostname = None;
+                current_user = "root".to_string();
+                current_port = 22;
+                current_identity = None;
+                current_control_master = false;
+                current_control_path = None;
+                current_proxy_jump = None;
+                current_options.clear();
+                current_local_forwards.clear();
+                current_remote_forwards.clear();
+            }
+            "user" => {
+                current_user = value.to_string();
+            }
+            "hostname" => {
+                current_hostname = Some(value.to_string());
+            }
+            "port" => {
+                current_port = value.parse().unwrap_or(22);
+            }
+            // ssh tries every IdentityFile in order; a session holds one, the first.
+            "identityfile" => match &current_identity {
+                None => current_identity = Some(PathBuf::from(value)),
+                Some(first) => eprintln!(
+                    "Warning: ignoring 'IdentityFile {}' (keeping {})",
+                    value,
+                    first.display()
+                ),
+            },
+            "controlmaster" => {
+                current_control_master = matches!(
+                    value.to_lowercase().as_str(),
+                    "auto" | "yes" | "autoask" | "ask"
+                );
+            }
+            "controlpath" => {
+                current_control_path = Some(value.to_string()).filter(|path| path != "none");
+            }
+            "proxyjump" => {
+                current_proxy_jump = Some(value.to_string()).filter(|jump| jump != "none");
+            }
+            "localforward" if current_host.is_some() => match openssh::forward_from_config(value) {
+                Some(spec) => current_local_forwards.push(spec),
+                None => add_option(&mut current_options, &keyword, value),
+            },
+            "remoteforward" if current_host.is_some() => {
+                match openssh::forward_from_config(value) {
+                    Some(spec) => current_remote_forwards.push(spec),
+                    None => add_option(&mut current_options, &keyword, value),
+                }
+            }
+            // Anything else is passed to ssh as `-o keyword=value`.
+            _ if current_host.is_some() => add_option(&mut current_options, &keyword, value),
+            _ => {}
+        }
+    }
+
+    // Save last host
+    if let Some(name) = current_host.take() {
+        let hostname = current_hostname.take().unwrap_or_else(|| name.clone());
+        sessions.push(Session {
+            name,
+            host: hostname,
+            user: current_user.clone(),
+            port: current_port,
+            identity_file: current_identity.take(),
+            tags: vec![],
+            last_connected_at: None,
+            has_stored_password: false,
+            passwd_unsafe_mode: None,
+            stored_password: None,
+            local_forwards: std::mem::take(&mut current_local_forwards),
+            remote_forwards: std::mem::take(&mut current_remote_forwards),
+            extra_args: Vec::new(),
+            options: std::mem::take(&mut current_options),
+            connect_count: 0,
+            proxy_jump: current_proxy_jump.take(),
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: current_control_master,
+            control_path: current_control_path.take(),
+        });
+    }
+
+    Ok(sessions)
+}
+
+/// Keywords whose values ssh adds up across lines instead of keeping the first.
+const ACCUMULATING_OPTIONS: [&str; 2] = ["sendenv", "setenv"];
+
+/// Record a `keyword value` line (keyword lowercased) the way ssh reads it: the first
+/// value wins, except that `SendEnv` and `SetEnv` lines add up. A conflicting later
+/// value is reported rather than dropped silently.
+fn add_option(options: &mut BTreeMap<String, String>, keyword: &str, value: &str) {
+    match options.get_mut(keyword) {
+        None => {
+            options.insert(keyword.to_string(), value.to_string());
+        }
+        Some(existing) if ACCUMULATING_OPTIONS.contains(&keyword) => {
+            existing.push(' ');
+            existing.push_str(value);
+        }
+        Some(existing) if existing != value => eprintln!(
+            "Warning: ignoring '{} {}' (ssh uses the first value, '{}')",
+            keyword, value, existing
+        ),
+        Some(_) => {}
+    }
+}
+
+/// The session name for a `Host` line: its first alias, or `None` when the line
+/// holds patterns (`*`, `?`, `!`) that match many hosts rather than naming one.
+fn session_name(patterns: &str) -> Option<String> {
+    if patterns.contains(['*', '?', '!']) {
+        return None;
+    }
+    patterns.split_whitespace().next().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssh_config_reads_host_blocks() {
+        let config = "\
+# bastion-only hosts
+Host web
+    HostName web.example.com
+    User deploy
+    Port 2222
+    IdentityFile ~/.ssh/web
+    ProxyJump ops@bastion
+
+Host wildcard-*
+    User nobody
+
+Host db
+    HostName db.example.com
+";
+        let sessions = import_from_ssh_config(config).expect("parse");
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].target(), "deploy@web.example.com");
+        assert_eq!(sessions[0].port, 2222);
+        assert_eq!(sessions[0].proxy_jump.as_deref(), Some("ops@bastion"));
+        assert_eq!(sessions[1].name, "db");
+        assert_eq!(sessions[1].user, "root");
+        assert_eq!(sessions[1].port, 22);
+        assert!(sessions[1].proxy_jump.is_none());
+    }
+
+    #[test]
+    fn ssh_config_keeps_unrecognized_keywords_as_options() {
+        let config = "\
+ServerAliveCountMax 3
+
+Host web
+    HostName web.example.com
+    ServerAliveInterval 30
+    LocalForward 8080 localhost:80
+
+Host db
+    HostName db.example.com
+";
+        let sessions = import_from_ssh_config(config).expect("parse");
+        assert_eq!(
+            sessions[0].options,
+            BTreeMap::from([("serveraliveinterval".to_string(), "30".to_string())])
+        );
+        assert_eq!(sessions[0].local_forwards, ["8080:localhost:80"]);
+        assert!(sessions[1].local_forwards.is_empty());
+        assert!(sessions[1].options.is_empty());
+    }
+
+    #[test]
+    fn ssh_config_repeated_keywords_keep_the_first_value() {
+        let config = "\
+Host web
+    IdentityFile ~/.ssh/first
+    IdentityFile ~/.ssh/second
+    ServerAliveInterval 30
+    serveraliveinterval 60
+    SendEnv LANG
+    SendEnv LC_*
+";
+        let sessions = import_from_ssh_config(config).expect("parse");
+        assert_eq!(
+            sessions[0].identity_file.as_deref(),
+            Some(std::path::Path::new("~/.ssh/first"))
+        );
+        assert_eq!(
+            sessions[0].options,
+            BTreeMap::from([
+                ("sendenv".to_string(), "LANG LC_*".to_string()),
+                ("serveraliveinterval".to_string(), "30".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn ssh_config_match_block_ends_the_previous_host() {
+        let config = "\
+Host web
+    HostName web.example.com
+    User deploy
+
+Match host *.internal exec \"test -f ~/.vpn\"
+    User vpnuser
+    Port 2200
+    ProxyJump gw.internal
+
+Host db
+    HostName db.example.com
+";
+        let sessions = import_from_ssh_config(config).expect("parse");
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].target(), "deploy@web.example.com");
+        assert_eq!(sessions[0].port, 22);
+        assert!(sessions[0].proxy_jump.is_none());
+        assert_eq!(sessions[1].target(), "root@db.example.com");
+        assert_eq!(sessions[1].port, 22);
+    }
+
+    #[test]
+    fn ssh_config_host_without_hostname_uses_the_alias() {
+        let config = "\
+Host *
+    User everyone
+
+Host jumpbox.example.com jumpbox
+    User ops
+
+Host lab
+    Port 2222
+";
+        let sessions = import_from_ssh_config(config).expect("parse");
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].name, "jumpbox.example.com");
+        assert_eq!(sessions[0].target(), "ops@jumpbox.example.com");
+        assert_eq!(sessions[1].target(), "root@lab");
+        assert_eq!(sessions[1].port, 2222);
+    }
+
+    #[test]
+    fn yaml_reads_hand_written_sessions() {
+        let content = "\
+- name: web
+  host: web.example.com
+  user: deploy
+  port: 2222
+  identity_file: ~/.ssh/web
+  tags: [prod, frontend]
+- name: db
+  host: db.example.com
+  user: postgres
+  port: 22
+";
+        let sessions = import_from_yaml(content).expect("parse");
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].target(), "deploy@web.example.com");
+        assert_eq!(sessions[0].port, 2222);
+        assert_eq!(sessions[0].tags, ["prod", "frontend"]);
+        assert_eq!(
+            sessions[0].identity_file.as_deref(),
+            Some(std::path::Path::new("~/.ssh/web"))
+        );
+        assert!(import_from_yaml("- name: broken\n").is_err());
+    }
+
+    #[test]
+    fn strict_json_requires_user_and_port() {
+        let content = r#"[{"name": "web", "host": "web.example.com"}]"#;
+        assert!(import_from_json(content).is_err());
+        temp_env::with_var("USER", Some("alice"), || {
+            let sessions = import_from_json_lenient(content).expect("lenient");
+            assert_eq!(sessions[0].target(), "alice@web.example.com");
+            assert_eq!(sessions[0].port, 22);
+        });
+    }
+}
@@ -1,7 +1,50 @@
+//! Session management behind the `se` command, usable as a library.
+//!
+//! The common entry points are re-exported here: [`Session`], the [`SessionStore`]
+//! trait with its JSON-file implementation [`JsonFileStore`], and the import/export
+//! functions. The `cli` and `ui` modules are the binary's front ends.
+//!
+//! ```no_run
+//! use ssher::{JsonFileStore, SessionStore, export_to_ssh_config, import_from_ssh_config};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let store = JsonFileStore::new("sessions.json".into());
+//! for session in import_from_ssh_config(&std::fs::read_to_string("ssh_config")?)? {
+//!     store.add(session)?;
+//! }
+//! print!("{}", export_to_ssh_config(&store.list()?, None, None));
+//! # Ok(())
+//! # }
+//! ```
+
 pub mod auth;
 pub mod cli;
+pub mod clock;
+pub mod config_check;
+pub mod dirs;
+pub mod export;
+pub mod import;
+pub mod logging;
 pub mod model;
+pub mod openssh;
+pub mod parallel;
 pub mod password;
+pub mod runner;
 pub mod ssh;
 pub mod store;
 pub mod ui;
+
+pub use export::{
+    CsvColumn, ExportFormat, ExportGroupBy, ExportSort, SshConfigStyle, SshConfigTemplate,
+    anonymize_export_sessions, export_to_csv, export_to_csv_columns, export_to_json,
+    export_to_ssh_config, export_to_ssh_config_with, export_to_yaml, render_export,
+    sanitize_export_sessions,
+};
+pub use import::{
+    import_from_json, import_from_json_lenient, import_from_ssh_config, import_from_yaml,
+};
+pub use model::{PasswdUnsafeMode, Session, SessionBuilder};
+pub use store::{
+    EncryptedFileStore, EnvSessionStore, JsonFileStore, SessionStore, SqliteStore, StoreOpener,
+    is_encrypted, resolve_store_path,
+};
@@ -0,0 +1,58 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Environment variable consulted when `--log-level` is not given.
+pub const LOG_ENV: &str = "RUST_LOG";
+
+/// Minimal logger that writes `[LEVEL target] message` lines to stderr.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Parse a level name such as `debug`; `RUST_LOG`-style `target=level`
+/// directives are reduced to their last plain level.
+pub fn parse_level(value: &str) -> Option<LevelFilter> {
+    value
+        .split(',')
+        .filter_map(|directive| {
+            let level = directive.rsplit('=').next()?.trim();
+            level.parse::<LevelFilter>().ok()
+        })
+        .next_back()
+}
+
+/// Install the stderr logger. Logging stays off unless a level is requested.
+pub fn init(level: Option<LevelFilter>) {
+    let level = level
+        .or_else(|| std::env::var(LOG_ENV).ok().as_deref().and_then(parse_level))
+        .unwrap_or(LevelFilter::Off);
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_level_accepts_names_and_directives() {
+        assert_eq!(parse_level("debug"), Some(LevelFilter::Debug));
+        assert_eq!(parse_level("ssher=trace"), Some(LevelFilter::Trace));
+        assert_eq!(parse_level("warn,ssher=info"), Some(LevelFilter::Info));
+        assert_eq!(parse_level("nonsense"), None);
+    }
+}
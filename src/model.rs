@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 
 /// Password storage mode for unsafe environments where keyring is unavailable
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
@@ -24,13 +26,26 @@ pub struct AuthStatus {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Session {
+    // Aliases accept field names used by other tools' JSON; serialization
+    // always uses the canonical names.
+    #[serde(alias = "alias")]
     pub name: String,
+    #[serde(alias = "hostname", alias = "host_name", alias = "address")]
     pub host: String,
+    #[serde(alias = "username", alias = "user_name", alias = "login")]
     pub user: String,
+    #[serde(alias = "port_number")]
     pub port: u16,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        alias = "identityfile",
+        alias = "identity",
+        alias = "key_file",
+        alias = "private_key"
+    )]
     pub identity_file: Option<PathBuf>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "labels")]
     pub tags: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_connected_at: Option<i64>,
@@ -42,17 +57,113 @@ pub struct Session {
     /// Password stored in unsafe format (plaintext for bare, base64-encoded XOR for simple)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub stored_password: Option<String>,
+    /// Share one connection per host via OpenSSH `ControlMaster=auto`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub control_master: bool,
+    /// ControlPath template; `None` uses the default `~/.ssh/ssher-%r@%h:%p`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control_path: Option<String>,
+    /// Bastion to hop through (`ssh -J`), e.g. `me@bastion.example.com:2222`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_jump: Option<String>,
+    /// Local folder that pre-fills scp's local path (`~` is expanded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_local_dir: Option<String>,
+    /// Compress scp transfers (`scp -C`) unless asked otherwise
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub scp_compress: bool,
+    /// Length of the most recent connection, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_duration_secs: Option<u64>,
+    /// Running average connection length in seconds, weighted toward recent connections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_duration_secs: Option<u64>,
+    /// How many ssh connections and scp transfers have succeeded
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub connect_count: u64,
+    /// Extra `-o Key=Value` options for ssh and scp (uses the OpenSSH client)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, String>,
+    /// Raw arguments passed to ssh before the destination (uses the OpenSSH client)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_args: Vec<String>,
+    /// `ssh -L` forwards, each `[bind_address:]port:host:hostport`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub local_forwards: Vec<String>,
+    /// `ssh -R` forwards, each `[bind_address:]port:host:hostport`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remote_forwards: Vec<String>,
 }
 
 fn should_skip_auth_indicator(b: &bool) -> bool {
     !b
 }
 
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
 impl Session {
+    /// Start a session on port 22 with every optional field empty.
+    pub fn builder(
+        name: impl Into<String>,
+        host: impl Into<String>,
+        user: impl Into<String>,
+    ) -> SessionBuilder {
+        SessionBuilder {
+            session: Session {
+                name: name.into(),
+                host: host.into(),
+                user: user.into(),
+                port: 22,
+                identity_file: None,
+                tags: Vec::new(),
+                last_connected_at: None,
+                has_stored_password: false,
+                passwd_unsafe_mode: None,
+                stored_password: None,
+                local_forwards: Vec::new(),
+                remote_forwards: Vec::new(),
+                extra_args: Vec::new(),
+                options: BTreeMap::new(),
+                connect_count: 0,
+                proxy_jump: None,
+                scp_compress: false,
+                last_duration_secs: None,
+                avg_duration_secs: None,
+                default_local_dir: None,
+                control_master: false,
+                control_path: None,
+            },
+        }
+    }
+
     pub fn target(&self) -> String {
         format!("{}@{}", self.user, self.host)
     }
 
+    /// `default_local_dir` with a leading `~` replaced by `$HOME`.
+    pub fn local_dir(&self) -> Option<PathBuf> {
+        self.default_local_dir.as_deref().map(expand_home)
+    }
+
+    /// Fold a finished connection into `last_duration_secs` and `avg_duration_secs`.
+    /// The average moves a quarter of the way toward each new duration, so it follows
+    /// changing habits without keeping a log.
+    pub fn record_duration(&mut self, secs: u64) {
+        self.last_duration_secs = Some(secs);
+        self.avg_duration_secs = Some(match self.avg_duration_secs {
+            Some(avg) => avg.saturating_mul(3).saturating_add(secs) / 4,
+            None => secs,
+        });
+    }
+
+    /// Directory holding the identity file (`~` expanded), if one is set.
+    pub fn identity_dir(&self) -> Option<PathBuf> {
+        let identity = expand_home(&self.identity_file.as_ref()?.to_string_lossy());
+        identity.parent().map(Path::to_path_buf)
+    }
+
     #[allow(dead_code)]
     pub fn auth_status(&self) -> AuthStatus {
         let has_key = self
@@ -68,6 +179,92 @@ impl Session {
     }
 }
 
+/// Builds a [`Session`]; see [`Session::builder`].
+#[derive(Debug, Clone)]
+pub struct SessionBuilder {
+    session: Session,
+}
+
+impl SessionBuilder {
+    pub fn port(mut self, port: u16) -> Self {
+        self.session.port = port;
+        self
+    }
+
+    pub fn identity_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.session.identity_file = Some(path.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.session.tags.push(tag.into());
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.session.tags.extend(tags);
+        self
+    }
+
+    pub fn last_connected_at(mut self, timestamp: i64) -> Self {
+        self.session.last_connected_at = Some(timestamp);
+        self
+    }
+
+    pub fn proxy_jump(mut self, proxy_jump: impl Into<String>) -> Self {
+        self.session.proxy_jump = Some(proxy_jump.into());
+        self
+    }
+
+    pub fn build(self) -> Session {
+        self.session
+    }
+}
+
+/// Compact elapsed time: `45s`, `3m12s`, `2h05m`.
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Replace a leading `~` in `path` with `$HOME` (left alone when `HOME` is unset).
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match (path, home) {
+        ("~", Some(home)) => home,
+        (path, Some(home)) if path.starts_with("~/") => home.join(&path[2..]),
+        (path, _) => PathBuf::from(path),
+    }
+}
+
+/// Derive a session name from a host: its first label (`web1` from `web1.example.com`),
+/// or the whole host for IP addresses.
+pub fn session_name_from_host(host: &str) -> String {
+    let host = host.trim();
+    if host.trim_matches(['[', ']']).parse::<IpAddr>().is_ok() {
+        return host.trim_matches(['[', ']']).to_string();
+    }
+    host.split('.')
+        .find(|label| !label.is_empty())
+        .unwrap_or(host)
+        .to_string()
+}
+
+/// Return `base`, or `base-2`, `base-3`, ... if the name is already taken.
+pub fn unique_session_name(base: &str, existing: &[Session]) -> String {
+    let taken = |name: &str| existing.iter().any(|session| session.name == name);
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|index| format!("{base}-{index}"))
+        .find(|candidate| !taken(candidate))
+        .expect("unbounded suffix search always finds a free name")
+}
+
 /// Root-level wrapper for the sessions.json file format
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionStoreData {
@@ -77,6 +274,15 @@ pub struct SessionStoreData {
     /// Fallback XOR key if SSHER_UNSAFE_KEY env var not set
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub passwd_unsafe_key: Option<String>,
+    /// Append every connect/scp to the connection log next to the store
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub log_connections: bool,
+    /// Rotate the connection log once it holds this many entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_max_entries: Option<u64>,
+    /// Rotate the connection log before it grows past this many bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_max_bytes: Option<u64>,
     /// List of sessions
     pub sessions: Vec<Session>,
 }
@@ -86,6 +292,9 @@ impl Default for SessionStoreData {
         Self {
             passwd_unsafe_mode: PasswdUnsafeMode::Normal,
             passwd_unsafe_key: None,
+            log_connections: false,
+            log_max_entries: None,
+            log_max_bytes: None,
             sessions: Vec::new(),
         }
     }
@@ -97,6 +306,9 @@ impl SessionStoreData {
         Self {
             passwd_unsafe_mode: PasswdUnsafeMode::Normal,
             passwd_unsafe_key: None,
+            log_connections: false,
+            log_max_entries: None,
+            log_max_bytes: None,
             sessions,
         }
     }
@@ -128,10 +340,47 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
         assert_eq!(session.target(), "alice@example.com");
     }
 
+    #[test]
+    fn builder_fills_defaults() {
+        let session = Session::builder("web", "web.example.com", "deploy").build();
+        assert_eq!(session.port, 22);
+        assert!(session.identity_file.is_none());
+        assert!(session.tags.is_empty());
+        assert!(session.last_connected_at.is_none());
+        assert!(!session.has_stored_password);
+
+        let session = Session::builder("web", "web.example.com", "deploy")
+            .port(2222)
+            .identity_file("~/.ssh/id_ed25519")
+            .tag("prod")
+            .tag("web")
+            .proxy_jump("bastion")
+            .build();
+        assert_eq!(session.port, 2222);
+        assert_eq!(
+            session.identity_file,
+            Some(PathBuf::from("~/.ssh/id_ed25519"))
+        );
+        assert_eq!(session.tags, ["prod", "web"]);
+        assert_eq!(session.proxy_jump.as_deref(), Some("bastion"));
+    }
+
     #[test]
     fn serialize_full_session() {
         let session = Session {
@@ -145,6 +394,18 @@ mod tests {
             has_stored_password: true,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
         let json = serde_json::to_string(&session).unwrap();
         assert!(json.contains(r#""name":"office""#));
@@ -209,6 +470,18 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
         let json = serde_json::to_string(&original).unwrap();
         let restored: Session = serde_json::from_str(&json).unwrap();
@@ -228,6 +501,18 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         let status = session.auth_status();
@@ -249,6 +534,18 @@ mod tests {
             has_stored_password: true,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         let status = session.auth_status();
@@ -270,6 +567,18 @@ mod tests {
             has_stored_password: true,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         let status = session.auth_status();
@@ -291,6 +600,18 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         let json = serde_json::to_string(&session).unwrap();
@@ -310,6 +631,18 @@ mod tests {
             has_stored_password: true,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         let json = serde_json::to_string(&session).unwrap();
@@ -367,6 +700,18 @@ mod tests {
             has_stored_password: true,
             passwd_unsafe_mode: Some(PasswdUnsafeMode::Bare),
             stored_password: Some("secret".to_string()),
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         let json = serde_json::to_string(&session).unwrap();
@@ -387,6 +732,18 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         let json = serde_json::to_string(&session).unwrap();
@@ -415,6 +772,18 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         }];
 
         let data = SessionStoreData::from_sessions(sessions);
@@ -441,6 +810,18 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: Some(PasswdUnsafeMode::Simple),
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         // Session override takes precedence
@@ -460,6 +841,18 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
         };
 
         // Falls back to global
@@ -474,6 +867,9 @@ mod tests {
         let data = SessionStoreData {
             passwd_unsafe_mode: PasswdUnsafeMode::Simple,
             passwd_unsafe_key: Some("my-key".to_string()),
+            log_connections: false,
+            log_max_entries: None,
+            log_max_bytes: None,
             sessions: vec![],
         };
 
@@ -481,4 +877,180 @@ mod tests {
         assert!(json.contains(r#""passwd_unsafe_mode":"simple""#));
         assert!(json.contains(r#""passwd_unsafe_key":"my-key""#));
     }
+
+    #[test]
+    fn session_deserializes_common_field_aliases() {
+        let cases = [
+            r#"{"alias":"a","host":"h","user":"u","port":22}"#,
+            r#"{"name":"a","hostname":"h","user":"u","port":22}"#,
+            r#"{"name":"a","host_name":"h","user":"u","port":22}"#,
+            r#"{"name":"a","address":"h","user":"u","port":22}"#,
+            r#"{"name":"a","host":"h","username":"u","port":22}"#,
+            r#"{"name":"a","host":"h","user_name":"u","port":22}"#,
+            r#"{"name":"a","host":"h","login":"u","port":22}"#,
+            r#"{"name":"a","host":"h","user":"u","port_number":22}"#,
+        ];
+        for json in cases {
+            let session: Session = serde_json::from_str(json).expect(json);
+            assert_eq!(
+                (
+                    session.name.as_str(),
+                    session.host.as_str(),
+                    session.user.as_str()
+                ),
+                ("a", "h", "u"),
+                "{json}"
+            );
+            assert_eq!(session.port, 22, "{json}");
+        }
+
+        for key in ["identityfile", "identity", "key_file", "private_key"] {
+            let json =
+                format!(r#"{{"name":"a","host":"h","user":"u","port":22,"{key}":"~/.ssh/id"}}"#);
+            let session: Session = serde_json::from_str(&json).expect(&json);
+            assert_eq!(session.identity_file, Some(PathBuf::from("~/.ssh/id")));
+        }
+
+        let session: Session =
+            serde_json::from_str(r#"{"name":"a","host":"h","user":"u","port":22,"labels":["x"]}"#)
+                .unwrap();
+        assert_eq!(session.tags, vec!["x".to_string()]);
+    }
+
+    #[test]
+    fn session_serializes_canonical_names() {
+        let session: Session =
+            serde_json::from_str(r#"{"name":"a","hostname":"h","username":"u","port_number":22}"#)
+                .unwrap();
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(json.contains(r#""host":"h""#));
+        assert!(json.contains(r#""user":"u""#));
+        assert!(json.contains(r#""port":22"#));
+        assert!(!json.contains("hostname"));
+    }
+
+    #[test]
+    fn local_and_identity_dirs_expand_home() {
+        let mut session = Session {
+            name: "web".to_string(),
+            host: "web.example.com".to_string(),
+            user: "me".to_string(),
+            port: 22,
+            identity_file: None,
+            tags: vec![],
+            last_connected_at: None,
+            has_stored_password: false,
+            passwd_unsafe_mode: None,
+            stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            control_master: false,
+            control_path: None,
+            default_local_dir: None,
+        };
+        assert_eq!(session.local_dir(), None);
+
+        temp_env::with_var("HOME", Some("/home/me"), || {
+            session.default_local_dir = Some("~/transfers".to_string());
+            assert_eq!(
+                session.local_dir(),
+                Some(PathBuf::from("/home/me/transfers"))
+            );
+            session.default_local_dir = Some("/srv/data".to_string());
+            assert_eq!(session.local_dir(), Some(PathBuf::from("/srv/data")));
+
+            assert_eq!(session.identity_dir(), None);
+            session.identity_file = Some(PathBuf::from("~/.ssh/prod/id_ed25519"));
+            assert_eq!(
+                session.identity_dir(),
+                Some(PathBuf::from("/home/me/.ssh/prod"))
+            );
+        });
+    }
+
+    #[test]
+    fn record_duration_keeps_last_and_weighted_average() {
+        let mut session: Session =
+            serde_json::from_str(r#"{"name":"web","host":"web","user":"me","port":22}"#).unwrap();
+        session.record_duration(100);
+        assert_eq!(session.last_duration_secs, Some(100));
+        assert_eq!(session.avg_duration_secs, Some(100));
+        session.record_duration(20);
+        assert_eq!(session.last_duration_secs, Some(20));
+        assert_eq!(session.avg_duration_secs, Some(80));
+    }
+
+    #[test]
+    fn connect_count_defaults_to_zero_and_is_omitted_when_zero() {
+        let mut session: Session =
+            serde_json::from_str(r#"{"name":"web","host":"web","user":"me","port":22}"#).unwrap();
+        assert_eq!(session.connect_count, 0);
+        assert!(
+            !serde_json::to_string(&session)
+                .unwrap()
+                .contains("connect_count")
+        );
+
+        session.connect_count = 3;
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(json.contains(r#""connect_count":3"#));
+    }
+
+    #[test]
+    fn format_duration_picks_two_units() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(192), "3m12s");
+        assert_eq!(format_duration(3600), "1h00m");
+        assert_eq!(format_duration(7_505), "2h05m");
+    }
+
+    #[test]
+    fn session_name_from_host_uses_first_label() {
+        assert_eq!(session_name_from_host("web1.example.com"), "web1");
+        assert_eq!(session_name_from_host("bastion"), "bastion");
+        assert_eq!(session_name_from_host("10.0.0.5"), "10.0.0.5");
+        assert_eq!(session_name_from_host("[::1]"), "::1");
+    }
+
+    #[test]
+    fn unique_session_name_appends_numeric_suffix() {
+        let named = |name: &str| Session {
+            name: name.to_string(),
+            host: "h".to_string(),
+            user: "u".to_string(),
+            port: 22,
+            identity_file: None,
+            tags: vec![],
+            last_connected_at: None,
+            has_stored_password: false,
+            passwd_unsafe_mode: None,
+            stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
+        };
+        assert_eq!(unique_session_name("web1", &[]), "web1");
+        assert_eq!(unique_session_name("web1", &[named("web1")]), "web1-2");
+        assert_eq!(
+            unique_session_name("web1", &[named("web1"), named("web1-2")]),
+            "web1-3"
+        );
+    }
 }
@@ -0,0 +1,712 @@
+//! Command lines for the OpenSSH `ssh` and `scp` binaries.
+//!
+//! Sessions normally connect through the built-in libssh2 client. Features that only
+//! the OpenSSH client implements, such as connection multiplexing or jump hosts, switch
+//! a session over to these commands instead.
+
+use crate::model::Session;
+use std::process::Command;
+
+/// ControlPath used when a session enables multiplexing without its own template.
+/// `%r`, `%h`, and `%p` are expanded by ssh to the user, host, and port.
+pub const DEFAULT_CONTROL_PATH: &str = "~/.ssh/ssher-%r@%h:%p";
+
+/// Whether this session must be run through the OpenSSH client.
+pub fn needs_openssh(session: &Session) -> bool {
+    session.control_master
+        || session.proxy_jump.is_some()
+        || !session.options.is_empty()
+        || !session.extra_args.is_empty()
+        || !session.local_forwards.is_empty()
+        || !session.remote_forwards.is_empty()
+}
+
+/// Split a `Key=Value` ssh option. The key must be a bare ssh_config keyword
+/// (letters and digits) and the value must not be empty.
+pub fn parse_option(raw: &str) -> Option<(String, String)> {
+    let (key, value) = raw.split_once('=')?;
+    let (key, value) = (key.trim(), value.trim());
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()) || value.is_empty() {
+        return None;
+    }
+    Some((key.to_string(), value.to_string()))
+}
+
+/// Check a `-L`/`-R` forward spec, `[bind_address:]port:host:hostport`. IPv6
+/// addresses go in brackets, e.g. `8080:[::1]:80`.
+pub fn validate_forward(spec: &str) -> Result<(), String> {
+    let parts = split_forward(spec);
+    let (host, ports) = match parts.as_slice() {
+        [port, host, host_port] | [_, port, host, host_port] => (*host, [*port, *host_port]),
+        _ => return Err("expected [bind_address:]port:host:hostport".to_string()),
+    };
+    if host.is_empty() {
+        return Err("missing host".to_string());
+    }
+    for port in ports {
+        if !matches!(port.parse::<u16>(), Ok(1..)) {
+            return Err(format!("'{}' is not a port number", port));
+        }
+    }
+    Ok(())
+}
+
+/// ssh_config value for a forward spec: `8080:localhost:80` becomes `8080 localhost:80`.
+pub fn forward_config_value(spec: &str) -> String {
+    let parts = split_forward(spec);
+    let split = parts.len().saturating_sub(2);
+    format!("{} {}", parts[..split].join(":"), parts[split..].join(":"))
+}
+
+/// Inverse of [`forward_config_value`]; `None` unless the result is a valid spec.
+pub fn forward_from_config(value: &str) -> Option<String> {
+    let (listen, destination) = value.trim().split_once(char::is_whitespace)?;
+    let spec = format!("{}:{}", listen, destination.trim());
+    validate_forward(&spec).ok().map(|()| spec)
+}
+
+/// Split on the colons outside `[...]`.
+pub(crate) fn split_forward(spec: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut bracketed = false;
+    let mut start = 0;
+    for (index, ch) in spec.char_indices() {
+        match ch {
+            '[' => bracketed = true,
+            ']' => bracketed = false,
+            ':' if !bracketed => {
+                parts.push(&spec[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&spec[start..]);
+    parts
+}
+
+pub fn control_path(session: &Session) -> &str {
+    session
+        .control_path
+        .as_deref()
+        .unwrap_or(DEFAULT_CONTROL_PATH)
+}
+
+/// Options shared by `ssh` and `scp` for this session.
+pub fn shared_options(session: &Session) -> Vec<String> {
+    let mut options = Vec::new();
+    if session.control_master {
+        options.push("-o".to_string());
+        options.push("ControlMaster=auto".to_string());
+        options.push("-o".to_string());
+        options.push(format!("ControlPath={}", control_path(session)));
+    }
+    if let Some(proxy_jump) = &session.proxy_jump {
+        options.push("-J".to_string());
+        options.push(proxy_jump.clone());
+    }
+    for (key, value) in &session.options {
+        options.push("-o".to_string());
+        options.push(format!("{}={}", key, value));
+    }
+    options
+}
+
+/// `ssh` with the session's port, identity, shared options, and extra arguments, but
+/// no destination. Each extra argument stays a single argv entry, spaces included.
+fn ssh_base(session: &Session) -> Command {
+    let mut command = Command::new("ssh");
+    command.arg("-p").arg(session.port.to_string());
+    if let Some(identity) = &session.identity_file {
+        command.arg("-i").arg(identity);
+    }
+    command.args(shared_options(session));
+    command.args(&session.extra_args);
+    command
+}
+
+/// `ssh` invocation for an interactive login to `session`, with its saved forwards.
+pub fn ssh_command(session: &Session) -> Command {
+    let mut command = ssh_base(session);
+    for spec in &session.local_forwards {
+        command.arg("-L").arg(spec);
+    }
+    for spec in &session.remote_forwards {
+        command.arg("-R").arg(spec);
+    }
+    command.arg(ssh_target(session));
+    command
+}
+
+/// `ssh` for a one-off `se tunnel`: the session's connection settings with only the
+/// given forwards (saved ones are left out), plus `-N` when no shell is wanted.
+pub fn tunnel_command(
+    session: &Session,
+    local_forwards: &[String],
+    remote_forwards: &[String],
+    no_shell: bool,
+) -> Command {
+    let mut command = ssh_base(session);
+    for spec in local_forwards {
+        command.arg("-L").arg(spec);
+    }
+    for spec in remote_forwards {
+        command.arg("-R").arg(spec);
+    }
+    if no_shell {
+        command.arg("-N");
+    }
+    command.arg(ssh_target(session));
+    command
+}
+
+/// `ssh` invocation that runs `remote_command` on `session` and exits. `BatchMode`
+/// makes it fail instead of prompting for a password, since several may run at once.
+pub fn exec_command(session: &Session, remote_command: &[String]) -> Command {
+    let mut command = ssh_base(session);
+    command.arg("-o").arg("BatchMode=yes");
+    command.arg(ssh_target(session)).args(remote_command);
+    command
+}
+
+/// Per-transfer `scp` flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScpOptions {
+    /// `-r`
+    pub recursive: bool,
+    /// `-p`: keep modification times and modes
+    pub preserve: bool,
+    /// `-C`
+    pub compress: bool,
+    /// Bandwidth cap in KB/s; scp's `-l` takes Kbit/s, so this is sent times 8
+    pub limit_kbps: Option<u32>,
+}
+
+/// `scp` invocation with the session's port, identity, and shared options; the caller
+/// appends the source and destination (see [`remote_spec`]).
+pub fn scp_command(session: &Session, options: ScpOptions) -> Command {
+    let mut command = Command::new("scp");
+    if options.recursive {
+        command.arg("-r");
+    }
+    if options.preserve {
+        command.arg("-p");
+    }
+    if options.compress {
+        command.arg("-C");
+    }
+    if let Some(limit) = options.limit_kbps {
+        command
+            .arg("-l")
+            .arg(u64::from(limit).saturating_mul(8).to_string());
+    }
+    if let Some(identity) = &session.identity_file {
+        command.arg("-i").arg(identity);
+    }
+    command.arg("-P").arg(session.port.to_string());
+    command.args(shared_options(session));
+    command
+}
+
+/// `ssh -O exit` invocation that asks the session's master connection to shut down.
+/// Fails (non-zero exit) when no master is listening on the control socket.
+pub fn exit_command(session: &Session) -> Command {
+    let mut command = Command::new("ssh");
+    command.arg("-O").arg("exit");
+    command.arg("-p").arg(session.port.to_string());
+    command
+        .arg("-o")
+        .arg(format!("ControlPath={}", control_path(session)));
+    command.arg(ssh_target(session));
+    command
+}
+
+/// Host without the `[...]` some users write around IPv6 addresses.
+fn bare_host(session: &Session) -> &str {
+    session.host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// `user@host` destination for ssh; IPv6 addresses are passed unbracketed.
+fn ssh_target(session: &Session) -> String {
+    format!("{}@{}", session.user, bare_host(session))
+}
+
+/// `user@host:path` operand for scp. IPv6 hosts are bracketed so their colons are not
+/// mistaken for the path separator. The path is passed as one argument, so spaces and
+/// quotes need no escaping.
+pub fn remote_spec(session: &Session, path: &str) -> String {
+    let host = bare_host(session);
+    if host.contains(':') {
+        format!("{}@[{}]:{}", session.user, host, path)
+    } else {
+        format!("{}@{}:{}", session.user, host, path)
+    }
+}
+
+/// `command` as a line that can be pasted into a POSIX shell. Arguments with spaces
+/// or shell metacharacters are single-quoted.
+pub fn command_line(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| shell_word(&arg.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `args` as one line of [`shell_word`]s, which [`split_shell_words`] splits back
+/// into the same list.
+pub fn join_shell_words(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| shell_word(arg.as_str()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split `input` into words like a POSIX shell, without expansions: whitespace
+/// separates words, single quotes keep everything literal, double quotes keep
+/// spaces, and a backslash escapes the next character.
+pub fn split_shell_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = input.chars();
+    while let Some(ch) = chars.next() {
+        if ch.is_whitespace() {
+            words.extend(word.take());
+            continue;
+        }
+        let current = word.get_or_insert_with(String::new);
+        match ch {
+            '\'' => loop {
+                match chars.next() {
+                    Some('\'') => break,
+                    Some(ch) => current.push(ch),
+                    None => return Err("unterminated single quote".to_string()),
+                }
+            },
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    // Inside double quotes a backslash only escapes these.
+                    Some('\\') => match chars.next() {
+                        Some(ch @ ('"' | '\\' | '$' | '`')) => current.push(ch),
+                        Some(ch) => {
+                            current.push('\\');
+                            current.push(ch);
+                        }
+                        None => return Err("unterminated double quote".to_string()),
+                    },
+                    Some(ch) => current.push(ch),
+                    None => return Err("unterminated double quote".to_string()),
+                }
+            },
+            '\\' => match chars.next() {
+                Some(ch) => current.push(ch),
+                None => return Err("trailing backslash".to_string()),
+            },
+            ch => current.push(ch),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+fn shell_word(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "@%+,-./:=_~".contains(ch));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn session() -> Session {
+        Session {
+            name: "web".to_string(),
+            host: "web.example.com".to_string(),
+            user: "deploy".to_string(),
+            port: 2222,
+            identity_file: Some("~/.ssh/id_ed25519".into()),
+            tags: vec![],
+            last_connected_at: None,
+            has_stored_password: false,
+            passwd_unsafe_mode: None,
+            stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
+        }
+    }
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn ssh_command_without_multiplexing() {
+        let session = session();
+        assert!(!needs_openssh(&session));
+        assert_eq!(
+            args(&ssh_command(&session)),
+            [
+                "-p",
+                "2222",
+                "-i",
+                "~/.ssh/id_ed25519",
+                "deploy@web.example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn exec_command_runs_without_prompting() {
+        let session = session();
+        let remote = ["uptime".to_string(), "-p".to_string()];
+        assert_eq!(
+            args(&exec_command(&session, &remote)),
+            [
+                "-p",
+                "2222",
+                "-i",
+                "~/.ssh/id_ed25519",
+                "-o",
+                "BatchMode=yes",
+                "deploy@web.example.com",
+                "uptime",
+                "-p"
+            ]
+        );
+    }
+
+    #[test]
+    fn control_master_adds_control_options() {
+        let mut session = session();
+        session.control_master = true;
+        assert!(needs_openssh(&session));
+        assert_eq!(
+            shared_options(&session),
+            [
+                "-o",
+                "ControlMaster=auto",
+                "-o",
+                "ControlPath=~/.ssh/ssher-%r@%h:%p"
+            ]
+        );
+
+        session.control_path = Some("/tmp/cm-%C".to_string());
+        let options = ScpOptions {
+            recursive: true,
+            preserve: true,
+            compress: true,
+            limit_kbps: Some(100),
+        };
+        let scp = scp_command(&session, options);
+        assert_eq!(scp.get_program(), "scp");
+        assert_eq!(
+            args(&scp),
+            [
+                "-r",
+                "-p",
+                "-C",
+                "-l",
+                "800",
+                "-i",
+                "~/.ssh/id_ed25519",
+                "-P",
+                "2222",
+                "-o",
+                "ControlMaster=auto",
+                "-o",
+                "ControlPath=/tmp/cm-%C"
+            ]
+        );
+    }
+
+    #[test]
+    fn proxy_jump_hops_through_bastion() {
+        let mut session = session();
+        session.proxy_jump = Some("me@bastion:2200".to_string());
+        assert!(needs_openssh(&session));
+        assert_eq!(
+            args(&ssh_command(&session)),
+            [
+                "-p",
+                "2222",
+                "-i",
+                "~/.ssh/id_ed25519",
+                "-J",
+                "me@bastion:2200",
+                "deploy@web.example.com"
+            ]
+        );
+        assert!(
+            args(&scp_command(&session, ScpOptions::default()))
+                .ends_with(&["-J".to_string(), "me@bastion:2200".to_string()])
+        );
+    }
+
+    #[test]
+    fn free_form_options_follow_the_typed_ones() {
+        let mut session = session();
+        session.proxy_jump = Some("gw".to_string());
+        session.options = BTreeMap::from([
+            ("ServerAliveInterval".to_string(), "30".to_string()),
+            ("LocalForward".to_string(), "8080 localhost:80".to_string()),
+        ]);
+        assert!(needs_openssh(&session));
+        assert_eq!(
+            shared_options(&session),
+            [
+                "-J",
+                "gw",
+                "-o",
+                "LocalForward=8080 localhost:80",
+                "-o",
+                "ServerAliveInterval=30"
+            ]
+        );
+    }
+
+    #[test]
+    fn extra_args_go_before_the_destination_as_separate_arguments() {
+        let mut session = session();
+        session.extra_args = vec![
+            "-A".to_string(),
+            "-o".to_string(),
+            "RemoteCommand=tmux new -A".to_string(),
+        ];
+        assert!(needs_openssh(&session));
+        assert_eq!(
+            args(&ssh_command(&session))[4..],
+            [
+                "-A",
+                "-o",
+                "RemoteCommand=tmux new -A",
+                "deploy@web.example.com"
+            ]
+        );
+        // scp has its own flags, so ssh-only arguments are not passed to it.
+        assert!(!args(&scp_command(&session, ScpOptions::default())).contains(&"-A".to_string()));
+    }
+
+    #[test]
+    fn forwards_are_added_to_interactive_logins_only() {
+        let mut session = session();
+        session.local_forwards = vec!["5432:db.internal:5432".to_string()];
+        session.remote_forwards = vec!["127.0.0.1:9000:localhost:3000".to_string()];
+        assert!(needs_openssh(&session));
+        assert_eq!(
+            args(&ssh_command(&session))[4..],
+            [
+                "-L",
+                "5432:db.internal:5432",
+                "-R",
+                "127.0.0.1:9000:localhost:3000",
+                "deploy@web.example.com"
+            ]
+        );
+        assert!(!args(&exec_command(&session, &[])).contains(&"-L".to_string()));
+    }
+
+    #[test]
+    fn tunnel_command_uses_only_the_given_forwards() {
+        let mut session = session();
+        session.local_forwards = vec!["5432:db.internal:5432".to_string()];
+        let command = tunnel_command(
+            &session,
+            &["8080:localhost:80".to_string()],
+            &["9000:localhost:3000".to_string()],
+            true,
+        );
+        assert_eq!(
+            args(&command)[4..],
+            [
+                "-L",
+                "8080:localhost:80",
+                "-R",
+                "9000:localhost:3000",
+                "-N",
+                "deploy@web.example.com"
+            ]
+        );
+        assert!(!args(&tunnel_command(&session, &[], &[], false)).contains(&"-N".to_string()));
+    }
+
+    #[test]
+    fn validate_forward_checks_ports_and_host() {
+        for good in ["8080:localhost:80", "0.0.0.0:8080:web:80", "8080:[::1]:80"] {
+            assert_eq!(validate_forward(good), Ok(()), "{good}");
+        }
+        assert!(validate_forward("8080").unwrap_err().contains("expected"));
+        assert!(
+            validate_forward("8080::80")
+                .unwrap_err()
+                .contains("missing host")
+        );
+        assert!(
+            validate_forward("http:localhost:80")
+                .unwrap_err()
+                .contains("'http' is not a port number")
+        );
+        assert!(validate_forward("8080:localhost:0").is_err());
+    }
+
+    #[test]
+    fn forward_specs_convert_to_and_from_ssh_config() {
+        assert_eq!(
+            forward_config_value("8080:localhost:80"),
+            "8080 localhost:80"
+        );
+        assert_eq!(
+            forward_config_value("127.0.0.1:8080:[::1]:80"),
+            "127.0.0.1:8080 [::1]:80"
+        );
+        assert_eq!(
+            forward_from_config("127.0.0.1:8080  [::1]:80").as_deref(),
+            Some("127.0.0.1:8080:[::1]:80")
+        );
+        assert_eq!(forward_from_config("8080"), None);
+    }
+
+    #[test]
+    fn parse_option_requires_key_and_value() {
+        assert_eq!(
+            parse_option("ServerAliveInterval=30"),
+            Some(("ServerAliveInterval".to_string(), "30".to_string()))
+        );
+        assert_eq!(
+            parse_option("SetEnv=A=b"),
+            Some(("SetEnv".to_string(), "A=b".to_string()))
+        );
+        for raw in ["ServerAliveInterval", "=30", "Key=", "Bad Key=1", "-oX=1"] {
+            assert_eq!(parse_option(raw), None, "{raw}");
+        }
+    }
+
+    #[test]
+    fn exit_command_targets_control_socket() {
+        let session = session();
+        assert_eq!(
+            args(&exit_command(&session)),
+            [
+                "-O",
+                "exit",
+                "-p",
+                "2222",
+                "-o",
+                "ControlPath=~/.ssh/ssher-%r@%h:%p",
+                "deploy@web.example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn ipv6_hosts_are_bracketed_only_for_scp() {
+        for host in ["2001:db8::1", "[2001:db8::1]"] {
+            let mut session = session();
+            session.host = host.to_string();
+            assert_eq!(
+                args(&ssh_command(&session)).last().unwrap(),
+                "deploy@2001:db8::1"
+            );
+            assert_eq!(
+                remote_spec(&session, "/tmp/x"),
+                "deploy@[2001:db8::1]:/tmp/x"
+            );
+        }
+        assert_eq!(
+            remote_spec(&session(), "/tmp/x"),
+            "deploy@web.example.com:/tmp/x"
+        );
+    }
+
+    #[test]
+    fn paths_with_spaces_and_quotes_stay_single_arguments() {
+        let mut session = session();
+        session.identity_file = Some("/home/me/My Keys/id_ed25519".into());
+        let ssh = args(&ssh_command(&session));
+        assert_eq!(ssh[3], "/home/me/My Keys/id_ed25519");
+        assert_eq!(ssh.len(), 5);
+
+        let remote = remote_spec(&session, "/srv/it's a \"file\".txt");
+        assert_eq!(remote, "deploy@web.example.com:/srv/it's a \"file\".txt");
+        let mut scp = scp_command(&session, ScpOptions::default());
+        scp.arg("./local file.txt").arg(&remote);
+        let scp = args(&scp);
+        assert_eq!(&scp[scp.len() - 2..], ["./local file.txt", remote.as_str()]);
+    }
+
+    #[test]
+    fn ports_at_the_u16_boundary() {
+        let mut session = session();
+        session.port = u16::MAX;
+        assert_eq!(args(&ssh_command(&session))[1], "65535");
+        assert_eq!(
+            args(&scp_command(&session, ScpOptions::default()))[3],
+            "65535"
+        );
+        session.port = 1;
+        assert_eq!(args(&exit_command(&session))[3], "1");
+    }
+
+    #[test]
+    fn command_line_quotes_only_what_the_shell_would_split() {
+        let mut session = session();
+        session.options =
+            BTreeMap::from([("LocalForward".to_string(), "8080 localhost:80".to_string())]);
+        let line = command_line(&ssh_command(&session));
+        assert!(line.starts_with("ssh -p "));
+        assert!(line.contains(" -o 'LocalForward=8080 localhost:80' "));
+        assert!(line.ends_with(&format!(" {}", ssh_target(&session))));
+        assert_eq!(shell_word("it's"), "'it'\\''s'");
+        assert_eq!(shell_word(""), "''");
+    }
+
+    #[test]
+    fn shell_words_round_trip() {
+        let args: Vec<String> = [
+            "-oKexAlgorithms=a,b",
+            "-oRemoteCommand=tmux new -A",
+            "it's",
+            "",
+            "-A",
+        ]
+        .map(String::from)
+        .to_vec();
+        let line = join_shell_words(&args);
+        assert_eq!(
+            line,
+            "-oKexAlgorithms=a,b '-oRemoteCommand=tmux new -A' 'it'\\''s' '' -A"
+        );
+        assert_eq!(split_shell_words(&line).unwrap(), args);
+
+        assert_eq!(
+            split_shell_words(r#" -o "SetEnv=A=\"b c\"" x\ y "#).unwrap(),
+            ["-o", r#"SetEnv=A="b c""#, "x y"]
+        );
+        assert!(split_shell_words("  ").unwrap().is_empty());
+        assert_eq!(
+            split_shell_words("-o 'Foo=bar").unwrap_err(),
+            "unterminated single quote"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::Mutex;
+use std::thread;
+
+/// Upper bound used when `--parallel` is not given.
+pub fn default_parallelism() -> usize {
+    thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(4)
+}
+
+/// Resolve a user-supplied `--parallel` value; `None` or `0` fall back to the default.
+pub fn resolve_parallelism(requested: Option<usize>) -> usize {
+    match requested {
+        Some(limit) if limit > 0 => limit,
+        _ => default_parallelism(),
+    }
+}
+
+/// Run `task` over `items` with at most `limit` running at once.
+///
+/// Results are returned in the same order as `items`.
+pub fn run_bounded<T, R, F>(items: Vec<T>, limit: usize, task: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let total = items.len();
+    let workers = limit.max(1).min(total);
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new(Vec::with_capacity(total));
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let next = queue.lock().expect("work queue poisoned").next();
+                    let Some((index, item)) = next else {
+                        break;
+                    };
+                    let result = task(item);
+                    results
+                        .lock()
+                        .expect("result list poisoned")
+                        .push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().expect("result list poisoned");
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn run_bounded_preserves_input_order() {
+        let results = run_bounded((0..20).collect(), 4, |value: u32| value * 2);
+        assert_eq!(results, (0..20).map(|value| value * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn run_bounded_never_exceeds_limit() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        run_bounded((0..12).collect(), 3, |_: u32| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(10));
+            running.fetch_sub(1, Ordering::SeqCst);
+        });
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[test]
+    fn run_bounded_handles_empty_input() {
+        let results: Vec<u32> = run_bounded(Vec::new(), 4, |value: u32| value);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn resolve_parallelism_ignores_zero() {
+        assert_eq!(resolve_parallelism(Some(8)), 8);
+        assert_eq!(resolve_parallelism(Some(0)), default_parallelism());
+        assert_eq!(resolve_parallelism(None), default_parallelism());
+    }
+}
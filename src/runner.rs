@@ -0,0 +1,251 @@
+//! Runs the external `ssh`/`scp` processes, behind a trait so tests can swap in a
+//! fake that records each command instead of executing it.
+//!
+//! Sessions that connect through the built-in libssh2 client (`crate::ssh`) do not
+//! start a process and so do not go through [`CommandRunner`]; only the OpenSSH
+//! paths can be faked this way.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
+use std::sync::{LazyLock, Mutex};
+
+/// `Sync` so fan-out commands can share one runner across worker threads.
+pub trait CommandRunner: Sync {
+    /// Run `command` with inherited stdio and wait for it (interactive ssh).
+    fn status(&self, command: &mut Command) -> io::Result<ExitStatus>;
+    /// Run `command` with captured stdout/stderr.
+    fn output(&self, command: &mut Command) -> io::Result<Output>;
+}
+
+/// Executes commands for real.
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn status(&self, command: &mut Command) -> io::Result<ExitStatus> {
+        ensure_installed(command.get_program())?;
+        command.status()
+    }
+
+    fn output(&self, command: &mut Command) -> io::Result<Output> {
+        ensure_installed(command.get_program())?;
+        command.output()
+    }
+}
+
+/// Whether each program looked up so far is on `PATH`; one lookup per run.
+static INSTALLED: LazyLock<Mutex<HashMap<OsString, bool>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Fail with an install hint, instead of the bare "No such file or directory" a
+/// spawn would give, when `program` is not on `PATH`. Paths are left to the spawn.
+fn ensure_installed(program: &OsStr) -> io::Result<()> {
+    if Path::new(program).components().count() > 1 {
+        return Ok(());
+    }
+    let found = *INSTALLED
+        .lock()
+        .expect("installed cache poisoned")
+        .entry(program.to_owned())
+        .or_insert_with(|| find_on_path(program).is_some());
+    if found {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "{} not found on PATH; {}",
+                program.to_string_lossy(),
+                INSTALL_HINT
+            ),
+        ))
+    }
+}
+
+pub const INSTALL_HINT: &str =
+    "install OpenSSH (the openssh-client package on most Linux distributions)";
+
+/// The first executable file named `program` in the `PATH` directories.
+pub fn find_on_path(program: impl AsRef<OsStr>) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    find_in_dirs(program.as_ref(), &path)
+}
+
+fn find_in_dirs(program: &OsStr, dirs: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(dirs)
+        .map(|dir| dir.join(program))
+        .find(|candidate| {
+            candidate
+                .metadata()
+                .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        })
+}
+
+/// Program and arguments of `command`, joined by spaces.
+pub fn describe(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+extern "C" fn ignore_interrupt(_: libc::c_int) {}
+
+/// Keeps `se` running through Ctrl-C while a foreground command (such as `ssh -N`)
+/// runs, so work after it still happens. The child gets the signal as usual: a
+/// caught signal is reset to its default action on exec. Dropping restores the
+/// previous handler.
+pub struct InterruptGuard {
+    /// Handler to restore; `None` when installing ours failed and nothing changed.
+    previous: Option<libc::sigaction>,
+}
+
+impl InterruptGuard {
+    pub fn install() -> Self {
+        // SAFETY: `sigaction` is plain old data, so all-zero is a valid empty value
+        // that `sigemptyset` and the assignments below fill in. The handler is an
+        // `extern "C"` fn that does nothing, which is async-signal-safe, and both
+        // pointers passed to `sigaction` refer to live locals.
+        let previous = unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = ignore_interrupt as *const () as libc::sighandler_t;
+            libc::sigemptyset(&mut action.sa_mask);
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            (libc::sigaction(libc::SIGINT, &action, &mut previous) == 0).then_some(previous)
+        };
+        if previous.is_none() {
+            log::warn!("unable to ignore Ctrl-C: {}", io::Error::last_os_error());
+        }
+        Self { previous }
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        let Some(previous) = &self.previous else {
+            return;
+        };
+        // SAFETY: `previous` was filled in by a successful `sigaction` call in
+        // `install`, so it is a valid disposition to put back; the old-action pointer
+        // may be null.
+        let result = unsafe { libc::sigaction(libc::SIGINT, previous, std::ptr::null_mut()) };
+        if result != 0 {
+            log::warn!(
+                "unable to restore the Ctrl-C handler: {}",
+                io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod fake {
+    use super::{CommandRunner, describe};
+    use std::io;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{Command, ExitStatus, Output};
+    use std::sync::Mutex;
+
+    /// Records every command (see [`describe`]) and reports the same exit status for
+    /// each.
+    pub struct FakeRunner {
+        pub commands: Mutex<Vec<String>>,
+        /// Raw wait status, as `ExitStatus::from_raw` takes it.
+        pub wait_status: i32,
+        pub stderr: String,
+    }
+
+    impl FakeRunner {
+        pub fn succeeding() -> Self {
+            Self::exiting(0, "")
+        }
+
+        pub fn exiting(exit_code: i32, stderr: &str) -> Self {
+            Self {
+                commands: Mutex::new(Vec::new()),
+                wait_status: exit_code << 8,
+                stderr: stderr.to_string(),
+            }
+        }
+
+        /// Commands end as if killed by `signal`, like ssh after Ctrl-C.
+        pub fn killed_by(signal: i32) -> Self {
+            Self {
+                commands: Mutex::new(Vec::new()),
+                wait_status: signal,
+                stderr: String::new(),
+            }
+        }
+
+        fn record(&self, command: &Command) -> ExitStatus {
+            self.commands
+                .lock()
+                .expect("commands poisoned")
+                .push(describe(command));
+            ExitStatus::from_raw(self.wait_status)
+        }
+    }
+
+    impl CommandRunner for FakeRunner {
+        fn status(&self, command: &mut Command) -> io::Result<ExitStatus> {
+            Ok(self.record(command))
+        }
+
+        fn output(&self, command: &mut Command) -> io::Result<Output> {
+            Ok(Output {
+                status: self.record(command),
+                stdout: Vec::new(),
+                stderr: self.stderr.clone().into_bytes(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fake::FakeRunner;
+    use super::*;
+
+    #[test]
+    fn fake_runner_records_commands_and_exit_codes() {
+        let runner = FakeRunner::exiting(255, "no route");
+        let mut command = Command::new("ssh");
+        command.arg("-p").arg("22").arg("me@web");
+
+        let status = runner.status(&mut command).unwrap();
+        assert_eq!(status.code(), Some(255));
+        let output = runner.output(&mut command).unwrap();
+        assert_eq!(output.stderr, b"no route");
+        assert_eq!(
+            *runner.commands.lock().unwrap(),
+            ["ssh -p 22 me@web", "ssh -p 22 me@web"]
+        );
+    }
+
+    #[test]
+    fn find_in_dirs_skips_missing_and_non_executable_files() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let plain = first.path().join("ssh");
+        std::fs::write(&plain, "").unwrap();
+        let executable = second.path().join("ssh");
+        std::fs::write(&executable, "").unwrap();
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let dirs = std::env::join_paths([first.path(), second.path()]).unwrap();
+        assert_eq!(find_in_dirs(OsStr::new("ssh"), &dirs), Some(executable));
+        assert_eq!(find_in_dirs(OsStr::new("scp"), &dirs), None);
+    }
+
+    #[test]
+    fn missing_programs_fail_with_an_install_hint() {
+        assert!(ensure_installed(OsStr::new("/nonexistent/ssh")).is_ok());
+        let err = ensure_installed(OsStr::new("ssher-test-missing-binary")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("install OpenSSH"));
+    }
+}
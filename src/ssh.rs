@@ -5,11 +5,11 @@ use crate::password;
 use anyhow::{Context, Result, anyhow};
 use ssh2::{KeyboardInteractivePrompt, Prompt, Session as Ssh2Session};
 use std::collections::HashSet;
-use std::io::IsTerminal;
-use std::net::TcpStream;
+use std::io::{IsTerminal, Read};
+use std::net::{TcpStream, ToSocketAddrs};
 use std::os::fd::AsRawFd;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub struct AuthConfig {
     pub identity_file: Option<String>,
@@ -24,6 +24,49 @@ pub struct SshConnection {
     session: Ssh2Session,
 }
 
+/// Result of a quick TCP reachability probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeResult {
+    /// Time taken to establish the TCP connection
+    pub latency: Duration,
+    /// SSH identification line sent by the server, if one arrived in time
+    pub banner: Option<String>,
+}
+
+/// Connect to `host:port` within `timeout` and try to read the SSH banner.
+pub fn probe_tcp(host: &str, port: u16, timeout: Duration) -> Result<ProbeResult> {
+    let addrs = (host, port)
+        .to_socket_addrs()
+        .with_context(|| format!("unable to resolve {}:{}", host, port))?;
+
+    let mut last_err = None;
+    for addr in addrs {
+        let started = Instant::now();
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(mut stream) => {
+                let latency = started.elapsed();
+                let _ = stream.set_read_timeout(Some(timeout));
+                let mut buf = [0u8; 256];
+                let banner = match stream.read(&mut buf) {
+                    Ok(read) if read > 0 => String::from_utf8_lossy(&buf[..read])
+                        .lines()
+                        .next()
+                        .filter(|line| line.starts_with("SSH-"))
+                        .map(str::to_string),
+                    _ => None,
+                };
+                return Ok(ProbeResult { latency, banner });
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    match last_err {
+        Some(err) => Err(err).with_context(|| format!("unable to reach {}:{}", host, port)),
+        None => Err(anyhow!("no addresses found for {}:{}", host, port)),
+    }
+}
+
 const REMOTE_COMPLETION_MAX_ENTRIES: usize = 256;
 
 fn poll_shell_fds(
@@ -768,6 +811,14 @@ impl SshConnection {
         Ok(())
     }
 
+    /// Whether `remote_path` exists and is a directory (via SFTP `stat`).
+    pub fn is_remote_dir(&self, remote_path: &str) -> Result<bool> {
+        let sftp = self.session.sftp().context("failed to start SFTP")?;
+        Ok(sftp
+            .stat(Path::new(remote_path))
+            .is_ok_and(|stat| stat.is_dir()))
+    }
+
     pub fn download(&self, remote_path: &str, local_path: &std::path::Path) -> Result<()> {
         let remote_path = std::path::Path::new(remote_path);
         let (mut channel, _stat) = self
@@ -801,8 +852,35 @@ impl SshConnection {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
     use tempfile::tempdir;
 
+    #[test]
+    fn probe_tcp_reads_ssh_banner() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let port = listener.local_addr().expect("addr").port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            stream.write_all(b"SSH-2.0-Test\r\n").expect("write");
+        });
+
+        let result = probe_tcp("127.0.0.1", port, Duration::from_secs(2)).expect("probe");
+        assert_eq!(result.banner.as_deref(), Some("SSH-2.0-Test"));
+        server.join().expect("join");
+    }
+
+    #[test]
+    fn probe_tcp_fails_for_closed_port() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .expect("bind")
+            .local_addr()
+            .expect("addr")
+            .port();
+
+        assert!(probe_tcp("127.0.0.1", port, Duration::from_millis(500)).is_err());
+    }
+
     #[test]
     fn auth_method_hints_parse_known_methods() {
         let hints = AuthMethodHints::from_server("publickey,password");
@@ -0,0 +1,325 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// What a logged connection was used for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionKind {
+    Ssh,
+    Scp,
+}
+
+impl ConnectionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConnectionKind::Ssh => "ssh",
+            ConnectionKind::Scp => "scp",
+        }
+    }
+}
+
+/// One line of the connection log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConnectionEvent {
+    pub session: String,
+    /// Unix timestamp (seconds) the connection finished
+    pub timestamp: i64,
+    pub kind: ConnectionKind,
+    pub duration_secs: u64,
+}
+
+/// The log lives next to the store file: `sessions.json` logs to
+/// `sessions.connections.jsonl`, so every profile keeps its own timeline.
+pub fn connection_log_path(store_path: &Path) -> PathBuf {
+    store_path.with_extension("connections.jsonl")
+}
+
+/// How many rotated files (`.1` newest, `.2` oldest) are kept besides the live log.
+pub const ROTATED_FILES: usize = 2;
+
+/// When the live log rotates; a `None` limit never triggers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogLimits {
+    pub max_entries: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", index));
+    PathBuf::from(rotated)
+}
+
+/// Oldest first: `.2`, `.1`, then the live log.
+fn log_files(path: &Path) -> Vec<PathBuf> {
+    (1..=ROTATED_FILES)
+        .rev()
+        .map(|index| rotated_path(path, index))
+        .chain(std::iter::once(path.to_path_buf()))
+        .collect()
+}
+
+/// Append `event`, first rotating the live log if the new line would take it past
+/// `limits`.
+pub fn append_event(path: &Path, event: &ConnectionEvent, limits: LogLimits) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("unable to create log directory {}", parent.display()))?;
+    }
+    let line = serde_json::to_string(event).context("unable to serialize connection event")?;
+    if is_full(path, line.len() as u64 + 1, limits)? {
+        rotate(path)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("unable to open connection log {}", path.display()))?;
+    writeln!(file, "{}", line)
+        .with_context(|| format!("unable to write connection log {}", path.display()))
+}
+
+fn is_full(path: &Path, incoming_bytes: u64, limits: LogLimits) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    if let Some(max_bytes) = limits.max_bytes {
+        let size = fs::metadata(path)
+            .with_context(|| format!("unable to read connection log {}", path.display()))?
+            .len();
+        if size > 0 && size + incoming_bytes > max_bytes {
+            return Ok(true);
+        }
+    }
+    if let Some(max_entries) = limits.max_entries {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("unable to read connection log {}", path.display()))?;
+        let entries = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        if entries as u64 >= max_entries {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Shift `.1` to `.2` (dropping the old `.2`) and the live log to `.1`.
+fn rotate(path: &Path) -> Result<()> {
+    for index in (1..=ROTATED_FILES).rev() {
+        let from = if index == 1 {
+            path.to_path_buf()
+        } else {
+            rotated_path(path, index - 1)
+        };
+        if from.exists() {
+            let to = rotated_path(path, index);
+            fs::rename(&from, &to).with_context(|| {
+                format!("unable to rotate {} to {}", from.display(), to.display())
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Read every event across the rotated files and the live log, oldest first. Missing
+/// files are empty; lines that do not parse (e.g. cut short by a crash mid-write) are
+/// skipped with a warning.
+pub fn read_events(path: &Path) -> Result<Vec<ConnectionEvent>> {
+    let mut events = Vec::new();
+    for file in log_files(path) {
+        events.extend(read_file(&file)?);
+    }
+    Ok(events)
+}
+
+fn read_file(path: &Path) -> Result<Vec<ConnectionEvent>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("unable to read connection log {}", path.display()))?;
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match serde_json::from_str(line) {
+            Ok(event) => Some(event),
+            Err(err) => {
+                log::warn!("skipping line {} of {}: {}", index + 1, path.display(), err);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Keep only the newest `keep` events: they are rewritten into the live log and the
+/// rotated files are removed. Returns how many events were dropped.
+pub fn prune(path: &Path, keep: usize) -> Result<usize> {
+    let events = read_events(path)?;
+    let dropped = events.len().saturating_sub(keep);
+    if dropped == 0 {
+        return Ok(0);
+    }
+    let mut content = String::new();
+    for event in &events[dropped..] {
+        content.push_str(
+            &serde_json::to_string(event).context("unable to serialize connection event")?,
+        );
+        content.push('\n');
+    }
+    fs::write(path, content)
+        .with_context(|| format!("unable to write connection log {}", path.display()))?;
+    for index in 1..=ROTATED_FILES {
+        let rotated = rotated_path(path, index);
+        if rotated.exists() {
+            fs::remove_file(&rotated)
+                .with_context(|| format!("unable to remove {}", rotated.display()))?;
+        }
+    }
+    Ok(dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn event(session: &str, timestamp: i64, kind: ConnectionKind) -> ConnectionEvent {
+        ConnectionEvent {
+            session: session.to_string(),
+            timestamp,
+            kind,
+            duration_secs: 42,
+        }
+    }
+
+    #[test]
+    fn log_path_sits_next_to_store() {
+        assert_eq!(
+            connection_log_path(Path::new("/cfg/profiles/work.json")),
+            PathBuf::from("/cfg/profiles/work.connections.jsonl")
+        );
+    }
+
+    #[test]
+    fn appends_and_reads_events_in_order() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.connections.jsonl");
+        assert!(read_events(&path).expect("read missing").is_empty());
+
+        append_event(
+            &path,
+            &event("web", 10, ConnectionKind::Ssh),
+            LogLimits::default(),
+        )
+        .expect("append");
+        append_event(
+            &path,
+            &event("db", 20, ConnectionKind::Scp),
+            LogLimits::default(),
+        )
+        .expect("append");
+
+        let content = std::fs::read_to_string(&path).expect("read");
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.contains(r#""kind":"scp""#));
+        assert_eq!(
+            read_events(&path).expect("read"),
+            vec![
+                event("web", 10, ConnectionKind::Ssh),
+                event("db", 20, ConnectionKind::Scp)
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_truncated_lines() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.connections.jsonl");
+        append_event(
+            &path,
+            &event("web", 10, ConnectionKind::Ssh),
+            LogLimits::default(),
+        )
+        .expect("append");
+        let mut file = OpenOptions::new().append(true).open(&path).expect("open");
+        write!(file, r#"{{"session":"db","time"#).expect("write");
+
+        assert_eq!(
+            read_events(&path).expect("read"),
+            vec![event("web", 10, ConnectionKind::Ssh)]
+        );
+    }
+
+    fn append_all(path: &Path, count: i64, limits: LogLimits) {
+        for timestamp in 0..count {
+            append_event(path, &event("web", timestamp, ConnectionKind::Ssh), limits)
+                .expect("append");
+        }
+    }
+
+    fn timestamps(events: &[ConnectionEvent]) -> Vec<i64> {
+        events.iter().map(|event| event.timestamp).collect()
+    }
+
+    #[test]
+    fn rotates_by_entry_count_and_reads_across_files() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.connections.jsonl");
+        let limits = LogLimits {
+            max_entries: Some(2),
+            max_bytes: None,
+        };
+        append_all(&path, 7, limits);
+
+        assert_eq!(timestamps(&read_file(&path).expect("live")), [6]);
+        assert_eq!(
+            timestamps(&read_file(&rotated_path(&path, 1)).expect(".1")),
+            [4, 5]
+        );
+        // The oldest entries fall off once `.2` is shifted out.
+        assert_eq!(
+            timestamps(&read_events(&path).expect("read")),
+            [2, 3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn rotates_by_size() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.connections.jsonl");
+        let line_len = serde_json::to_string(&event("web", 0, ConnectionKind::Ssh))
+            .expect("serialize")
+            .len() as u64
+            + 1;
+        let limits = LogLimits {
+            max_entries: None,
+            max_bytes: Some(line_len * 3),
+        };
+        append_all(&path, 4, limits);
+
+        assert_eq!(timestamps(&read_file(&path).expect("live")), [3]);
+        assert_eq!(timestamps(&read_events(&path).expect("read")), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn prune_keeps_newest_entries() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.connections.jsonl");
+        let limits = LogLimits {
+            max_entries: Some(2),
+            max_bytes: None,
+        };
+        append_all(&path, 5, limits);
+
+        assert_eq!(prune(&path, 3).expect("prune"), 2);
+        assert!(!rotated_path(&path, 1).exists());
+        assert_eq!(timestamps(&read_file(&path).expect("live")), [2, 3, 4]);
+        assert_eq!(prune(&path, 10).expect("prune again"), 0);
+    }
+}
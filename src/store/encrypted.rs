@@ -0,0 +1,213 @@
+use super::{ConnectionEvent, JsonFileStore, SessionStore, StoreConfig};
+use crate::model::Session;
+use anyhow::{Result, anyhow};
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the passphrase of an encrypted store.
+pub const PASSPHRASE_ENV: &str = "SSHER_PASSPHRASE";
+
+/// First bytes of an encrypted store file, followed by the salt, the nonce and the
+/// ChaCha20-Poly1305 ciphertext of the store JSON.
+pub(super) const MAGIC: &[u8] = b"SSHER-ENCRYPTED-1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Session store whose file holds the same JSON as [`JsonFileStore`], encrypted with
+/// a key derived from a passphrase (Argon2id, then ChaCha20-Poly1305).
+///
+/// A plaintext store at `path` is read as-is and encrypted by the next write. The
+/// lock file and the connection log next to it stay plaintext.
+pub struct EncryptedFileStore {
+    inner: JsonFileStore,
+}
+
+impl EncryptedFileStore {
+    pub fn new(path: PathBuf, passphrase: String) -> Self {
+        Self {
+            inner: JsonFileStore {
+                path,
+                passphrase: Some(passphrase),
+            },
+        }
+    }
+}
+
+/// Whether the file at `path` starts with the encrypted store header.
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0u8; MAGIC.len()];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| header == MAGIC)
+}
+
+pub(super) fn encrypt(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt)
+        .and_then(|()| getrandom::getrandom(&mut nonce))
+        .map_err(|err| anyhow!("unable to generate random bytes: {}", err))?;
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| anyhow!("unable to encrypt store"))?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt a buffer produced by [`encrypt`]. A wrong passphrase and a tampered file
+/// look the same to the cipher, so both get the same error.
+pub(super) fn decrypt(passphrase: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let body = data
+        .strip_prefix(MAGIC)
+        .filter(|body| body.len() >= SALT_LEN + NONCE_LEN)
+        .ok_or_else(|| anyhow!("encrypted store is truncated"))?;
+    let (salt, rest) = body.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("wrong passphrase (or the encrypted store is corrupted)"))
+}
+
+/// Whether `passphrase` decrypts the encrypted store at `path`.
+pub(super) fn unlocks(path: &Path, passphrase: &str) -> bool {
+    std::fs::read(path).is_ok_and(|data| decrypt(passphrase, &data).is_ok())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| anyhow!("unable to derive store key: {}", err))?;
+    Ok(key)
+}
+
+impl SessionStore for EncryptedFileStore {
+    fn add(&self, session: Session) -> Result<()> {
+        self.inner.add(session)
+    }
+
+    fn update(&self, session: Session) -> Result<()> {
+        self.inner.update(session)
+    }
+
+    fn rename(&self, old_name: &str, session: Session) -> Result<()> {
+        self.inner.rename(old_name, session)
+    }
+
+    fn rename_replacing(&self, old_name: &str, session: Session) -> Result<()> {
+        self.inner.rename_replacing(old_name, session)
+    }
+
+    fn modify(
+        &self,
+        name: &str,
+        change: &mut dyn FnMut(&mut Session) -> Result<()>,
+    ) -> Result<Session> {
+        self.inner.modify(name, change)
+    }
+
+    fn list(&self) -> Result<Vec<Session>> {
+        self.inner.list()
+    }
+
+    fn remove(&self, name: &str) -> Result<()> {
+        self.inner.remove(name)
+    }
+
+    fn touch_last_connected(&self, name: &str, timestamp: i64) -> Result<()> {
+        self.inner.touch_last_connected(name, timestamp)
+    }
+
+    fn record_duration(&self, name: &str, secs: u64) -> Result<()> {
+        self.inner.record_duration(name, secs)
+    }
+
+    fn log_connection(&self, event: &ConnectionEvent) -> Result<()> {
+        self.inner.log_connection(event)
+    }
+
+    fn connection_log(&self) -> Result<Vec<ConnectionEvent>> {
+        self.inner.connection_log()
+    }
+
+    fn prune_connection_log(&self, keep: usize) -> Result<usize> {
+        self.inner.prune_connection_log(keep)
+    }
+
+    fn get_config(&self) -> Result<StoreConfig> {
+        self.inner.get_config()
+    }
+
+    fn set_config(&self, config: &StoreConfig) -> Result<()> {
+        self.inner.set_config(config)
+    }
+
+    fn repair(&self) -> Result<Vec<String>> {
+        self.inner.repair()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn round_trips_and_hides_the_json() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.json");
+        let store = EncryptedFileStore::new(path.clone(), "hunter2".to_string());
+        store
+            .add(Session::builder("web", "web.internal", "me").build())
+            .expect("add");
+
+        assert!(is_encrypted(&path));
+        let raw = std::fs::read(&path).expect("read");
+        assert!(!String::from_utf8_lossy(&raw).contains("web.internal"));
+        assert_eq!(store.list().expect("list")[0].host, "web.internal");
+    }
+
+    #[test]
+    fn wrong_passphrase_is_reported_as_such() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.json");
+        EncryptedFileStore::new(path.clone(), "right".to_string())
+            .add(Session::builder("web", "example.com", "me").build())
+            .expect("add");
+
+        let err = EncryptedFileStore::new(path.clone(), "wrong".to_string())
+            .list()
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("wrong passphrase"));
+
+        let err = JsonFileStore::new(path).list().unwrap_err();
+        assert!(format!("{:#}", err).contains(PASSPHRASE_ENV));
+    }
+
+    #[test]
+    fn encrypts_an_existing_plaintext_store_on_write() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.json");
+        JsonFileStore::new(path.clone())
+            .add(Session::builder("web", "example.com", "me").build())
+            .expect("add");
+        assert!(!is_encrypted(&path));
+
+        let store = EncryptedFileStore::new(path.clone(), "pw".to_string());
+        store
+            .add(Session::builder("db", "example.com", "me").build())
+            .expect("add");
+        assert!(is_encrypted(&path));
+        assert_eq!(store.list().expect("list").len(), 2);
+    }
+}
@@ -0,0 +1,144 @@
+use super::{ConnectionEvent, SessionStore, StoreConfig, ensure_unique_names, parse_store_data};
+use crate::model::Session;
+use anyhow::{Context, Result, anyhow};
+
+/// Environment variable holding inline session JSON.
+pub const SESSIONS_ENV: &str = "SSHER_SESSIONS";
+
+/// Read-only store backed by JSON content from `SSHER_SESSIONS`.
+///
+/// Accepts the same formats as the store file. Any write fails, except
+/// `touch_last_connected`, `record_duration` and `log_connection`, which are
+/// silently skipped so connecting still works.
+pub struct EnvSessionStore {
+    sessions: Vec<Session>,
+    config: StoreConfig,
+}
+
+impl EnvSessionStore {
+    pub fn from_json(content: &str) -> Result<Self> {
+        let data = parse_store_data(content)
+            .with_context(|| format!("unable to parse sessions from {}", SESSIONS_ENV))?;
+        ensure_unique_names(&data, SESSIONS_ENV)?;
+        Ok(Self {
+            config: StoreConfig {
+                passwd_unsafe_mode: data.passwd_unsafe_mode,
+                passwd_unsafe_key: data.passwd_unsafe_key,
+                log_connections: data.log_connections,
+                log_max_entries: data.log_max_entries,
+                log_max_bytes: data.log_max_bytes,
+            },
+            sessions: data.sessions,
+        })
+    }
+
+    fn read_only() -> anyhow::Error {
+        anyhow!(
+            "store is read-only (env-backed via {}); unset it to modify sessions",
+            SESSIONS_ENV
+        )
+    }
+}
+
+impl SessionStore for EnvSessionStore {
+    fn add(&self, _session: Session) -> Result<()> {
+        Err(Self::read_only())
+    }
+
+    fn update(&self, _session: Session) -> Result<()> {
+        Err(Self::read_only())
+    }
+
+    fn rename(&self, _old_name: &str, _session: Session) -> Result<()> {
+        Err(Self::read_only())
+    }
+
+    fn rename_replacing(&self, _old_name: &str, _session: Session) -> Result<()> {
+        Err(Self::read_only())
+    }
+
+    fn modify(
+        &self,
+        _name: &str,
+        _change: &mut dyn FnMut(&mut Session) -> Result<()>,
+    ) -> Result<Session> {
+        Err(Self::read_only())
+    }
+
+    fn list(&self) -> Result<Vec<Session>> {
+        let mut sessions = self.sessions.clone();
+        sessions.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(sessions)
+    }
+
+    fn remove(&self, _name: &str) -> Result<()> {
+        Err(Self::read_only())
+    }
+
+    fn touch_last_connected(&self, _name: &str, _timestamp: i64) -> Result<()> {
+        Ok(())
+    }
+
+    fn record_duration(&self, _name: &str, _secs: u64) -> Result<()> {
+        Ok(())
+    }
+
+    fn log_connection(&self, _event: &ConnectionEvent) -> Result<()> {
+        Ok(())
+    }
+
+    fn connection_log(&self) -> Result<Vec<ConnectionEvent>> {
+        Ok(Vec::new())
+    }
+
+    fn prune_connection_log(&self, _keep: usize) -> Result<usize> {
+        Err(Self::read_only())
+    }
+
+    fn get_config(&self) -> Result<StoreConfig> {
+        Ok(self.config.clone())
+    }
+
+    fn set_config(&self, _config: &StoreConfig) -> Result<()> {
+        Err(Self::read_only())
+    }
+
+    fn repair(&self) -> Result<Vec<String>> {
+        Err(Self::read_only())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_sessions_from_array_content() {
+        let store = EnvSessionStore::from_json(
+            r#"[{"name":"zeta","host":"z.example.com","user":"me","port":22},
+                {"name":"alpha","host":"a.example.com","user":"me","port":22}]"#,
+        )
+        .expect("parse");
+
+        let names: Vec<_> = store
+            .list()
+            .expect("list")
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["alpha".to_string(), "zeta".to_string()]);
+    }
+
+    #[test]
+    fn writes_fail_with_read_only_error() {
+        let store = EnvSessionStore::from_json(r#"{"sessions": []}"#).expect("parse");
+        let err = store.remove("office").unwrap_err().to_string();
+        assert!(err.contains("read-only"));
+        assert!(store.touch_last_connected("office", 1).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_content() {
+        assert!(EnvSessionStore::from_json("not json").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result, anyhow};
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a store change waits for another `se` process to finish its own.
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An exclusive advisory lock on `<store>.lock`, held until dropped.
+///
+/// Closing the file releases the `flock`, so a crashed process never leaves the
+/// store locked; the empty sidecar file itself is left in place.
+pub struct StoreLock {
+    _file: File,
+}
+
+impl StoreLock {
+    /// Lock the store at `store_path`, retrying until `timeout` passes.
+    pub fn acquire(store_path: &Path, timeout: Duration) -> Result<Self> {
+        let path = lock_path(store_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("unable to create store directory {}", parent.display())
+            })?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("unable to open lock file {}", path.display()))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            // SAFETY: `flock` only takes a descriptor and flags; the descriptor comes
+            // from `file`, which stays open for the whole call.
+            let rc = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+            if rc == 0 {
+                return Ok(Self { _file: file });
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::WouldBlock {
+                return Err(err).with_context(|| format!("unable to lock {}", path.display()));
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "store {} is locked by another se process; try again once it finishes",
+                    store_path.display()
+                ));
+            }
+            log::debug!("waiting for lock {}", path.display());
+            thread::sleep(RETRY_INTERVAL);
+        }
+    }
+}
+
+pub fn lock_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn second_lock_times_out_while_the_first_is_held() {
+        let dir = tempdir().expect("tempdir");
+        let store_path = dir.path().join("sessions.json");
+
+        let held = StoreLock::acquire(&store_path, LOCK_TIMEOUT).expect("first lock");
+        let err = StoreLock::acquire(&store_path, Duration::from_millis(120))
+            .err()
+            .expect("second lock should time out");
+        assert!(err.to_string().contains("is locked by another se process"));
+
+        drop(held);
+        StoreLock::acquire(&store_path, Duration::ZERO).expect("lock after release");
+    }
+
+    #[test]
+    fn lock_file_sits_next_to_the_store() {
+        assert_eq!(
+            lock_path(Path::new("/tmp/ssher/sessions.json")),
+            PathBuf::from("/tmp/ssher/sessions.json.lock")
+        );
+    }
+}
@@ -1,20 +1,79 @@
+mod connection_log;
+mod encrypted;
+mod env;
+mod lock;
+mod open;
 mod path;
+mod sqlite;
 
 use crate::model::{PasswdUnsafeMode, Session, SessionStoreData};
 use anyhow::{Context, Result, anyhow};
+use lock::{LOCK_TIMEOUT, StoreLock};
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
-
-pub use path::resolve_store_path;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+pub use connection_log::{ConnectionEvent, ConnectionKind, LogLimits};
+pub use encrypted::{EncryptedFileStore, PASSPHRASE_ENV, is_encrypted};
+pub use env::{EnvSessionStore, SESSIONS_ENV};
+pub use open::StoreOpener;
+pub use path::{
+    DEFAULT_PROFILE, STORE_FORMAT_ENV, StoreFormat, default_store_file, list_profiles,
+    resolve_store_path,
+};
+pub use sqlite::{SqliteStore, is_sqlite_path};
 
 pub trait SessionStore {
     fn add(&self, session: Session) -> Result<()>;
     fn update(&self, session: Session) -> Result<()>;
+    /// Replace the session called `old_name` with `session`, whose name may differ.
+    fn rename(&self, old_name: &str, session: Session) -> Result<()>;
+    /// Like [`rename`](Self::rename), but a session already called `session.name` is
+    /// dropped in the same write instead of being an error.
+    fn rename_replacing(&self, old_name: &str, session: Session) -> Result<()>;
+    /// Apply `change` to the session called `name` and save the result in one locked
+    /// write, so checks `change` makes still hold when it is saved. `change` keeps the
+    /// name and must not call back into the store. Returns the saved session.
+    fn modify(
+        &self,
+        name: &str,
+        change: &mut dyn FnMut(&mut Session) -> Result<()>,
+    ) -> Result<Session>;
     fn list(&self) -> Result<Vec<Session>>;
     fn remove(&self, name: &str) -> Result<()>;
     fn touch_last_connected(&self, name: &str, timestamp: i64) -> Result<()>;
+    fn record_duration(&self, name: &str, secs: u64) -> Result<()>;
+    /// Append to the connection log; a no-op unless `log_connections` is on.
+    fn log_connection(&self, event: &ConnectionEvent) -> Result<()>;
+    /// Every logged event across rotated files, oldest first.
+    fn connection_log(&self) -> Result<Vec<ConnectionEvent>>;
+    /// Trim the connection log to its newest `keep` events; returns how many were dropped.
+    fn prune_connection_log(&self, keep: usize) -> Result<usize>;
     fn get_config(&self) -> Result<StoreConfig>;
     fn set_config(&self, config: &StoreConfig) -> Result<()>;
+    fn repair(&self) -> Result<Vec<String>>;
+
+    /// Store a finished connection: `last_connected_at`, the duration stats and the
+    /// connection log. Callers warn rather than fail on an error, since the shell
+    /// or transfer itself already went through.
+    fn record_connection(
+        &self,
+        name: &str,
+        kind: ConnectionKind,
+        timestamp: i64,
+        duration_secs: u64,
+    ) -> Result<()> {
+        self.touch_last_connected(name, timestamp)?;
+        self.record_duration(name, duration_secs)?;
+        self.log_connection(&ConnectionEvent {
+            session: name.to_string(),
+            timestamp,
+            kind,
+            duration_secs,
+        })
+    }
 }
 
 /// Configuration values that can be read/modified at the store level
@@ -22,6 +81,9 @@ pub trait SessionStore {
 pub struct StoreConfig {
     pub passwd_unsafe_mode: PasswdUnsafeMode,
     pub passwd_unsafe_key: Option<String>,
+    pub log_connections: bool,
+    pub log_max_entries: Option<u64>,
+    pub log_max_bytes: Option<u64>,
 }
 
 impl Default for StoreConfig {
@@ -29,20 +91,29 @@ impl Default for StoreConfig {
         Self {
             passwd_unsafe_mode: PasswdUnsafeMode::Normal,
             passwd_unsafe_key: None,
+            log_connections: false,
+            log_max_entries: None,
+            log_max_bytes: None,
         }
     }
 }
 
 pub struct JsonFileStore {
     path: PathBuf,
+    /// Set by `EncryptedFileStore`; the file is then written encrypted
+    passphrase: Option<String>,
 }
 
 impl JsonFileStore {
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        Self {
+            path,
+            passphrase: None,
+        }
     }
 
     pub fn add(&self, session: Session) -> Result<()> {
+        let _lock = self.lock()?;
         let mut data = self.load_full()?;
         if data
             .sessions
@@ -56,6 +127,7 @@ impl JsonFileStore {
     }
 
     pub fn update(&self, session: Session) -> Result<()> {
+        let _lock = self.lock()?;
         let mut data = self.load_full()?;
         if let Some(existing) = data.sessions.iter_mut().find(|s| s.name == session.name) {
             *existing = session;
@@ -65,6 +137,52 @@ impl JsonFileStore {
         }
     }
 
+    pub fn rename(&self, old_name: &str, session: Session) -> Result<()> {
+        self.rename_over(old_name, session, false)
+    }
+
+    pub fn rename_replacing(&self, old_name: &str, session: Session) -> Result<()> {
+        self.rename_over(old_name, session, true)
+    }
+
+    fn rename_over(&self, old_name: &str, session: Session, replace: bool) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut data = self.load_full()?;
+        let Some(index) = data.sessions.iter().position(|s| s.name == old_name) else {
+            return Err(anyhow!("session '{}' not found", old_name));
+        };
+        let taken = data
+            .sessions
+            .iter()
+            .position(|s| s.name == session.name && s.name != old_name);
+        if taken.is_some() && !replace {
+            return Err(anyhow!("session '{}' already exists", session.name));
+        }
+        data.sessions[index] = session;
+        if let Some(taken) = taken {
+            data.sessions.remove(taken);
+        }
+        self.save(&data)
+    }
+
+    pub fn modify(
+        &self,
+        name: &str,
+        change: &mut dyn FnMut(&mut Session) -> Result<()>,
+    ) -> Result<Session> {
+        let _lock = self.lock()?;
+        let mut data = self.load_full()?;
+        let session = data
+            .sessions
+            .iter_mut()
+            .find(|session| session.name == name)
+            .ok_or_else(|| anyhow!("session '{}' not found", name))?;
+        change(session)?;
+        let session = session.clone();
+        self.save(&data)?;
+        Ok(session)
+    }
+
     pub fn list(&self) -> Result<Vec<Session>> {
         let mut data = self.load_full()?;
         data.sessions.sort_by(|a, b| a.name.cmp(&b.name));
@@ -72,6 +190,7 @@ impl JsonFileStore {
     }
 
     pub fn remove(&self, name: &str) -> Result<()> {
+        let _lock = self.lock()?;
         let mut data = self.load_full()?;
         let before = data.sessions.len();
         data.sessions.retain(|session| session.name != name);
@@ -82,11 +201,13 @@ impl JsonFileStore {
     }
 
     pub fn touch_last_connected(&self, name: &str, timestamp: i64) -> Result<()> {
+        let _lock = self.lock()?;
         let mut data = self.load_full()?;
         let mut found = false;
         for session in &mut data.sessions {
             if session.name == name {
                 session.last_connected_at = Some(timestamp);
+                session.connect_count = session.connect_count.saturating_add(1);
                 found = true;
                 break;
             }
@@ -97,34 +218,75 @@ impl JsonFileStore {
         self.save(&data)
     }
 
+    pub fn record_duration(&self, name: &str, secs: u64) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut data = self.load_full()?;
+        let session = data
+            .sessions
+            .iter_mut()
+            .find(|session| session.name == name)
+            .ok_or_else(|| anyhow!("session '{}' not found", name))?;
+        session.record_duration(secs);
+        self.save(&data)
+    }
+
+    /// Drop sessions whose name repeats an earlier entry, keeping the first
+    /// occurrence. Returns the names of the dropped duplicates.
+    pub fn repair(&self) -> Result<Vec<String>> {
+        let _lock = self.lock()?;
+        let mut data = self.load_raw()?;
+        let mut seen = HashSet::new();
+        let mut removed = Vec::new();
+        data.sessions.retain(|session| {
+            if seen.insert(session.name.clone()) {
+                true
+            } else {
+                removed.push(session.name.clone());
+                false
+            }
+        });
+        if !removed.is_empty() {
+            self.save(&data)?;
+        }
+        Ok(removed)
+    }
+
+    /// Hold the store's lock for the rest of a load-modify-save cycle, so concurrent
+    /// `se` processes cannot overwrite each other's changes.
+    fn lock(&self) -> Result<StoreLock> {
+        StoreLock::acquire(&self.path, LOCK_TIMEOUT)
+    }
+
     /// Load the full store data including config
     fn load_full(&self) -> Result<SessionStoreData> {
+        let data = self.load_raw()?;
+        ensure_unique_names(&data, &self.path.display().to_string())?;
+        Ok(data)
+    }
+
+    /// Load the store data without integrity checks
+    fn load_raw(&self) -> Result<SessionStoreData> {
         if !self.path.exists() {
             return Ok(SessionStoreData::default());
         }
-        let data = fs::read_to_string(&self.path)
+        log::debug!("reading store {}", self.path.display());
+        let mut data = fs::read(&self.path)
             .with_context(|| format!("unable to read store {}", self.path.display()))?;
-        if data.trim().is_empty() {
-            return Ok(SessionStoreData::default());
-        }
-
-        // Try to parse as new format first
-        let parsed: serde_json::Value = serde_json::from_str(&data)
-            .with_context(|| format!("unable to parse store {}", self.path.display()))?;
-
-        // Check if it's the new format (has "sessions" key at root)
-        if let Some(obj) = parsed.as_object()
-            && obj.contains_key("sessions")
-        {
-            let store_data: SessionStoreData = serde_json::from_value(parsed)
-                .with_context(|| format!("unable to parse store {}", self.path.display()))?;
-            return Ok(store_data);
+        if data.starts_with(encrypted::MAGIC) {
+            let passphrase = self.passphrase.as_deref().ok_or_else(|| {
+                anyhow!(
+                    "store {} is encrypted; pass --encrypted or set {}",
+                    self.path.display(),
+                    PASSPHRASE_ENV
+                )
+            })?;
+            data = encrypted::decrypt(passphrase, &data)
+                .with_context(|| format!("unable to decrypt store {}", self.path.display()))?;
         }
-
-        // Old format: root is an array of sessions - migrate it
-        let sessions: Vec<Session> = serde_json::from_str(&data)
+        let data = String::from_utf8(data)
             .with_context(|| format!("unable to parse store {}", self.path.display()))?;
-        Ok(SessionStoreData::from_sessions(sessions))
+        parse_store_data(&data)
+            .with_context(|| format!("unable to parse store {}", self.path.display()))
     }
 
     fn save(&self, data: &SessionStoreData) -> Result<()> {
@@ -133,10 +295,42 @@ impl JsonFileStore {
                 format!("unable to create store directory {}", parent.display())
             })?;
         }
+        log::debug!(
+            "writing {} session(s) to {}",
+            data.sessions.len(),
+            self.path.display()
+        );
         let json = serde_json::to_string_pretty(data).context("unable to serialize sessions")?;
-        fs::write(&self.path, json)
-            .with_context(|| format!("unable to write store {}", self.path.display()))?;
-        Ok(())
+        let content = match &self.passphrase {
+            Some(passphrase) => encrypted::encrypt(passphrase, json.as_bytes())?,
+            None => json.into_bytes(),
+        };
+        write_atomic(&self.path, &content)
+            .with_context(|| format!("unable to write store {}", self.path.display()))
+    }
+
+    pub fn log_connection(&self, event: &ConnectionEvent) -> Result<()> {
+        let data = self.load_raw()?;
+        if !data.log_connections {
+            return Ok(());
+        }
+        let limits = LogLimits {
+            max_entries: data.log_max_entries,
+            max_bytes: data.log_max_bytes,
+        };
+        connection_log::append_event(
+            &connection_log::connection_log_path(&self.path),
+            event,
+            limits,
+        )
+    }
+
+    pub fn connection_log(&self) -> Result<Vec<ConnectionEvent>> {
+        connection_log::read_events(&connection_log::connection_log_path(&self.path))
+    }
+
+    pub fn prune_connection_log(&self, keep: usize) -> Result<usize> {
+        connection_log::prune(&connection_log::connection_log_path(&self.path), keep)
     }
 
     /// Get the current config
@@ -145,18 +339,61 @@ impl JsonFileStore {
         Ok(StoreConfig {
             passwd_unsafe_mode: data.passwd_unsafe_mode,
             passwd_unsafe_key: data.passwd_unsafe_key,
+            log_connections: data.log_connections,
+            log_max_entries: data.log_max_entries,
+            log_max_bytes: data.log_max_bytes,
         })
     }
 
     /// Update the config
     pub fn set_config(&self, config: &StoreConfig) -> Result<()> {
+        let _lock = self.lock()?;
         let mut data = self.load_full()?;
         data.passwd_unsafe_mode = config.passwd_unsafe_mode.clone();
         data.passwd_unsafe_key = config.passwd_unsafe_key.clone();
+        data.log_connections = config.log_connections;
+        data.log_max_entries = config.log_max_entries;
+        data.log_max_bytes = config.log_max_bytes;
         self.save(&data)
     }
 }
 
+/// Parse store content in either the current `{"sessions": [...]}` envelope
+/// or the legacy bare-array format.
+fn parse_store_data(data: &str) -> Result<SessionStoreData> {
+    if data.trim().is_empty() {
+        return Ok(SessionStoreData::default());
+    }
+
+    // Try to parse as new format first
+    let parsed: serde_json::Value = serde_json::from_str(data)?;
+
+    // Check if it's the new format (has "sessions" key at root)
+    if let Some(obj) = parsed.as_object()
+        && obj.contains_key("sessions")
+    {
+        return Ok(serde_json::from_value(parsed)?);
+    }
+
+    // Old format: root is an array of sessions - migrate it
+    let sessions: Vec<Session> = serde_json::from_value(parsed)?;
+    Ok(SessionStoreData::from_sessions(sessions))
+}
+
+fn ensure_unique_names(data: &SessionStoreData, source: &str) -> Result<()> {
+    let mut seen = HashSet::new();
+    for session in &data.sessions {
+        if !seen.insert(session.name.as_str()) {
+            return Err(anyhow!(
+                "duplicate session '{}' in store {}; run `se store repair` to fix it",
+                session.name,
+                source
+            ));
+        }
+    }
+    Ok(())
+}
+
 impl SessionStore for JsonFileStore {
     fn add(&self, session: Session) -> Result<()> {
         JsonFileStore::add(self, session)
@@ -166,6 +403,22 @@ impl SessionStore for JsonFileStore {
         JsonFileStore::update(self, session)
     }
 
+    fn rename(&self, old_name: &str, session: Session) -> Result<()> {
+        JsonFileStore::rename(self, old_name, session)
+    }
+
+    fn rename_replacing(&self, old_name: &str, session: Session) -> Result<()> {
+        JsonFileStore::rename_replacing(self, old_name, session)
+    }
+
+    fn modify(
+        &self,
+        name: &str,
+        change: &mut dyn FnMut(&mut Session) -> Result<()>,
+    ) -> Result<Session> {
+        JsonFileStore::modify(self, name, change)
+    }
+
     fn list(&self) -> Result<Vec<Session>> {
         JsonFileStore::list(self)
     }
@@ -178,6 +431,22 @@ impl SessionStore for JsonFileStore {
         JsonFileStore::touch_last_connected(self, name, timestamp)
     }
 
+    fn record_duration(&self, name: &str, secs: u64) -> Result<()> {
+        JsonFileStore::record_duration(self, name, secs)
+    }
+
+    fn log_connection(&self, event: &ConnectionEvent) -> Result<()> {
+        JsonFileStore::log_connection(self, event)
+    }
+
+    fn connection_log(&self) -> Result<Vec<ConnectionEvent>> {
+        JsonFileStore::connection_log(self)
+    }
+
+    fn prune_connection_log(&self, keep: usize) -> Result<usize> {
+        JsonFileStore::prune_connection_log(self, keep)
+    }
+
     fn get_config(&self) -> Result<StoreConfig> {
         JsonFileStore::get_config(self)
     }
@@ -185,28 +454,61 @@ impl SessionStore for JsonFileStore {
     fn set_config(&self, config: &StoreConfig) -> Result<()> {
         JsonFileStore::set_config(self, config)
     }
+
+    fn repair(&self) -> Result<Vec<String>> {
+        JsonFileStore::repair(self)
+    }
+}
+
+/// Temporary file `write_atomic` fills before renaming it over `path`.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp-{}", name, std::process::id()))
+}
+
+/// Replace `path` with `content` so readers see either the old file or the new one,
+/// never a truncated mix: write a sibling temp file, flush it to disk, then rename it
+/// over `path`. The existing file's permissions are kept, and a new file is readable
+/// by its owner only, since it may hold passwords. A symlinked store is written
+/// through to the file it points at, so the link stays.
+fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let temp = temp_path(&target);
+    let result = (|| {
+        // A leftover temp file would keep its old mode, so never reuse one.
+        let _ = fs::remove_file(&temp);
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&temp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(&target) {
+            fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        fs::rename(&temp, &target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::JsonFileStore;
-    use super::StoreConfig;
+    use super::{ConnectionEvent, ConnectionKind};
+    use super::{StoreConfig, temp_path};
     use crate::model::{PasswdUnsafeMode, Session};
+    use anyhow::anyhow;
     use tempfile::tempdir;
 
     fn sample_session(name: &str) -> Session {
-        Session {
-            name: name.to_string(),
-            host: "example.com".to_string(),
-            user: "me".to_string(),
-            port: 22,
-            identity_file: None,
-            tags: Vec::new(),
-            last_connected_at: None,
-            has_stored_password: false,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        }
+        Session::builder(name, "example.com", "me").build()
     }
 
     #[test]
@@ -252,6 +554,21 @@ mod tests {
         assert_eq!(list[0].last_connected_at, Some(1234));
     }
 
+    #[test]
+    fn touch_last_connected_counts_connections() {
+        let dir = tempdir().expect("tempdir");
+        let store_path = dir.path().join("sessions.json");
+        let store = JsonFileStore::new(store_path);
+
+        store.add(sample_session("office")).expect("add");
+        store.touch_last_connected("office", 1234).expect("touch");
+        store.touch_last_connected("office", 1300).expect("touch");
+
+        let list = store.list().expect("list");
+        assert_eq!(list[0].last_connected_at, Some(1300));
+        assert_eq!(list[0].connect_count, 2);
+    }
+
     #[test]
     fn load_returns_empty_for_nonexistent_file() {
         let dir = tempdir().expect("tempdir");
@@ -322,6 +639,20 @@ mod tests {
         assert!(err.contains("not found"));
     }
 
+    #[test]
+    fn record_duration_updates_last_and_average() {
+        let dir = tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        store.add(sample_session("office")).expect("add");
+
+        store.record_duration("office", 60).expect("record");
+        store.record_duration("office", 20).expect("record");
+        let office = &store.list().expect("list")[0];
+        assert_eq!(office.last_duration_secs, Some(20));
+        assert_eq!(office.avg_duration_secs, Some(50));
+        assert!(store.record_duration("missing", 1).is_err());
+    }
+
     #[test]
     fn touch_last_connected_nonexistent_fails() {
         let dir = tempdir().expect("tempdir");
@@ -345,6 +676,74 @@ mod tests {
         assert!(store_path.parent().unwrap().exists());
     }
 
+    #[test]
+    fn failed_save_keeps_the_previous_store() {
+        let dir = tempdir().expect("tempdir");
+        let store_path = dir.path().join("sessions.json");
+        let store = JsonFileStore::new(store_path.clone());
+        store.add(sample_session("office")).expect("add");
+        let before = std::fs::read_to_string(&store_path).expect("read");
+
+        // A directory where the temp file goes makes the write fail midway.
+        let temp = temp_path(&store_path);
+        std::fs::create_dir(&temp).expect("block temp file");
+        assert!(store.add(sample_session("lab")).is_err());
+
+        assert_eq!(std::fs::read_to_string(&store_path).expect("read"), before);
+        std::fs::remove_dir(&temp).expect("unblock temp file");
+        let names: Vec<_> = store
+            .list()
+            .expect("list")
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["office"]);
+    }
+
+    #[test]
+    fn new_stores_are_private_and_symlinks_survive_saves() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().expect("tempdir");
+        let real = dir.path().join("real.json");
+        let store = JsonFileStore::new(real.clone());
+        store.add(sample_session("office")).expect("add");
+        let mode = std::fs::metadata(&real)
+            .expect("metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let link = dir.path().join("sessions.json");
+        std::os::unix::fs::symlink(&real, &link).expect("symlink");
+        JsonFileStore::new(link.clone())
+            .add(sample_session("lab"))
+            .expect("add through link");
+        assert!(
+            std::fs::symlink_metadata(&link)
+                .expect("link metadata")
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(store.list().expect("list").len(), 2);
+    }
+
+    #[test]
+    fn save_leaves_no_temp_file_behind() {
+        let dir = tempdir().expect("tempdir");
+        let store_path = dir.path().join("sessions.json");
+        let store = JsonFileStore::new(store_path.clone());
+        store.add(sample_session("office")).expect("add");
+        store.add(sample_session("lab")).expect("add");
+
+        let mut entries: Vec<_> = std::fs::read_dir(dir.path())
+            .expect("read_dir")
+            .map(|entry| entry.expect("entry").file_name())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, ["sessions.json", "sessions.json.lock"]);
+    }
+
     #[test]
     fn update_existing_session() {
         let dir = tempdir().expect("tempdir");
@@ -376,6 +775,85 @@ mod tests {
         assert!(err.contains("not found"));
     }
 
+    #[test]
+    fn rename_replaces_session_under_new_name() {
+        let dir = tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        let mut office = sample_session("office");
+        office.last_connected_at = Some(42);
+        store.add(office.clone()).expect("add");
+        store.add(sample_session("home")).expect("add");
+
+        office.name = "work".to_string();
+        store.rename("office", office.clone()).expect("rename");
+        let list = store.list().expect("list");
+        let names: Vec<&str> = list.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["home", "work"]);
+        assert_eq!(list[1].last_connected_at, Some(42));
+
+        office.name = "home".to_string();
+        let err = store.rename("work", office).unwrap_err().to_string();
+        assert!(err.contains("already exists"), "{err}");
+        let err = store
+            .rename("office", sample_session("office"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("not found"), "{err}");
+    }
+
+    #[test]
+    fn modify_saves_the_change_unless_it_fails() {
+        let dir = tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        store.add(sample_session("office")).expect("add");
+
+        let saved = store
+            .modify("office", &mut |session| {
+                session.tags.push("work".to_string());
+                Ok(())
+            })
+            .expect("modify");
+        assert_eq!(saved.tags, ["work"]);
+        assert_eq!(store.list().expect("list")[0].tags, ["work"]);
+
+        let err = store
+            .modify("office", &mut |session| {
+                session.tags.clear();
+                Err(anyhow!("refused"))
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "refused");
+        assert_eq!(store.list().expect("list")[0].tags, ["work"]);
+        assert!(store.modify("nonexistent", &mut |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn rename_replacing_drops_the_target_in_the_same_write() {
+        let dir = tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        store.add(sample_session("office")).expect("add");
+        store.add(sample_session("home")).expect("add");
+
+        let mut office = sample_session("office");
+        office.name = "home".to_string();
+        office.host = "office.example.com".to_string();
+        store
+            .rename_replacing("office", office)
+            .expect("rename replacing");
+        let list = store.list().expect("list");
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].name, "home");
+        assert_eq!(list[0].host, "office.example.com");
+
+        // A missing source changes nothing, so the target survives.
+        let err = store
+            .rename_replacing("office", sample_session("home"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("not found"), "{err}");
+        assert_eq!(store.list().expect("list")[0].host, "office.example.com");
+    }
+
     #[test]
     fn update_preserves_other_sessions() {
         let dir = tempdir().expect("tempdir");
@@ -417,6 +895,9 @@ mod tests {
         let config = StoreConfig {
             passwd_unsafe_mode: PasswdUnsafeMode::Bare,
             passwd_unsafe_key: Some("my-key".to_string()),
+            log_connections: false,
+            log_max_entries: None,
+            log_max_bytes: None,
         };
         store.set_config(&config).expect("set_config");
 
@@ -455,6 +936,9 @@ mod tests {
         let config = StoreConfig {
             passwd_unsafe_mode: PasswdUnsafeMode::Simple,
             passwd_unsafe_key: Some("secret".to_string()),
+            log_connections: false,
+            log_max_entries: None,
+            log_max_bytes: None,
         };
         store.set_config(&config).expect("set_config");
 
@@ -479,6 +963,9 @@ mod tests {
         let config = StoreConfig {
             passwd_unsafe_mode: PasswdUnsafeMode::Bare,
             passwd_unsafe_key: None,
+            log_connections: false,
+            log_max_entries: None,
+            log_max_bytes: None,
         };
         store.set_config(&config).expect("set_config");
 
@@ -489,4 +976,66 @@ mod tests {
         let loaded = store.get_config().expect("get_config");
         assert_eq!(loaded.passwd_unsafe_mode, PasswdUnsafeMode::Bare);
     }
+
+    #[test]
+    fn connection_log_is_written_only_when_enabled() {
+        let dir = tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        store.add(sample_session("office")).expect("add");
+        let event = ConnectionEvent {
+            session: "office".to_string(),
+            timestamp: 100,
+            kind: ConnectionKind::Ssh,
+            duration_secs: 5,
+        };
+
+        store.log_connection(&event).expect("log while disabled");
+        assert!(!dir.path().join("sessions.connections.jsonl").exists());
+
+        let config = StoreConfig {
+            log_connections: true,
+            ..StoreConfig::default()
+        };
+        store.set_config(&config).expect("set_config");
+        store.log_connection(&event).expect("log while enabled");
+        assert_eq!(store.connection_log().expect("read log"), vec![event]);
+    }
+
+    #[test]
+    fn load_fails_for_duplicate_names() {
+        let dir = tempdir().expect("tempdir");
+        let store_path = dir.path().join("sessions.json");
+        let duplicated = r#"[
+            {"name":"office","host":"a.example.com","user":"me","port":22},
+            {"name":"office","host":"b.example.com","user":"me","port":22}
+        ]"#;
+        std::fs::write(&store_path, duplicated).expect("write");
+        let store = JsonFileStore::new(store_path);
+
+        let err = store.list().unwrap_err().to_string();
+        assert!(err.contains("duplicate session 'office'"));
+    }
+
+    #[test]
+    fn repair_keeps_first_duplicate() {
+        let dir = tempdir().expect("tempdir");
+        let store_path = dir.path().join("sessions.json");
+        let duplicated = r#"[
+            {"name":"office","host":"a.example.com","user":"me","port":22},
+            {"name":"home","host":"home.example.com","user":"me","port":22},
+            {"name":"office","host":"b.example.com","user":"me","port":22}
+        ]"#;
+        std::fs::write(&store_path, duplicated).expect("write");
+        let store = JsonFileStore::new(store_path);
+
+        let removed = store.repair().expect("repair");
+        assert_eq!(removed, vec!["office".to_string()]);
+
+        let list = store.list().expect("list");
+        assert_eq!(list.len(), 2);
+        let office = list.iter().find(|s| s.name == "office").expect("office");
+        assert_eq!(office.host, "a.example.com");
+
+        assert!(store.repair().expect("repair").is_empty());
+    }
 }
@@ -0,0 +1,274 @@
+use super::{
+    EncryptedFileStore, JsonFileStore, PASSPHRASE_ENV, SessionStore, SqliteStore, StoreFormat,
+    encrypted, is_encrypted,
+};
+use anyhow::{Context, Result, anyhow};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Wrong passphrases accepted at the prompt before giving up.
+const PASSPHRASE_ATTEMPTS: usize = 3;
+
+/// Picks the backend for a store file. The CLI builds one from its flags and hands
+/// it to the TUI, so stores opened from the profile picker match `--profile`.
+#[derive(Clone, Default)]
+pub struct StoreOpener {
+    /// `--store-format`: the backend to use whatever the file's extension
+    pub format: Option<StoreFormat>,
+    /// `--encrypted`: encrypt a JSON store that is still plaintext
+    pub encrypted: bool,
+    /// Ask on the terminal for a passphrase that is neither in `SSHER_PASSPHRASE` nor
+    /// entered earlier; off while the TUI owns the terminal
+    pub prompt: bool,
+    /// Passphrases that unlocked a store in this run, by store path. Shared between
+    /// clones, so the TUI reuses what the CLI asked for.
+    passphrases: Arc<Mutex<HashMap<PathBuf, String>>>,
+}
+
+impl fmt::Debug for StoreOpener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreOpener")
+            .field("format", &self.format)
+            .field("encrypted", &self.encrypted)
+            .field("prompt", &self.prompt)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StoreOpener {
+    /// The opener for stores picked in the TUI: it cannot prompt, and it leaves a
+    /// plaintext profile plaintext rather than encrypting it behind the user's back.
+    /// Encrypted profiles open with `SSHER_PASSPHRASE` or a passphrase entered
+    /// earlier that unlocks them.
+    pub fn for_tui(&self) -> Self {
+        Self {
+            encrypted: false,
+            prompt: false,
+            ..self.clone()
+        }
+    }
+
+    /// The store file at `path` in `format`, or else the one its extension implies:
+    /// a SQLite database for `.db`/`.sqlite` paths, otherwise JSON, encrypted when
+    /// `encrypted` is set or the file already is.
+    pub fn open(&self, path: PathBuf) -> Result<Box<dyn SessionStore>> {
+        let format = self.format.unwrap_or_else(|| StoreFormat::of_path(&path));
+        if format == StoreFormat::Sqlite {
+            if self.encrypted {
+                return Err(anyhow!("--encrypted supports only JSON stores"));
+            }
+            log::debug!("using SQLite store {}", path.display());
+            return Ok(Box::new(SqliteStore::new(path)));
+        }
+        if self.encrypted || is_encrypted(&path) {
+            log::debug!("using encrypted store {}", path.display());
+            let passphrase = self.passphrase(&path)?;
+            return Ok(Box::new(EncryptedFileStore::new(path, passphrase)));
+        }
+        log::debug!("using store {}", path.display());
+        Ok(Box::new(JsonFileStore::new(path)))
+    }
+
+    /// Passphrase for the encrypted store at `path`: `SSHER_PASSPHRASE`, one entered
+    /// earlier in this run that unlocks it, or a terminal prompt. The prompt asks
+    /// again after a wrong passphrase, and twice when the store is about to be
+    /// encrypted for the first time.
+    fn passphrase(&self, path: &Path) -> Result<String> {
+        if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+            return Ok(passphrase);
+        }
+        let mut known = self
+            .passphrases
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(passphrase) = known.get(path) {
+            return Ok(passphrase.clone());
+        }
+        let exists = is_encrypted(path);
+        // Profiles often share a passphrase; only one that actually decrypts counts.
+        if exists
+            && let Some(passphrase) = known
+                .values()
+                .find(|passphrase| encrypted::unlocks(path, passphrase))
+                .cloned()
+        {
+            known.insert(path.to_path_buf(), passphrase.clone());
+            return Ok(passphrase);
+        }
+        if !self.prompt || !io::stdin().is_terminal() {
+            return Err(anyhow!(
+                "set {} to the passphrase of encrypted store {}",
+                PASSPHRASE_ENV,
+                path.display()
+            ));
+        }
+        let passphrase = if exists {
+            prompt_existing_passphrase(path)?
+        } else {
+            prompt_new_passphrase(path)?
+        };
+        known.insert(path.to_path_buf(), passphrase.clone());
+        Ok(passphrase)
+    }
+}
+
+fn prompt_existing_passphrase(path: &Path) -> Result<String> {
+    for attempt in 1..=PASSPHRASE_ATTEMPTS {
+        let passphrase = read_passphrase(&format!("Passphrase for {}: ", path.display()))?;
+        if encrypted::unlocks(path, &passphrase) {
+            return Ok(passphrase);
+        }
+        if attempt < PASSPHRASE_ATTEMPTS {
+            eprintln!("Wrong passphrase, try again.");
+        }
+    }
+    Err(anyhow!(
+        "wrong passphrase for encrypted store {}",
+        path.display()
+    ))
+}
+
+fn prompt_new_passphrase(path: &Path) -> Result<String> {
+    let passphrase = read_passphrase(&format!("Passphrase for {}: ", path.display()))?;
+    if passphrase.is_empty() {
+        return Err(anyhow!("the store passphrase cannot be empty"));
+    }
+    if read_passphrase("Repeat passphrase: ")? != passphrase {
+        return Err(anyhow!("passphrases do not match"));
+    }
+    Ok(passphrase)
+}
+
+fn read_passphrase(prompt: &str) -> Result<String> {
+    rpassword::prompt_password(prompt).context("failed to read passphrase from terminal")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Session;
+    use tempfile::tempdir;
+
+    #[test]
+    fn picks_the_backend_from_the_path() {
+        let dir = tempdir().expect("tempdir");
+        let opener = StoreOpener::default();
+        for name in ["sessions.json", "sessions.db"] {
+            let path = dir.path().join(name);
+            opener
+                .open(path.clone())
+                .expect("open")
+                .add(Session::builder("web", "example.com", "me").build())
+                .expect("add");
+            let raw = std::fs::read(&path).expect("read");
+            assert_eq!(raw.starts_with(b"SQLite format 3"), name.ends_with(".db"));
+            assert_eq!(
+                opener
+                    .open(path)
+                    .expect("reopen")
+                    .list()
+                    .expect("list")
+                    .len(),
+                1
+            );
+        }
+
+        let encrypted = StoreOpener {
+            encrypted: true,
+            ..StoreOpener::default()
+        };
+        assert!(encrypted.open(dir.path().join("sessions.db")).is_err());
+
+        let forced = StoreOpener {
+            format: Some(StoreFormat::Sqlite),
+            ..StoreOpener::default()
+        };
+        let path = dir.path().join("forced.json");
+        forced
+            .open(path.clone())
+            .expect("open")
+            .add(Session::builder("web", "example.com", "me").build())
+            .expect("add");
+        assert!(
+            std::fs::read(&path)
+                .expect("read")
+                .starts_with(b"SQLite format 3")
+        );
+    }
+
+    #[test]
+    fn encrypted_stores_without_a_prompt_need_a_known_passphrase() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.json");
+        EncryptedFileStore::new(path.clone(), "pw".to_string())
+            .add(Session::builder("web", "example.com", "me").build())
+            .expect("add");
+
+        let opener = StoreOpener::default();
+        temp_env::with_var(PASSPHRASE_ENV, None::<&str>, || {
+            let err = opener.open(path.clone()).err().expect("needs a passphrase");
+            assert!(format!("{:#}", err).contains(PASSPHRASE_ENV));
+        });
+        temp_env::with_var(PASSPHRASE_ENV, Some("pw"), || {
+            let store = opener.open(path.clone()).expect("open");
+            assert_eq!(store.list().expect("list").len(), 1);
+        });
+    }
+
+    #[test]
+    fn remembered_passphrases_are_reused_only_where_they_unlock() {
+        let dir = tempdir().expect("tempdir");
+        let work = dir.path().join("work.json");
+        let home = dir.path().join("home.json");
+        let same = dir.path().join("same.json");
+        for (path, passphrase) in [(&work, "pw"), (&home, "other"), (&same, "pw")] {
+            EncryptedFileStore::new(path.clone(), passphrase.to_string())
+                .add(Session::builder("web", "example.com", "me").build())
+                .expect("add");
+        }
+
+        let opener = StoreOpener::default();
+        opener
+            .passphrases
+            .lock()
+            .unwrap()
+            .insert(work.clone(), "pw".to_string());
+        let tui = opener.for_tui();
+        temp_env::with_var(PASSPHRASE_ENV, None::<&str>, || {
+            assert_eq!(
+                tui.open(work.clone()).expect("open").list().unwrap().len(),
+                1
+            );
+            assert_eq!(
+                tui.open(same.clone()).expect("open").list().unwrap().len(),
+                1
+            );
+            let err = tui.open(home.clone()).err().expect("other passphrase");
+            assert!(format!("{:#}", err).contains(PASSPHRASE_ENV));
+        });
+        assert!(opener.passphrases.lock().unwrap().contains_key(&same));
+    }
+
+    #[test]
+    fn the_tui_leaves_plaintext_profiles_plaintext() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("work.json");
+        let opener = StoreOpener {
+            encrypted: true,
+            prompt: true,
+            ..StoreOpener::default()
+        };
+        temp_env::with_var(PASSPHRASE_ENV, Some("pw"), || {
+            opener
+                .for_tui()
+                .open(path.clone())
+                .expect("open")
+                .add(Session::builder("web", "example.com", "me").build())
+                .expect("add");
+        });
+        assert!(!is_encrypted(&path));
+    }
+}
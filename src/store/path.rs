@@ -1,15 +1,131 @@
+use crate::dirs::resolve_config_dir;
 use anyhow::{Result, anyhow};
-use directories::ProjectDirs;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
-pub fn resolve_store_path(override_path: Option<PathBuf>) -> Result<PathBuf> {
-    if let Some(path) = override_path {
-        return Ok(path);
+/// Environment variable naming the format of a newly created default store.
+pub const STORE_FORMAT_ENV: &str = "SSHER_STORE_FORMAT";
+
+/// Name of the profile that maps to the plain store file.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// File extensions a store can have: JSON, or SQLite (`db`/`sqlite`).
+const STORE_EXTENSIONS: [&str; 3] = ["json", "db", "sqlite"];
+
+/// Backend of a store file. Normally it follows the file extension; `--store-format`
+/// forces one.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreFormat {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl StoreFormat {
+    /// The format a store file's extension implies: SQLite for `.db`/`.sqlite`.
+    pub fn of_path(path: &Path) -> Self {
+        if super::is_sqlite_path(path) {
+            Self::Sqlite
+        } else {
+            Self::Json
+        }
+    }
+
+    /// Name of the default store file in this format.
+    pub fn default_file_name(self) -> &'static str {
+        match self {
+            Self::Json => "sessions.json",
+            Self::Sqlite => "sessions.db",
+        }
+    }
+}
+
+/// The default store in `dir`: an existing `sessions.json`, `.db` or `.sqlite` keeps
+/// its format (`format`'s file first if several exist), and a fresh install gets
+/// `format`'s file.
+pub fn default_store_file(dir: &Path, format: StoreFormat) -> PathBuf {
+    let preferred = dir.join(format.default_file_name());
+    std::iter::once(preferred.clone())
+        .chain(
+            STORE_EXTENSIONS
+                .into_iter()
+                .map(|ext| dir.join(format!("sessions.{}", ext))),
+        )
+        .find(|path| path.exists())
+        .unwrap_or(preferred)
+}
+
+fn store_extension(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?;
+    STORE_EXTENSIONS
+        .into_iter()
+        .find(|known| known.eq_ignore_ascii_case(ext))
+}
+
+/// Resolve the session store file.
+///
+/// Without a profile this is the override path or `~/.config/ssher/sessions.json`
+/// (`sessions.db` when only that exists).
+/// A named profile lives in `profiles/<name>.<ext>` next to that file: an existing
+/// profile keeps its extension, and a new one takes the base store's, so a SQLite
+/// base store gets SQLite profiles.
+pub fn resolve_store_path(
+    override_path: Option<PathBuf>,
+    profile: Option<&str>,
+) -> Result<PathBuf> {
+    let base = match override_path {
+        Some(path) => path,
+        None => default_store_file(&resolve_config_dir()?, StoreFormat::Json),
+    };
+
+    match profile {
+        None | Some(DEFAULT_PROFILE) => Ok(base),
+        Some(name) => {
+            validate_profile_name(name)?;
+            let dir = base
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join("profiles");
+            let preferred = store_extension(&base).unwrap_or("json");
+            let path = std::iter::once(preferred)
+                .chain(STORE_EXTENSIONS)
+                .map(|ext| dir.join(format!("{}.{}", name, ext)))
+                .find(|path| path.exists())
+                .unwrap_or_else(|| dir.join(format!("{}.{}", name, preferred)));
+            Ok(path)
+        }
     }
+}
 
-    let project_dirs = ProjectDirs::from("", "", "ssher")
-        .ok_or_else(|| anyhow!("unable to resolve config directory"))?;
-    Ok(project_dirs.config_dir().join("sessions.json"))
+/// Profiles that exist next to the base store `base`: `default` first, then every
+/// `profiles/<name>.json`, `.db` or `.sqlite` sorted by name.
+pub fn list_profiles(base: &Path) -> Vec<String> {
+    let dir = base
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("profiles");
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            store_extension(&path)?;
+            Some(path.file_stem()?.to_string_lossy().into_owned())
+        })
+        .filter(|name| name != DEFAULT_PROFILE)
+        .collect();
+    names.sort();
+    names.dedup();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    names
+}
+
+fn validate_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(anyhow!("invalid profile name '{}'", name));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -19,16 +135,81 @@ mod tests {
     #[test]
     fn override_path_takes_precedence() {
         let custom_path = PathBuf::from("/custom/path/sessions.json");
-        let result = resolve_store_path(Some(custom_path.clone()));
+        let result = resolve_store_path(Some(custom_path.clone()), None);
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), custom_path);
     }
 
     #[test]
     fn none_override_uses_project_dirs() {
-        let result = resolve_store_path(None);
+        let result = resolve_store_path(None, None);
         assert!(result.is_ok());
         let path = result.unwrap();
         assert!(path.ends_with("sessions.json"));
     }
+
+    #[test]
+    fn profile_resolves_next_to_base_store() {
+        let custom_path = PathBuf::from("/custom/path/sessions.json");
+        let result = resolve_store_path(Some(custom_path.clone()), Some("work")).unwrap();
+        assert_eq!(result, PathBuf::from("/custom/path/profiles/work.json"));
+
+        let result = resolve_store_path(Some(custom_path.clone()), Some(DEFAULT_PROFILE)).unwrap();
+        assert_eq!(result, custom_path);
+    }
+
+    #[test]
+    fn default_store_keeps_an_existing_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let fresh = default_store_file(dir.path(), StoreFormat::Sqlite);
+        assert_eq!(fresh, dir.path().join("sessions.db"));
+
+        std::fs::write(dir.path().join("sessions.json"), "[]").unwrap();
+        let existing = default_store_file(dir.path(), StoreFormat::Sqlite);
+        assert_eq!(existing, dir.path().join("sessions.json"));
+        assert_eq!(StoreFormat::of_path(&existing), StoreFormat::Json);
+    }
+
+    #[test]
+    fn profiles_follow_the_base_store_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("sessions.db");
+        let work = resolve_store_path(Some(base.clone()), Some("work")).unwrap();
+        assert_eq!(work, dir.path().join("profiles/work.db"));
+
+        // An existing profile keeps its own format.
+        std::fs::create_dir(dir.path().join("profiles")).unwrap();
+        std::fs::write(dir.path().join("profiles/home.json"), "[]").unwrap();
+        let home = resolve_store_path(Some(base), Some("home")).unwrap();
+        assert_eq!(home, dir.path().join("profiles/home.json"));
+    }
+
+    #[test]
+    fn list_profiles_starts_with_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("sessions.json");
+        assert_eq!(list_profiles(&base), [DEFAULT_PROFILE]);
+
+        let profiles = dir.path().join("profiles");
+        std::fs::create_dir(&profiles).unwrap();
+        for file in [
+            "work.json",
+            "home.json",
+            "notes.txt",
+            "lab.db",
+            "work.sqlite",
+        ] {
+            std::fs::write(profiles.join(file), "[]").unwrap();
+        }
+        assert_eq!(
+            list_profiles(&base),
+            [DEFAULT_PROFILE, "home", "lab", "work"]
+        );
+    }
+
+    #[test]
+    fn profile_rejects_path_like_names() {
+        assert!(resolve_store_path(None, Some("../work")).is_err());
+        assert!(resolve_store_path(None, Some("")).is_err());
+    }
 }
@@ -0,0 +1,601 @@
+use super::connection_log;
+use super::lock::LOCK_TIMEOUT;
+use super::{ConnectionEvent, LogLimits, SessionStore, StoreConfig};
+use crate::model::{Session, SessionStoreData};
+use anyhow::{Context, Result, anyhow};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, TransactionBehavior, params};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    name TEXT PRIMARY KEY NOT NULL,
+    host TEXT NOT NULL,
+    user TEXT NOT NULL,
+    port INTEGER NOT NULL,
+    tags TEXT NOT NULL DEFAULT '[]',
+    last_connected_at INTEGER,
+    connect_count INTEGER NOT NULL DEFAULT 0,
+    settings TEXT NOT NULL DEFAULT '{}'
+);
+CREATE TABLE IF NOT EXISTS store_config (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    data TEXT NOT NULL
+);
+";
+
+const SELECT_SESSION: &str = "SELECT name, host, user, port, tags, last_connected_at, \
+     connect_count, settings FROM sessions";
+
+/// Session fields with a column of their own; everything else lives in `settings`.
+const COLUMN_FIELDS: [&str; 7] = [
+    "name",
+    "host",
+    "user",
+    "port",
+    "tags",
+    "last_connected_at",
+    "connect_count",
+];
+
+/// Whether `path` names a SQLite store by its `.db` or `.sqlite` extension.
+pub fn is_sqlite_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("db") || ext.eq_ignore_ascii_case("sqlite"))
+}
+
+/// Session store in a SQLite database, so a change rewrites one row instead of the
+/// whole file.
+///
+/// Names, hosts, users, ports, tags (a JSON array) and the connection counters are
+/// columns of the `sessions` table; the remaining session fields are kept as JSON in
+/// its `settings` column. The connection log stays a JSONL file next to the database.
+pub struct SqliteStore {
+    path: PathBuf,
+}
+
+/// One `sessions` row as stored.
+struct SessionRow {
+    name: String,
+    host: String,
+    user: String,
+    port: u16,
+    tags: String,
+    last_connected_at: Option<i64>,
+    connect_count: i64,
+    settings: String,
+}
+
+impl SessionRow {
+    fn read(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            name: row.get(0)?,
+            host: row.get(1)?,
+            user: row.get(2)?,
+            port: row.get(3)?,
+            tags: row.get(4)?,
+            last_connected_at: row.get(5)?,
+            connect_count: row.get(6)?,
+            settings: row.get(7)?,
+        })
+    }
+
+    fn from_session(session: &Session) -> Result<Self> {
+        let Value::Object(mut settings) =
+            serde_json::to_value(session).context("unable to serialize session")?
+        else {
+            return Err(anyhow!(
+                "session '{}' did not serialize to an object",
+                session.name
+            ));
+        };
+        for field in COLUMN_FIELDS {
+            settings.remove(field);
+        }
+        Ok(Self {
+            name: session.name.clone(),
+            host: session.host.clone(),
+            user: session.user.clone(),
+            port: session.port,
+            tags: serde_json::to_string(&session.tags)?,
+            last_connected_at: session.last_connected_at,
+            connect_count: i64::try_from(session.connect_count).unwrap_or(i64::MAX),
+            settings: Value::Object(settings).to_string(),
+        })
+    }
+
+    fn into_session(self) -> Result<Session> {
+        let mut fields: serde_json::Map<String, Value> = serde_json::from_str(&self.settings)
+            .with_context(|| format!("unable to parse settings of session '{}'", self.name))?;
+        let tags: Value = serde_json::from_str(&self.tags)
+            .with_context(|| format!("unable to parse tags of session '{}'", self.name))?;
+        fields.insert("tags".to_string(), tags);
+        fields.insert("port".to_string(), self.port.into());
+        fields.insert("host".to_string(), self.host.into());
+        fields.insert("user".to_string(), self.user.into());
+        fields.insert(
+            "last_connected_at".to_string(),
+            self.last_connected_at.into(),
+        );
+        fields.insert(
+            "connect_count".to_string(),
+            self.connect_count.max(0).into(),
+        );
+        fields.insert("name".to_string(), self.name.clone().into());
+        serde_json::from_value(Value::Object(fields))
+            .with_context(|| format!("unable to parse session '{}'", self.name))
+    }
+
+    /// Overwrite the row called `name` with this one; returns whether it existed.
+    fn replace(&self, conn: &Connection, name: &str) -> Result<bool> {
+        let changed = conn.execute(
+            "UPDATE sessions SET name = ?1, host = ?2, user = ?3, port = ?4, tags = ?5, \
+             last_connected_at = ?6, connect_count = ?7, settings = ?8 WHERE name = ?9",
+            params![
+                self.name,
+                self.host,
+                self.user,
+                self.port,
+                self.tags,
+                self.last_connected_at,
+                self.connect_count,
+                self.settings,
+                name
+            ],
+        )?;
+        Ok(changed > 0)
+    }
+
+    fn insert(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "INSERT INTO sessions (name, host, user, port, tags, last_connected_at, \
+             connect_count, settings) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                self.name,
+                self.host,
+                self.user,
+                self.port,
+                self.tags,
+                self.last_connected_at,
+                self.connect_count,
+                self.settings
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl SqliteStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Open the database for adding to it, creating the file and its tables on first
+    /// use. Concurrent `se` processes wait for each other's writes as long as the JSON
+    /// store's lock does.
+    fn create(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("unable to create store directory {}", parent.display())
+            })?;
+        }
+        log::debug!("opening store {}", self.path.display());
+        let conn = Connection::open(&self.path)
+            .with_context(|| format!("unable to open store {}", self.path.display()))?;
+        conn.busy_timeout(LOCK_TIMEOUT)?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("unable to set up store {}", self.path.display()))?;
+        Ok(conn)
+    }
+
+    /// Open a database that already exists; `None` when there is no file yet, which
+    /// reads treat as an empty store. Never creates the file.
+    fn open_existing(&self, flags: OpenFlags) -> Result<Option<Connection>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        log::debug!("opening store {}", self.path.display());
+        let conn = Connection::open_with_flags(&self.path, flags)
+            .with_context(|| format!("unable to open store {}", self.path.display()))?;
+        conn.busy_timeout(LOCK_TIMEOUT)?;
+        Ok(Some(conn))
+    }
+
+    fn open_read_only(&self) -> Result<Option<Connection>> {
+        self.open_existing(OpenFlags::SQLITE_OPEN_READ_ONLY)
+    }
+
+    /// Open the database to change rows that must already exist; a missing file
+    /// means `name` is not found.
+    fn open_for_change(&self, name: &str) -> Result<Connection> {
+        self.open_existing(OpenFlags::SQLITE_OPEN_READ_WRITE)?
+            .ok_or_else(|| anyhow!("session '{}' not found", name))
+    }
+
+    /// Rename in one transaction; with `replace`, a session already called
+    /// `session.name` is deleted in it instead of failing the rename.
+    fn rename_over(&self, old_name: &str, session: Session, replace: bool) -> Result<()> {
+        let mut conn = self.open_for_change(old_name)?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        if !Self::exists(&tx, old_name)? {
+            return Err(anyhow!("session '{}' not found", old_name));
+        }
+        if session.name != old_name && Self::exists(&tx, &session.name)? {
+            if !replace {
+                return Err(anyhow!("session '{}' already exists", session.name));
+            }
+            tx.execute("DELETE FROM sessions WHERE name = ?1", [&session.name])?;
+        }
+        SessionRow::from_session(&session)?.replace(&tx, old_name)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn exists(conn: &Connection, name: &str) -> Result<bool> {
+        Ok(conn
+            .query_row("SELECT 1 FROM sessions WHERE name = ?1", [name], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    fn load_data(conn: &Connection) -> Result<SessionStoreData> {
+        let data: Option<String> = conn
+            .query_row("SELECT data FROM store_config WHERE id = 1", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match data {
+            Some(data) => serde_json::from_str(&data).context("unable to parse store config"),
+            None => Ok(SessionStoreData::default()),
+        }
+    }
+}
+
+impl SessionStore for SqliteStore {
+    fn add(&self, session: Session) -> Result<()> {
+        let mut conn = self.create()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        if Self::exists(&tx, &session.name)? {
+            return Err(anyhow!("session '{}' already exists", session.name));
+        }
+        SessionRow::from_session(&session)?.insert(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn update(&self, session: Session) -> Result<()> {
+        let conn = self.open_for_change(&session.name)?;
+        if SessionRow::from_session(&session)?.replace(&conn, &session.name)? {
+            Ok(())
+        } else {
+            Err(anyhow!("session '{}' not found", session.name))
+        }
+    }
+
+    fn rename(&self, old_name: &str, session: Session) -> Result<()> {
+        self.rename_over(old_name, session, false)
+    }
+
+    fn rename_replacing(&self, old_name: &str, session: Session) -> Result<()> {
+        self.rename_over(old_name, session, true)
+    }
+
+    fn modify(
+        &self,
+        name: &str,
+        change: &mut dyn FnMut(&mut Session) -> Result<()>,
+    ) -> Result<Session> {
+        let mut conn = self.open_for_change(name)?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let row = tx
+            .query_row(
+                &format!("{} WHERE name = ?1", SELECT_SESSION),
+                [name],
+                SessionRow::read,
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("session '{}' not found", name))?;
+        let mut session = row.into_session()?;
+        change(&mut session)?;
+        SessionRow::from_session(&session)?.replace(&tx, name)?;
+        tx.commit()?;
+        Ok(session)
+    }
+
+    fn list(&self) -> Result<Vec<Session>> {
+        let Some(conn) = self.open_read_only()? else {
+            return Ok(Vec::new());
+        };
+        let mut statement = conn.prepare(&format!("{} ORDER BY name", SELECT_SESSION))?;
+        let rows = statement
+            .query_map([], SessionRow::read)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .with_context(|| format!("unable to read store {}", self.path.display()))?;
+        rows.into_iter().map(SessionRow::into_session).collect()
+    }
+
+    fn remove(&self, name: &str) -> Result<()> {
+        let conn = self.open_for_change(name)?;
+        match conn.execute("DELETE FROM sessions WHERE name = ?1", [name])? {
+            0 => Err(anyhow!("session '{}' not found", name)),
+            _ => Ok(()),
+        }
+    }
+
+    fn touch_last_connected(&self, name: &str, timestamp: i64) -> Result<()> {
+        let conn = self.open_for_change(name)?;
+        let changed = conn.execute(
+            "UPDATE sessions SET last_connected_at = ?2, connect_count = connect_count + 1 \
+             WHERE name = ?1",
+            params![name, timestamp],
+        )?;
+        match changed {
+            0 => Err(anyhow!("session '{}' not found", name)),
+            _ => Ok(()),
+        }
+    }
+
+    fn record_duration(&self, name: &str, secs: u64) -> Result<()> {
+        let mut conn = self.open_for_change(name)?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let row = tx
+            .query_row(
+                &format!("{} WHERE name = ?1", SELECT_SESSION),
+                [name],
+                SessionRow::read,
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("session '{}' not found", name))?;
+        let mut session = row.into_session()?;
+        session.record_duration(secs);
+        SessionRow::from_session(&session)?.replace(&tx, name)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn log_connection(&self, event: &ConnectionEvent) -> Result<()> {
+        let config = self.get_config()?;
+        if !config.log_connections {
+            return Ok(());
+        }
+        let limits = LogLimits {
+            max_entries: config.log_max_entries,
+            max_bytes: config.log_max_bytes,
+        };
+        connection_log::append_event(
+            &connection_log::connection_log_path(&self.path),
+            event,
+            limits,
+        )
+    }
+
+    fn connection_log(&self) -> Result<Vec<ConnectionEvent>> {
+        connection_log::read_events(&connection_log::connection_log_path(&self.path))
+    }
+
+    fn prune_connection_log(&self, keep: usize) -> Result<usize> {
+        connection_log::prune(&connection_log::connection_log_path(&self.path), keep)
+    }
+
+    fn get_config(&self) -> Result<StoreConfig> {
+        let data = match self.open_read_only()? {
+            Some(conn) => Self::load_data(&conn)?,
+            None => SessionStoreData::default(),
+        };
+        Ok(StoreConfig {
+            passwd_unsafe_mode: data.passwd_unsafe_mode,
+            passwd_unsafe_key: data.passwd_unsafe_key,
+            log_connections: data.log_connections,
+            log_max_entries: data.log_max_entries,
+            log_max_bytes: data.log_max_bytes,
+        })
+    }
+
+    fn set_config(&self, config: &StoreConfig) -> Result<()> {
+        let data = SessionStoreData {
+            passwd_unsafe_mode: config.passwd_unsafe_mode.clone(),
+            passwd_unsafe_key: config.passwd_unsafe_key.clone(),
+            log_connections: config.log_connections,
+            log_max_entries: config.log_max_entries,
+            log_max_bytes: config.log_max_bytes,
+            sessions: Vec::new(),
+        };
+        let json = serde_json::to_string(&data).context("unable to serialize store config")?;
+        self.create()?.execute(
+            "INSERT INTO store_config (id, data) VALUES (1, ?1) \
+             ON CONFLICT(id) DO UPDATE SET data = excluded.data",
+            [json],
+        )?;
+        Ok(())
+    }
+
+    /// Session names are the table's primary key, so there are no duplicates to drop.
+    fn repair(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::ConnectionKind;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+
+    fn store() -> (tempfile::TempDir, SqliteStore) {
+        let dir = tempdir().expect("tempdir");
+        let store = SqliteStore::new(dir.path().join("sessions.db"));
+        (dir, store)
+    }
+
+    #[test]
+    fn sessions_round_trip_through_columns_and_settings() {
+        let (_dir, store) = store();
+        let mut session = Session::builder("web", "web.internal", "me")
+            .port(2222)
+            .build();
+        session.tags = vec!["prod".to_string(), "eu".to_string()];
+        session.proxy_jump = Some("bastion".to_string());
+        session.options = BTreeMap::from([("Compression".to_string(), "yes".to_string())]);
+        store.add(session.clone()).expect("add");
+        store
+            .add(Session::builder("db", "db.internal", "me").build())
+            .expect("add");
+
+        let listed = store.list().expect("list");
+        assert_eq!(
+            listed.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            ["db", "web"]
+        );
+        assert_eq!(listed[1], session);
+        assert!(store.add(session).is_err());
+    }
+
+    #[test]
+    fn touch_and_duration_update_one_row() {
+        let (_dir, store) = store();
+        store
+            .add(Session::builder("web", "example.com", "me").build())
+            .expect("add");
+        store
+            .touch_last_connected("web", 1_700_000_000)
+            .expect("touch");
+        store
+            .touch_last_connected("web", 1_700_000_100)
+            .expect("touch");
+        store.record_duration("web", 40).expect("duration");
+
+        let session = &store.list().expect("list")[0];
+        assert_eq!(session.last_connected_at, Some(1_700_000_100));
+        assert_eq!(session.connect_count, 2);
+        assert_eq!(session.last_duration_secs, Some(40));
+        assert!(store.touch_last_connected("missing", 1).is_err());
+    }
+
+    #[test]
+    fn rename_update_and_remove() {
+        let (_dir, store) = store();
+        store
+            .add(Session::builder("web", "example.com", "me").build())
+            .expect("add");
+        store
+            .add(Session::builder("db", "example.com", "me").build())
+            .expect("add");
+
+        let renamed = Session::builder("www", "example.com", "me").build();
+        assert!(
+            store
+                .rename("web", Session::builder("db", "x", "me").build())
+                .is_err()
+        );
+        store.rename("web", renamed).expect("rename");
+        store
+            .update(Session::builder("www", "new.example.com", "me").build())
+            .expect("update");
+        store.remove("db").expect("remove");
+        assert!(store.remove("db").is_err());
+
+        let listed = store.list().expect("list");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].host, "new.example.com");
+    }
+
+    #[test]
+    fn rename_replacing_deletes_the_target_in_one_transaction() {
+        let (_dir, store) = store();
+        store
+            .add(Session::builder("web", "web.example.com", "me").build())
+            .expect("add");
+        store
+            .add(Session::builder("db", "db.example.com", "me").build())
+            .expect("add");
+
+        store
+            .rename_replacing(
+                "web",
+                Session::builder("db", "web.example.com", "me").build(),
+            )
+            .expect("rename replacing");
+        let listed = store.list().expect("list");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "db");
+        assert_eq!(listed[0].host, "web.example.com");
+        assert!(
+            store
+                .rename_replacing("web", Session::builder("db", "x", "me").build())
+                .is_err()
+        );
+        assert_eq!(store.list().expect("list")[0].host, "web.example.com");
+    }
+
+    #[test]
+    fn modify_changes_one_row_in_a_transaction() {
+        let (_dir, store) = store();
+        store
+            .add(Session::builder("web", "web.example.com", "me").build())
+            .expect("add");
+
+        let saved = store
+            .modify("web", &mut |session| {
+                session.port = 2222;
+                Ok(())
+            })
+            .expect("modify");
+        assert_eq!(saved.port, 2222);
+        assert!(
+            store
+                .modify("web", &mut |session| {
+                    session.port = 22;
+                    Err(anyhow!("refused"))
+                })
+                .is_err()
+        );
+        assert_eq!(store.list().expect("list")[0].port, 2222);
+    }
+
+    #[test]
+    fn config_and_connection_log() {
+        let (_dir, store) = store();
+        assert_eq!(store.get_config().expect("config"), StoreConfig::default());
+        store
+            .add(Session::builder("web", "example.com", "me").build())
+            .expect("add");
+        store
+            .set_config(&StoreConfig {
+                log_connections: true,
+                ..StoreConfig::default()
+            })
+            .expect("set config");
+        store
+            .record_connection("web", ConnectionKind::Ssh, 1_700_000_000, 5)
+            .expect("record");
+
+        assert!(store.get_config().expect("config").log_connections);
+        assert_eq!(store.connection_log().expect("log").len(), 1);
+    }
+
+    #[test]
+    fn reads_do_not_create_the_database() {
+        let (_dir, store) = store();
+        assert!(store.list().expect("list").is_empty());
+        assert_eq!(store.get_config().expect("config"), StoreConfig::default());
+        assert!(store.repair().expect("repair").is_empty());
+        assert!(store.touch_last_connected("web", 1).is_err());
+        assert!(store.remove("web").is_err());
+        assert!(!store.path.exists());
+
+        store
+            .add(Session::builder("web", "example.com", "me").build())
+            .expect("add");
+        assert!(store.path.exists());
+    }
+
+    #[test]
+    fn recognizes_sqlite_extensions() {
+        assert!(is_sqlite_path(Path::new("sessions.db")));
+        assert!(is_sqlite_path(Path::new("/tmp/ssher.SQLITE")));
+        assert!(!is_sqlite_path(Path::new("sessions.json")));
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::{Context, Result, anyhow};
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Clipboard helpers tried in order; the first one that runs successfully wins.
+const BACKENDS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// Copy `text` to the system clipboard by piping it into an external helper.
+/// Returns the name of the helper that was used.
+pub fn copy_to_clipboard(text: &str) -> Result<&'static str> {
+    let wayland = env::var_os("WAYLAND_DISPLAY").is_some();
+    for (program, args) in BACKENDS {
+        if *program == "wl-copy" && !wayland {
+            continue;
+        }
+        if pipe_to(program, args, text).is_ok() {
+            return Ok(*program);
+        }
+    }
+    Err(anyhow!("no clipboard helper available"))
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to launch {program}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .with_context(|| format!("failed to write to {program}"))?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("{program} exited with {status}"))
+    }
+}
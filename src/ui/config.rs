@@ -1,6 +1,8 @@
-use anyhow::{Context, Result, anyhow};
-use directories::ProjectDirs;
-use serde::{Deserialize, Serialize};
+use crate::config_check::ensure_known_keys;
+use crate::dirs::resolve_config_dir;
+use anyhow::{Context, Result, bail};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fs;
 use std::path::PathBuf;
 
@@ -13,6 +15,7 @@ pub struct UiConfig {
     pub input: InputConfig,
     pub ordering: OrderingConfig,
     pub highlights: SessionHighlightConfig,
+    pub columns: ColumnsConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -50,6 +53,10 @@ pub struct LayoutConfig {
     pub monitor_height: u16,
     pub help_height: u16,
     pub status_height: u16,
+    pub row_density: RowDensity,
+    pub identity_display: IdentityDisplay,
+    /// Seconds between `ps` scans while the monitor panel is open
+    pub monitor_refresh_secs: u64,
 }
 
 impl Default for LayoutConfig {
@@ -65,10 +72,194 @@ impl Default for LayoutConfig {
             monitor_height: 5,
             help_height: 2,
             status_height: 1,
+            row_density: RowDensity::Full,
+            identity_display: IdentityDisplay::Middle,
+            monitor_refresh_secs: 1,
         }
     }
 }
 
+impl LayoutConfig {
+    /// One message per layout value outside its allowed range.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.monitor_refresh_secs < MIN_MONITOR_REFRESH_SECS {
+            problems.push(format!(
+                "layout.monitor_refresh_secs: {} is below the minimum of {}",
+                self.monitor_refresh_secs, MIN_MONITOR_REFRESH_SECS
+            ));
+        }
+        problems
+    }
+}
+
+/// How much of each session row the table renders.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RowDensity {
+    /// All columns (name, target, port, identity, tags, password)
+    #[default]
+    Full,
+    /// Name and target only
+    Compact,
+}
+
+impl RowDensity {
+    pub fn toggled(self) -> Self {
+        match self {
+            RowDensity::Full => RowDensity::Compact,
+            RowDensity::Compact => RowDensity::Full,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RowDensity::Full => "full",
+            RowDensity::Compact => "compact",
+        }
+    }
+}
+
+/// How identity file paths are shortened to fit the table's identity column.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityDisplay {
+    /// The whole path, cut off at the column edge
+    Full,
+    /// Only the file name
+    Basename,
+    /// Trailing path components behind an ellipsis, e.g. `…/prod/id_ed25519`
+    #[default]
+    Middle,
+}
+
+/// Shortest monitor refresh interval; anything faster would scan `ps` every frame.
+pub const MIN_MONITOR_REFRESH_SECS: u64 = 1;
+/// Narrowest fixed width a session table column may be given.
+pub const MIN_COLUMN_WIDTH: u16 = 3;
+/// Narrowest the full session table may add up to; `auto` columns count as
+/// [`AUTO_COLUMN_MIN_WIDTH`].
+pub const MIN_TABLE_WIDTH: u16 = 30;
+/// Space an `auto` column is guaranteed before it shares what is left over.
+pub const AUTO_COLUMN_MIN_WIDTH: u16 = 10;
+
+/// Width of one session table column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnWidth {
+    /// Share the space the fixed columns leave over (written as `"auto"`)
+    Auto,
+    /// Exactly this many cells
+    Fixed(u16),
+}
+
+impl ColumnWidth {
+    fn min_width(self) -> u16 {
+        match self {
+            ColumnWidth::Auto => AUTO_COLUMN_MIN_WIDTH,
+            ColumnWidth::Fixed(width) => width,
+        }
+    }
+}
+
+impl Serialize for ColumnWidth {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            ColumnWidth::Auto => serializer.serialize_str("auto"),
+            ColumnWidth::Fixed(width) => serializer.serialize_u16(*width),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ColumnWidth {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Fixed(u16),
+            Keyword(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Fixed(width) => Ok(ColumnWidth::Fixed(width)),
+            Raw::Keyword(keyword) if keyword.eq_ignore_ascii_case("auto") => Ok(ColumnWidth::Auto),
+            Raw::Keyword(keyword) => Err(D::Error::custom(format!(
+                "invalid column width '{}' (expected a number or \"auto\")",
+                keyword
+            ))),
+        }
+    }
+}
+
+/// Widths of the session table columns in full row density. Compact rows use
+/// `name` and let the target fill the rest.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ColumnsConfig {
+    pub name: ColumnWidth,
+    pub target: ColumnWidth,
+    pub port: ColumnWidth,
+    pub identity: ColumnWidth,
+    pub tags: ColumnWidth,
+    pub password: ColumnWidth,
+    pub connections: ColumnWidth,
+}
+
+impl Default for ColumnsConfig {
+    fn default() -> Self {
+        Self {
+            name: ColumnWidth::Fixed(20),
+            target: ColumnWidth::Fixed(30),
+            port: ColumnWidth::Fixed(6),
+            identity: ColumnWidth::Fixed(18),
+            tags: ColumnWidth::Auto,
+            password: ColumnWidth::Fixed(5),
+            connections: ColumnWidth::Fixed(6),
+        }
+    }
+}
+
+impl ColumnsConfig {
+    /// Columns in table order, labelled with their config keys.
+    pub fn widths(&self) -> [(&'static str, ColumnWidth); 7] {
+        [
+            ("name", self.name),
+            ("target", self.target),
+            ("port", self.port),
+            ("identity", self.identity),
+            ("tags", self.tags),
+            ("password", self.password),
+            ("connections", self.connections),
+        ]
+    }
+
+    /// One message per width that would leave the table unreadable.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (key, width) in self.widths() {
+            if let ColumnWidth::Fixed(width) = width
+                && width < MIN_COLUMN_WIDTH
+            {
+                problems.push(format!(
+                    "columns.{}: width {} is below the minimum of {}",
+                    key, width, MIN_COLUMN_WIDTH
+                ));
+            }
+        }
+        let total: u32 = self
+            .widths()
+            .iter()
+            .map(|(_, width)| u32::from(width.min_width()))
+            .sum();
+        if total < u32::from(MIN_TABLE_WIDTH) {
+            problems.push(format!(
+                "columns: widths add up to {}, below the minimum of {}",
+                total, MIN_TABLE_WIDTH
+            ));
+        }
+        problems
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(default)]
 pub struct ThemeConfig {
@@ -99,12 +290,97 @@ impl Default for ThemeConfig {
 #[serde(default)]
 pub struct InputConfig {
     pub form_default_mode: FormStartMode,
+    /// Ask before discarding a partially-filled add/edit/SCP form on Esc
+    pub confirm_discard: bool,
+    /// Add/edit form fields in Tab order; fields left out keep their defaults
+    pub form_fields: Vec<FormField>,
+    /// What Enter does on the session list
+    pub enter_action: EnterAction,
 }
 
 impl Default for InputConfig {
     fn default() -> Self {
         Self {
             form_default_mode: FormStartMode::Normal,
+            confirm_discard: false,
+            form_fields: DEFAULT_FORM_FIELDS.to_vec(),
+            enter_action: EnterAction::Connect,
+        }
+    }
+}
+
+impl InputConfig {
+    /// One message per missing required field or repeated field.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for required in [FormField::Name, FormField::Host] {
+            if !self.form_fields.contains(&required) {
+                problems.push(format!(
+                    "input.form_fields: \"{}\" is required",
+                    required.key()
+                ));
+            }
+        }
+        for (index, field) in self.form_fields.iter().enumerate() {
+            if self.form_fields[..index].contains(field) {
+                problems.push(format!(
+                    "input.form_fields: \"{}\" is listed more than once",
+                    field.key()
+                ));
+            }
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FormField {
+    Name,
+    Host,
+    User,
+    Port,
+    Identity,
+    #[serde(rename = "proxy_jump")]
+    ProxyJump,
+    #[serde(rename = "ssh_args")]
+    ExtraArgs,
+    #[serde(rename = "local_forwards")]
+    LocalForwards,
+    #[serde(rename = "remote_forwards")]
+    RemoteForwards,
+    Password,
+    Tags,
+}
+
+pub const DEFAULT_FORM_FIELDS: [FormField; 11] = [
+    FormField::Name,
+    FormField::Host,
+    FormField::User,
+    FormField::Port,
+    FormField::Identity,
+    FormField::ProxyJump,
+    FormField::ExtraArgs,
+    FormField::LocalForwards,
+    FormField::RemoteForwards,
+    FormField::Password,
+    FormField::Tags,
+];
+
+impl FormField {
+    pub fn key(self) -> &'static str {
+        match self {
+            FormField::Name => "name",
+            FormField::Host => "host",
+            FormField::User => "user",
+            FormField::Port => "port",
+            FormField::Identity => "identity",
+            FormField::ProxyJump => "proxy_jump",
+            FormField::ExtraArgs => "ssh_args",
+            FormField::LocalForwards => "local_forwards",
+            FormField::RemoteForwards => "remote_forwards",
+            FormField::Password => "password",
+            FormField::Tags => "tags",
         }
     }
 }
@@ -117,6 +393,15 @@ pub enum FormStartMode {
     Insert,
 }
 
+/// `connect` opens ssh straight away; `menu` asks which action to run first.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EnterAction {
+    #[default]
+    Connect,
+    Menu,
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum SessionOrderMode {
@@ -126,6 +411,24 @@ pub enum SessionOrderMode {
     Alphabetical,
 }
 
+impl SessionOrderMode {
+    pub fn next(self) -> Self {
+        match self {
+            SessionOrderMode::LatestFirst => SessionOrderMode::FrequencyBased,
+            SessionOrderMode::FrequencyBased => SessionOrderMode::Alphabetical,
+            SessionOrderMode::Alphabetical => SessionOrderMode::LatestFirst,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionOrderMode::LatestFirst => "latest first",
+            SessionOrderMode::FrequencyBased => "most used",
+            SessionOrderMode::Alphabetical => "name",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(default)]
 pub struct SessionLifetimeConfig {
@@ -175,13 +478,57 @@ impl Default for OrderingConfig {
     }
 }
 
+/// Color names understood by the TUI and CLI theme parsers (matched case-insensitively).
+/// Anything else silently falls back to white.
+pub const COLOR_NAMES: &[&str] = &[
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "gray",
+    "darkgray",
+    "dark_gray",
+    "lightred",
+    "light_red",
+    "lightgreen",
+    "light_green",
+    "lightyellow",
+    "light_yellow",
+    "lightblue",
+    "light_blue",
+    "lightmagenta",
+    "light_magenta",
+    "lightcyan",
+    "light_cyan",
+    "white",
+];
+
+pub fn is_color_name(name: &str) -> bool {
+    COLOR_NAMES.contains(&name.to_lowercase().as_str())
+}
+
 pub fn load_ui_config(override_path: Option<PathBuf>) -> Result<UiConfig> {
     let path = resolve_ui_config_path(override_path)?;
     if let Some(path) = path {
+        log::debug!("loading UI config from {}", path.display());
         let data = fs::read_to_string(&path)
             .with_context(|| format!("unable to read {}", path.display()))?;
-        let config = serde_json::from_str(&data)
+        ensure_known_keys::<UiConfig>(&data, &path)?;
+        let config: UiConfig = serde_json::from_str(&data)
             .with_context(|| format!("unable to parse {}", path.display()))?;
+        if let Some(problem) = config
+            .layout
+            .problems()
+            .into_iter()
+            .chain(config.columns.problems())
+            .chain(config.input.problems())
+            .next()
+        {
+            bail!("{}: {}", path.display(), problem);
+        }
         return Ok(config);
     }
     Ok(UiConfig::default())
@@ -192,9 +539,7 @@ fn resolve_ui_config_path(override_path: Option<PathBuf>) -> Result<Option<PathB
         return Ok(Some(path));
     }
 
-    let project_dirs = ProjectDirs::from("", "", "ssher")
-        .ok_or_else(|| anyhow!("unable to resolve config directory"))?;
-    let candidate = project_dirs.config_dir().join("ui.json");
+    let candidate = ui_config_candidate_path(None)?;
     if candidate.exists() {
         Ok(Some(candidate))
     } else {
@@ -202,10 +547,37 @@ fn resolve_ui_config_path(override_path: Option<PathBuf>) -> Result<Option<PathB
     }
 }
 
+/// Path the UI config is read from, whether or not the file exists.
+pub fn ui_config_candidate_path(override_path: Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(path) = override_path {
+        return Ok(path);
+    }
+
+    Ok(resolve_config_dir()?.join("ui.json"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn default_theme_colors_are_known_names() {
+        let theme = ThemeConfig::default();
+        for color in [
+            &theme.logo,
+            &theme.header,
+            &theme.highlight,
+            &theme.border,
+            &theme.help,
+            &theme.status,
+            &theme.text,
+        ] {
+            assert!(is_color_name(color), "{color}");
+        }
+        assert!(is_color_name("Light_Blue"));
+        assert!(!is_color_name("purple"));
+    }
+
     #[test]
     fn ui_config_default_values() {
         let config = UiConfig::default();
@@ -256,6 +628,93 @@ mod tests {
         assert_eq!(config.monitor_height, 5);
         assert_eq!(config.help_height, 2);
         assert_eq!(config.status_height, 1);
+        assert_eq!(config.row_density, RowDensity::Full);
+    }
+
+    #[test]
+    fn row_density_deserializes_and_toggles() {
+        let json = r#"{"layout": {"row_density": "compact"}}"#;
+        let config: UiConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.layout.row_density, RowDensity::Compact);
+        assert_eq!(config.layout.row_density.toggled(), RowDensity::Full);
+    }
+
+    #[test]
+    fn columns_accept_numbers_and_auto() {
+        let json = r#"{"columns": {"name": 12, "target": "auto", "tags": "AUTO"}}"#;
+        let config: UiConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.columns.name, ColumnWidth::Fixed(12));
+        assert_eq!(config.columns.target, ColumnWidth::Auto);
+        assert_eq!(config.columns.tags, ColumnWidth::Auto);
+        assert_eq!(config.columns.port, ColumnWidth::Fixed(6));
+        assert!(config.columns.problems().is_empty());
+
+        let value = serde_json::to_value(&config.columns).unwrap();
+        assert_eq!(value["name"], 12);
+        assert_eq!(value["target"], "auto");
+
+        let err = serde_json::from_str::<UiConfig>(r#"{"columns": {"name": "wide"}}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("expected a number or \"auto\""), "{err}");
+    }
+
+    #[test]
+    fn columns_reject_narrow_widths() {
+        let columns = ColumnsConfig {
+            name: ColumnWidth::Fixed(1),
+            target: ColumnWidth::Fixed(4),
+            port: ColumnWidth::Fixed(4),
+            identity: ColumnWidth::Fixed(4),
+            tags: ColumnWidth::Fixed(4),
+            password: ColumnWidth::Fixed(4),
+            connections: ColumnWidth::Fixed(4),
+        };
+        assert_eq!(
+            columns.problems(),
+            vec![
+                "columns.name: width 1 is below the minimum of 3".to_string(),
+                "columns: widths add up to 25, below the minimum of 30".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn monitor_refresh_has_a_minimum() {
+        let config: UiConfig =
+            serde_json::from_str(r#"{"layout": {"monitor_refresh_secs": 5}}"#).unwrap();
+        assert_eq!(config.layout.monitor_refresh_secs, 5);
+        assert!(config.layout.problems().is_empty());
+        assert_eq!(UiConfig::default().layout.monitor_refresh_secs, 1);
+
+        let config: UiConfig =
+            serde_json::from_str(r#"{"layout": {"monitor_refresh_secs": 0}}"#).unwrap();
+        assert_eq!(
+            config.layout.problems(),
+            vec!["layout.monitor_refresh_secs: 0 is below the minimum of 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn form_fields_can_be_reordered_and_trimmed() {
+        let json = r#"{"input": {"form_fields": ["host", "name", "user"]}}"#;
+        let config: UiConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.input.form_fields,
+            [FormField::Host, FormField::Name, FormField::User]
+        );
+        assert!(config.input.problems().is_empty());
+        assert_eq!(UiConfig::default().input.form_fields, DEFAULT_FORM_FIELDS);
+
+        let json = r#"{"input": {"form_fields": ["host", "tags", "tags"]}}"#;
+        let config: UiConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.input.problems(),
+            vec![
+                "input.form_fields: \"name\" is required".to_string(),
+                "input.form_fields: \"tags\" is listed more than once".to_string(),
+            ]
+        );
     }
 
     #[test]
@@ -308,6 +767,15 @@ mod tests {
         assert_eq!(config.theme.logo, "Red");
         assert_eq!(config.theme.header, "Blue");
         assert_eq!(config.input.form_default_mode, FormStartMode::Insert);
+        assert!(!config.input.confirm_discard);
+        assert_eq!(config.input.enter_action, EnterAction::Connect);
+    }
+
+    #[test]
+    fn enter_action_reads_menu() {
+        let json = r#"{"input": {"enter_action": "menu"}}"#;
+        let config: UiConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.input.enter_action, EnterAction::Menu);
     }
 
     #[test]
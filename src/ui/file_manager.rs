@@ -0,0 +1,27 @@
+use anyhow::{Result, anyhow};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Launchers tried in order; the first one that starts wins.
+const LAUNCHERS: &[&str] = if cfg!(target_os = "macos") {
+    &["open"]
+} else {
+    &["xdg-open"]
+};
+
+/// Show `dir` in the desktop file manager without waiting for it to close.
+/// Returns the name of the launcher that was used.
+pub fn open_directory(dir: &Path) -> Result<&'static str> {
+    for program in LAUNCHERS {
+        let spawned = Command::new(program)
+            .arg(dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if spawned.is_ok() {
+            return Ok(*program);
+        }
+    }
+    Err(anyhow!("no file manager launcher available"))
+}
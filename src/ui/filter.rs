@@ -1,44 +1,129 @@
 use crate::model::Session;
 
+/// Indices of sessions matching `filter`, best match first.
+///
+/// Each whitespace-separated term must fuzzy-match (as a subsequence, ignoring case)
+/// the name, host, user, identity file or a tag, so `ofex` finds `office.example.com`
+/// and `ex off` finds it too. A `name:`, `host:`, `user:` or `tag:` prefix limits a
+/// term to that field, so `tag:prod host:example` needs both. Tighter matches rank
+/// higher, then matches that start earlier in the field, then the original order.
 pub fn filter_sessions(sessions: &[Session], filter: &str) -> Vec<usize> {
-    if filter.trim().is_empty() {
+    let lowered = filter.to_lowercase();
+    let terms: Vec<Term> = lowered
+        .split_whitespace()
+        .map(Term::parse)
+        .filter(|term| !term.needle.is_empty())
+        .collect();
+    if terms.is_empty() {
         return (0..sessions.len()).collect();
     }
 
-    let needle = filter.to_lowercase();
-    sessions
+    let mut scored: Vec<(Score, usize)> = sessions
         .iter()
         .enumerate()
-        .filter(|(_, session)| session_matches(session, &needle))
-        .map(|(index, _)| index)
-        .collect()
+        .filter_map(|(index, session)| session_score(session, &terms).map(|score| (score, index)))
+        .collect();
+    scored.sort();
+    scored.into_iter().map(|(_, index)| index).collect()
 }
 
-fn session_matches(session: &Session, needle: &str) -> bool {
-    let name = session.name.to_lowercase();
-    let host = session.host.to_lowercase();
-    let user = session.user.to_lowercase();
+/// The field a `prefix:` term is limited to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Name,
+    Host,
+    User,
+    Tag,
+}
+
+/// One search term; `scope` is `None` for bare words, which match any field.
+struct Term {
+    scope: Option<Scope>,
+    needle: Vec<char>,
+}
 
-    if name.contains(needle) || host.contains(needle) || user.contains(needle) {
-        return true;
+impl Term {
+    /// Split off a known `prefix:`; anything else (`http://`, `a:b`) stays a bare word.
+    fn parse(word: &str) -> Self {
+        let scoped = word.split_once(':').and_then(|(prefix, rest)| {
+            let scope = match prefix {
+                "name" => Scope::Name,
+                "host" => Scope::Host,
+                "user" => Scope::User,
+                "tag" => Scope::Tag,
+                _ => return None,
+            };
+            Some((scope, rest))
+        });
+        match scoped {
+            Some((scope, rest)) => Self {
+                scope: Some(scope),
+                needle: rest.chars().collect(),
+            },
+            None => Self {
+                scope: None,
+                needle: word.chars().collect(),
+            },
+        }
     }
+}
 
+/// How well a needle matched: characters skipped inside the match, then where the
+/// match started. Lower is better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Score {
+    gaps: usize,
+    start: usize,
+}
+
+fn session_score(session: &Session, terms: &[Term]) -> Option<Score> {
+    let chars = |text: &str| -> Vec<char> { text.to_lowercase().chars().collect() };
+    let mut fields = vec![
+        (Some(Scope::Name), chars(&session.name)),
+        (Some(Scope::Host), chars(&session.host)),
+        (Some(Scope::User), chars(&session.user)),
+    ];
     if let Some(identity) = &session.identity_file {
-        let identity_str = identity.to_string_lossy().to_lowercase();
-        if identity_str.contains(needle) {
-            return true;
-        }
+        fields.push((None, chars(&identity.to_string_lossy())));
     }
+    fields.extend(
+        session
+            .tags
+            .iter()
+            .map(|tag| (Some(Scope::Tag), chars(tag))),
+    );
 
-    if session
-        .tags
-        .iter()
-        .any(|tag| tag.to_lowercase().contains(needle))
-    {
-        return true;
+    let mut total = Score { gaps: 0, start: 0 };
+    for term in terms {
+        let best = fields
+            .iter()
+            .filter(|(scope, _)| term.scope.is_none() || *scope == term.scope)
+            .filter_map(|(_, field)| fuzzy_score(field, &term.needle))
+            .min()?;
+        total.gaps += best.gaps;
+        total.start += best.start;
     }
+    Some(total)
+}
 
-    false
+/// The tightest subsequence match of `needle` in `haystack`, if any.
+fn fuzzy_score(haystack: &[char], needle: &[char]) -> Option<Score> {
+    let first = *needle.first()?;
+    haystack
+        .iter()
+        .enumerate()
+        .filter(|(_, ch)| **ch == first)
+        .filter_map(|(start, _)| {
+            let mut end = start;
+            for ch in &needle[1..] {
+                end += 1 + haystack[end + 1..].iter().position(|c| c == ch)?;
+            }
+            Some(Score {
+                gaps: end + 1 - start - needle.len(),
+                start,
+            })
+        })
+        .min()
 }
 
 #[cfg(test)]
@@ -48,18 +133,9 @@ mod tests {
     use std::path::PathBuf;
 
     fn session(name: &str, host: &str, user: &str, identity: Option<&str>) -> Session {
-        Session {
-            name: name.to_string(),
-            host: host.to_string(),
-            user: user.to_string(),
-            port: 22,
-            identity_file: identity.map(PathBuf::from),
-            tags: Vec::new(),
-            last_connected_at: None,
-            has_stored_password: false,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        }
+        let mut session = Session::builder(name, host, user).build();
+        session.identity_file = identity.map(PathBuf::from);
+        session
     }
 
     #[test]
@@ -70,7 +146,8 @@ mod tests {
         ];
         assert_eq!(filter_sessions(&sessions, "office"), vec![0]);
         assert_eq!(filter_sessions(&sessions, "deploy"), vec![1]);
-        assert_eq!(filter_sessions(&sessions, "example"), vec![0, 1]);
+        // Both hosts contain "example"; prod's match starts earlier.
+        assert_eq!(filter_sessions(&sessions, "example"), vec![1, 0]);
     }
 
     #[test]
@@ -146,6 +223,74 @@ mod tests {
         assert!(filter_sessions(&sessions, "").is_empty());
     }
 
+    #[test]
+    fn filter_matches_subsequences_and_out_of_order_terms() {
+        let sessions = vec![
+            session("prod", "prod.example.com", "deploy", None),
+            session("office", "office.example.com", "me", None),
+        ];
+        assert_eq!(filter_sessions(&sessions, "ofex"), vec![1]);
+        assert_eq!(filter_sessions(&sessions, "ex off"), vec![1]);
+        assert!(filter_sessions(&sessions, "xeof").is_empty());
+    }
+
+    #[test]
+    fn filter_ranks_tighter_matches_first() {
+        let sessions = vec![
+            session("db-backup", "b.example.com", "me", None),
+            session("dbb", "c.example.com", "me", None),
+        ];
+        // "dbb" is contiguous in the second name but spread out in the first.
+        assert_eq!(filter_sessions(&sessions, "dbb"), vec![1, 0]);
+    }
+
+    #[test]
+    fn filter_breaks_ties_by_match_position() {
+        let sessions = vec![
+            session("web-lab", "a.example.com", "me", None),
+            session("lab-web", "b.example.com", "me", None),
+            session("lab-db", "c.example.com", "me", None),
+        ];
+        // Equal gaps: the earlier match wins, then the original order.
+        assert_eq!(filter_sessions(&sessions, "lab"), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn scoped_terms_only_match_their_field() {
+        let mut web = session("web", "db-proxy.example.com", "deploy", None);
+        web.tags = vec!["prod".to_string()];
+        let mut db = session("db", "web-cache.example.com", "web", None);
+        db.tags = vec!["staging".to_string()];
+        let sessions = vec![web, db];
+
+        assert_eq!(filter_sessions(&sessions, "web"), vec![0, 1]);
+        assert_eq!(filter_sessions(&sessions, "name:web"), vec![0]);
+        assert_eq!(filter_sessions(&sessions, "host:web"), vec![1]);
+        assert_eq!(filter_sessions(&sessions, "user:web"), vec![1]);
+        assert_eq!(filter_sessions(&sessions, "tag:prod"), vec![0]);
+        assert_eq!(filter_sessions(&sessions, "TAG:Stag"), vec![1]);
+        assert!(filter_sessions(&sessions, "name:prod").is_empty());
+    }
+
+    #[test]
+    fn scoped_and_bare_terms_combine() {
+        let mut web = session("web", "web.example.com", "deploy", None);
+        web.tags = vec!["prod".to_string()];
+        let mut api = session("api", "api.example.org", "deploy", None);
+        api.tags = vec!["prod".to_string()];
+        let sessions = vec![web, api];
+
+        assert_eq!(
+            filter_sessions(&sessions, "tag:prod host:example"),
+            vec![0, 1]
+        );
+        assert_eq!(filter_sessions(&sessions, "tag:prod com"), vec![0]);
+        assert_eq!(filter_sessions(&sessions, "deploy host:org"), vec![1]);
+        // A bare prefix filters nothing yet; unknown prefixes are plain text.
+        assert_eq!(filter_sessions(&sessions, "tag:"), vec![0, 1]);
+        assert!(filter_sessions(&sessions, "port:22").is_empty());
+    }
+
     #[test]
     fn filter_special_characters_in_identity() {
         let mut s = session(
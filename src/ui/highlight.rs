@@ -1,5 +1,4 @@
 use crate::model::Session;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SessionHighlight {
@@ -9,12 +8,8 @@ pub enum SessionHighlight {
 }
 
 impl SessionHighlight {
-    pub fn classify(session: &Session, dying_threshold_days: u32) -> Self {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-
+    /// Classify by age of the last connection as of `now` (epoch seconds).
+    pub fn classify(session: &Session, dying_threshold_days: u32, now: i64) -> Self {
         let Some(last_connected) = session.last_connected_at else {
             // Sessions never connected are "dying"
             return SessionHighlight::Dying;
@@ -39,75 +34,84 @@ mod tests {
     use super::*;
     use crate::model::Session;
 
+    const NOW: i64 = 1_700_000_000;
+
     fn session(name: &str, ts: Option<i64>) -> Session {
-        Session {
-            name: name.to_string(),
-            host: "example.com".to_string(),
-            user: "user".to_string(),
-            port: 22,
-            identity_file: None,
-            tags: vec![],
-            last_connected_at: ts,
-            has_stored_password: false,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        }
+        let mut session = Session::builder(name, "example.com", "user").build();
+        session.last_connected_at = ts;
+        session
     }
 
     #[test]
     fn classify_hot_session() {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let one_hour_ago = now - 3600;
+        let one_hour_ago = NOW - 3600;
 
         let s = session("hot", Some(one_hour_ago));
-        assert_eq!(SessionHighlight::classify(&s, 7), SessionHighlight::Hot);
+        assert_eq!(
+            SessionHighlight::classify(&s, 7, NOW),
+            SessionHighlight::Hot
+        );
     }
 
     #[test]
     fn classify_normal_session() {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let three_days_ago = now - (3 * 24 * 3600);
+        let three_days_ago = NOW - (3 * 24 * 3600);
 
         let s = session("normal", Some(three_days_ago));
-        assert_eq!(SessionHighlight::classify(&s, 7), SessionHighlight::Normal);
+        assert_eq!(
+            SessionHighlight::classify(&s, 7, NOW),
+            SessionHighlight::Normal
+        );
     }
 
     #[test]
     fn classify_dying_session() {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let ten_days_ago = now - (10 * 24 * 3600);
+        let ten_days_ago = NOW - (10 * 24 * 3600);
 
         let s = session("dying", Some(ten_days_ago));
-        assert_eq!(SessionHighlight::classify(&s, 7), SessionHighlight::Dying);
+        assert_eq!(
+            SessionHighlight::classify(&s, 7, NOW),
+            SessionHighlight::Dying
+        );
+    }
+
+    #[test]
+    fn classify_dims_only_past_threshold() {
+        let day = 24 * 3600;
+        let s = session("edge", Some(NOW - 7 * day));
+        assert_eq!(
+            SessionHighlight::classify(&s, 7, NOW),
+            SessionHighlight::Normal
+        );
+        assert_eq!(
+            SessionHighlight::classify(&s, 7, NOW + day),
+            SessionHighlight::Dying
+        );
     }
 
     #[test]
     fn classify_never_connected() {
         let s = session("never", None);
-        assert_eq!(SessionHighlight::classify(&s, 7), SessionHighlight::Dying);
+        assert_eq!(
+            SessionHighlight::classify(&s, 7, NOW),
+            SessionHighlight::Dying
+        );
     }
 
     #[test]
     fn classify_custom_threshold() {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let five_days_ago = now - (5 * 24 * 3600);
+        let five_days_ago = NOW - (5 * 24 * 3600);
 
         let s = session("custom", Some(five_days_ago));
         // With 3-day threshold, 5 days is dying
-        assert_eq!(SessionHighlight::classify(&s, 3), SessionHighlight::Dying);
+        assert_eq!(
+            SessionHighlight::classify(&s, 3, NOW),
+            SessionHighlight::Dying
+        );
         // With 7-day threshold, 5 days is normal
-        assert_eq!(SessionHighlight::classify(&s, 7), SessionHighlight::Normal);
+        assert_eq!(
+            SessionHighlight::classify(&s, 7, NOW),
+            SessionHighlight::Normal
+        );
     }
 }
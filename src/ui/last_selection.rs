@@ -0,0 +1,62 @@
+use crate::dirs::resolve_config_dir;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Small TUI state persisted between launches.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+struct TuiState {
+    last_selected: Option<String>,
+}
+
+pub fn resolve_last_selection_path() -> Result<PathBuf> {
+    Ok(resolve_config_dir()?.join("tui_state.json"))
+}
+
+/// Read the remembered session name; a missing or unreadable file yields `None`.
+pub fn load_last_selection(path: &Path) -> Option<String> {
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<TuiState>(&data).ok()?.last_selected
+}
+
+pub fn save_last_selection(path: &Path, name: Option<&str>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("unable to create directory {}", parent.display()))?;
+    }
+    let state = TuiState {
+        last_selected: name.map(str::to_string),
+    };
+    let json = serde_json::to_string_pretty(&state).context("unable to serialize TUI state")?;
+    fs::write(path, json).with_context(|| format!("unable to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("nested/tui_state.json");
+
+        save_last_selection(&path, Some("office")).expect("save");
+        assert_eq!(load_last_selection(&path), Some("office".to_string()));
+
+        save_last_selection(&path, None).expect("save");
+        assert_eq!(load_last_selection(&path), None);
+    }
+
+    #[test]
+    fn load_ignores_missing_or_invalid_file() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("tui_state.json");
+        assert_eq!(load_last_selection(&path), None);
+
+        fs::write(&path, "not json").expect("write");
+        assert_eq!(load_last_selection(&path), None);
+    }
+}
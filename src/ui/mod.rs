@@ -1,19 +1,33 @@
+pub(crate) mod clipboard;
 pub mod config;
+mod file_manager;
 pub mod filter;
 pub mod highlight;
+mod last_selection;
 pub mod ordering;
+mod probe;
 mod state;
 
 use crate::auth::resolve_session_password;
-use crate::model::{PasswdUnsafeMode, Session};
+use crate::clock::now_epoch_seconds;
+use crate::export::{ExportFormat, render_export};
+use crate::model::{PasswdUnsafeMode, Session, format_duration};
+use crate::openssh::{self, ScpOptions};
 use crate::password;
+use crate::runner::CommandRunner;
 use crate::ssh::{AuthConfig, SshConnection};
-use crate::store::SessionStore;
+use crate::store::{
+    ConnectionKind, DEFAULT_PROFILE, SessionStore, StoreOpener, list_profiles, resolve_store_path,
+};
+use crate::ui::config::{
+    AUTO_COLUMN_MIN_WIDTH, ColumnWidth, EnterAction, IdentityDisplay, RowDensity,
+};
+use crate::ui::probe::ReachabilityProber;
 use crate::ui::state::{
-    AddField, AddSessionForm, AppState, FormEditMode, InputMode, MonitorEntry, ScpDirection,
-    ScpField, ScpForm,
+    AddField, AddSessionForm, AppState, FormEditMode, InputMode, MonitorEntry, Panel,
+    PanelVisibility, ReachabilityFilter, ScpDirection, ScpField, ScpForm, SessionAction,
 };
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use crossterm::cursor::{Hide, Show};
 use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent};
 use crossterm::execute;
@@ -22,17 +36,19 @@ use crossterm::terminal::{
 };
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Constraint, Direction, Flex, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::io;
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub use config::{ThemeConfig, UiConfig, load_ui_config};
 pub use highlight::SessionHighlight;
@@ -50,25 +66,70 @@ struct Theme {
 
 const PAGE_STEP: usize = 5;
 const FIELD_LABEL_WIDTH: usize = 10;
+const IDENTITY_COLUMN_WIDTH: usize = 18;
 const CARET_BLINK_MS: u128 = 600;
 const NAVIGATION_NOTES: &str = "↑/↓ or j/k | gg/G | / search | ? for help";
-const HELP_PANEL_LINES: &[&str] = &[
-    "?             Toggle this help panel",
-    "Enter         Connect to selected session",
-    "o / O         Add session form",
-    "e             Edit selected session",
-    "dd            Delete selected session (confirm name)",
-    "yy            Yank selected session",
-    "p             Paste yanked session as a new draft",
-    "/             Search (type to filter)",
-    "s             Open SCP form",
-    "m             Toggle monitor view",
-    "Ctrl-d / Ctrl-u  Page down/up",
-    "j / k / ↑ / ↓  Move selection",
-    "gg / G        Jump top or bottom",
-    "Esc           Close help, cancel, or dismiss error details",
+/// Key bindings shown in the help overlay, grouped under headings.
+const HELP_SECTIONS: &[(&str, &[&str])] = &[
+    (
+        "Navigation",
+        &[
+            "j / k / ↑ / ↓  Move selection",
+            "Ctrl-d / Ctrl-u  Page down/up",
+            "gg / G        Jump top or bottom",
+            "1-9           Jump to the Nth most recently connected session",
+            "/             Search (type to filter)",
+            "r             Cycle reachable/unreachable/all filter",
+            "S             Cycle sort order (latest first/most used/name)",
+            "P             Switch profile (reloads sessions from its store)",
+            "T             Clear the tag filter (se tui --tag)",
+        ],
+    ),
+    (
+        "Sessions",
+        &[
+            "Enter         Connect, or open the action menu (input.enter_action)",
+            "Space         Action menu for the selected session",
+            "L             Reconnect to the most recently connected session",
+            "o / O         Add session form",
+            "e             Edit selected session",
+            "c             Clone selected session into a new add form",
+            "dd            Delete selected session (confirm name)",
+            "yy            Yank selected session",
+            "p             Paste yanked session as a new draft",
+            "i             Show session details (connection durations)",
+            "s             Open SCP form",
+        ],
+    ),
+    (
+        "Files and clipboard",
+        &[
+            "yh/yt/yi      Copy host / user@host / identity path to clipboard",
+            "E             Open the identity file's directory (asks first)",
+            "X             Export selection: mark sessions, then write or copy them",
+        ],
+    ),
+    (
+        "View",
+        &[
+            "m             Toggle monitor view",
+            "zl/zs/zt/zh   Toggle logo / search bar / status line / help",
+            "v             Toggle compact/full rows",
+        ],
+    ),
+    (
+        "This panel",
+        &[
+            "j / k         Scroll (Ctrl-d / Ctrl-u by page, g / G top or bottom)",
+            "q             Quit",
+            "Esc / ?       Close (so does any other key)",
+        ],
+    ),
 ];
 
+/// Rows the help overlay scrolls by for Ctrl-d / Ctrl-u.
+const HELP_PAGE_LINES: usize = 10;
+
 struct PopupCursor {
     line: u16,
     column: u16,
@@ -82,6 +143,8 @@ struct PopupPanel<'a> {
     height_percent: u16,
     cursor: Option<PopupCursor>,
     wrap: bool,
+    /// Body lines scrolled off the top
+    scroll: u16,
 }
 
 enum TextEntryAction {
@@ -104,18 +167,101 @@ impl Theme {
     }
 }
 
-pub fn run_tui(store: &dyn SessionStore, config: &UiConfig) -> Result<Option<Session>> {
-    let mut sessions = store.list()?;
+/// Startup options for the TUI that come from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct TuiOptions {
+    /// Initial search text, as if typed after `/`.
+    pub filter: Option<String>,
+    /// Show only sessions carrying exactly this tag until cleared with `T`.
+    pub tag: Option<String>,
+    /// Start with this session selected instead of the remembered one.
+    pub select: Option<String>,
+    /// Store that profiles live next to, enabling the `P` profile picker. `None` when
+    /// sessions do not come from a file (`SSHER_SESSIONS`).
+    pub profile_base: Option<PathBuf>,
+    /// Profile of the store passed to `run_tui`; `None` means the default one.
+    pub profile: Option<String>,
+    /// Opens the store of a profile picked with `P`, the same way the CLI does, but
+    /// through [`StoreOpener::for_tui`]: it never prompts and never encrypts a
+    /// plaintext profile.
+    pub store_opener: StoreOpener,
+}
 
-    // Apply ordering based on config
-    sort_sessions(&mut sessions, config.ordering.mode);
+/// The session picked with Enter, and the store it belongs to.
+#[derive(Debug)]
+pub struct TuiSelection {
+    pub session: Session,
+    /// Store of the profile switched to with `P`; `None` if the TUI stayed on the
+    /// store it was started with.
+    pub store_path: Option<PathBuf>,
+}
+
+pub fn run_tui(
+    store: &dyn SessionStore,
+    runner: &dyn CommandRunner,
+    config: &UiConfig,
+    parallel: usize,
+    options: &TuiOptions,
+) -> Result<Option<TuiSelection>> {
+    let sessions = store.list()?;
 
     let mut app = AppState::new(&sessions);
+    // Start in the configured order; S cycles it at runtime
+    app.set_order_mode(config.ordering.mode, now_epoch_seconds());
+    app.set_profile_context(
+        options.profile_base.clone(),
+        options.profile.as_deref().unwrap_or(DEFAULT_PROFILE),
+    );
     app.set_monitor_enabled(config.layout.show_monitor);
+    app.set_monitor_interval(Duration::from_secs(config.layout.monitor_refresh_secs));
+    app.set_panels(PanelVisibility {
+        logo: config.layout.show_logo,
+        search: config.layout.show_search,
+        status: config.layout.show_status,
+        help: config.layout.show_help,
+    });
+    app.set_row_density(config.layout.row_density);
+    app.set_confirm_discard(config.input.confirm_discard);
+    app.set_enter_action(config.input.enter_action);
+    if let Some(filter) = &options.filter {
+        app.set_filter(filter.as_str());
+    }
+    if options.tag.is_some() {
+        app.set_tag_filter(options.tag.clone());
+    }
+    // Restore after filtering so a remembered session outside the filter is not selected.
+    let selection_path = last_selection::resolve_last_selection_path().ok();
+    if let Some(name) = &options.select {
+        app.select_requested(name);
+    } else if let Some(name) = selection_path
+        .as_deref()
+        .and_then(last_selection::load_last_selection)
+    {
+        app.select_by_name(&name);
+    }
     app.set_form_default_mode(match config.input.form_default_mode {
         config::FormStartMode::Normal => FormEditMode::Normal,
         config::FormStartMode::Insert => FormEditMode::Insert,
     });
+    let form_fields: Vec<AddField> = config
+        .input
+        .form_fields
+        .iter()
+        .map(|field| match field {
+            config::FormField::Name => AddField::Name,
+            config::FormField::Host => AddField::Host,
+            config::FormField::User => AddField::User,
+            config::FormField::Port => AddField::Port,
+            config::FormField::Identity => AddField::Identity,
+            config::FormField::ProxyJump => AddField::ProxyJump,
+            config::FormField::ExtraArgs => AddField::ExtraArgs,
+            config::FormField::LocalForwards => AddField::LocalForwards,
+            config::FormField::RemoteForwards => AddField::RemoteForwards,
+            config::FormField::Password => AddField::Password,
+            config::FormField::Tags => AddField::Tags,
+        })
+        .collect();
+    app.set_form_fields(&form_fields);
     let theme = Theme::from_config(config);
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -123,7 +269,21 @@ pub fn run_tui(store: &dyn SessionStore, config: &UiConfig) -> Result<Option<Ses
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run_app(&mut terminal, &mut app, store, config, &theme);
+    let context = AppContext {
+        store,
+        runner,
+        opener: options.store_opener.for_tui(),
+        parallel,
+    };
+    let result = run_app(&mut terminal, &mut app, &context, config, &theme);
+
+    // Remembering the selection is a convenience; never fail the TUI over it.
+    if let Some(path) = selection_path.as_deref() {
+        let _ = last_selection::save_last_selection(
+            path,
+            app.selected_session().map(|session| session.name.as_str()),
+        );
+    }
 
     disable_raw_mode()?;
     execute!(
@@ -137,21 +297,50 @@ pub fn run_tui(store: &dyn SessionStore, config: &UiConfig) -> Result<Option<Ses
     result
 }
 
+/// What the event loop works with besides the terminal, the app state and the look.
+struct AppContext<'a> {
+    store: &'a dyn SessionStore,
+    runner: &'a dyn CommandRunner,
+    /// Opens the store of a profile picked with `P`
+    opener: StoreOpener,
+    /// Concurrent reachability probes
+    parallel: usize,
+}
+
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut AppState,
-    store: &dyn SessionStore,
+    context: &AppContext,
     config: &UiConfig,
     theme: &Theme,
-) -> Result<Option<Session>> {
+) -> Result<Option<TuiSelection>> {
+    let mut prober = ReachabilityProber::new(context.parallel);
+    // Store of the profile picked with `P`, replacing `store` from then on
+    let mut switched: Option<(Box<dyn SessionStore>, PathBuf)> = None;
     loop {
+        if app.reachability_filter() != ReachabilityFilter::All {
+            prober.request(app.probe_candidates());
+        }
+        for (name, reachable) in prober.drain() {
+            app.set_reachability(&name, reachable);
+        }
+
         terminal.draw(|frame| draw_ui(frame, app, config, theme))?;
 
         if event::poll(Duration::from_millis(200))?
             && let Event::Key(key) = event::read()?
         {
-            match handle_key(app, store, key) {
-                Ok(Some(selection)) => return Ok(selection),
+            let active: &dyn SessionStore = match &switched {
+                Some((profile_store, _)) => profile_store.as_ref(),
+                None => context.store,
+            };
+            match handle_key(app, active, context.runner, key) {
+                Ok(Some(selection)) => {
+                    return Ok(selection.map(|session| TuiSelection {
+                        session,
+                        store_path: switched.map(|(_, path)| path),
+                    }));
+                }
                 Ok(None) => {}
                 Err(err) => show_error_popup(
                     app,
@@ -159,13 +348,47 @@ fn run_app<B: ratatui::backend::Backend>(
                     format!("{err:#}"),
                 ),
             }
+            if let Some(profile) = app.take_profile_request() {
+                match switch_profile(app, &profile, &context.opener) {
+                    Ok(next) => switched = Some(next),
+                    Err(err) => show_error_popup(
+                        app,
+                        "Could not switch profile (Esc closes error details)",
+                        format!("{err:#}"),
+                    ),
+                }
+            }
         }
     }
 }
 
+/// Load `profile`'s store into `app`, returning the store to use from now on.
+fn switch_profile(
+    app: &mut AppState,
+    profile: &str,
+    opener: &StoreOpener,
+) -> Result<(Box<dyn SessionStore>, PathBuf)> {
+    let base = app
+        .profile_base()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| anyhow!("profiles are unavailable for this store"))?;
+    let path = resolve_store_path(Some(base), Some(profile))?;
+    let store = opener.open(path.clone())?;
+    let sessions = store.list()?;
+    let count = sessions.len();
+    app.replace_sessions(sessions, now_epoch_seconds());
+    app.set_active_profile(profile);
+    app.set_status(format!(
+        "Switched to profile '{}' ({} sessions)",
+        profile, count
+    ));
+    Ok((store, path))
+}
+
 fn handle_key(
     app: &mut AppState,
     store: &dyn SessionStore,
+    runner: &dyn CommandRunner,
     key: KeyEvent,
 ) -> Result<Option<Option<Session>>> {
     if key.modifiers.contains(event::KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
@@ -179,6 +402,21 @@ fn handle_key(
         return Ok(None);
     }
 
+    if app.discard_prompt() {
+        if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+            if app.mode() == InputMode::Scp {
+                app.cancel_scp();
+            } else {
+                app.cancel_add_session();
+            }
+            app.set_status("Discarded unsaved changes");
+        } else {
+            app.dismiss_discard_prompt();
+            app.clear_status();
+        }
+        return Ok(None);
+    }
+
     match app.mode() {
         InputMode::Normal => handle_normal_key(app, key),
         InputMode::Search => handle_search_key(app, key),
@@ -186,7 +424,19 @@ fn handle_key(
         InputMode::AddSession => handle_add_session_key(app, store, key),
         InputMode::EditSession => handle_edit_session_key(app, store, key),
         InputMode::Help => handle_help_key(app, key),
-        InputMode::Scp => handle_scp_key(app, store, key),
+        InputMode::Scp => handle_scp_key(app, store, runner, key),
+        InputMode::ExportSelect => handle_export_select_key(app, key),
+        InputMode::Details => handle_details_key(app, key),
+        InputMode::ProfilePicker => handle_profile_picker_key(app, key),
+        InputMode::ActionMenu => handle_action_menu_key(app, key),
+    }
+}
+
+fn discard_prompt_text(mode: InputMode) -> &'static str {
+    match mode {
+        InputMode::EditSession => "Discard changes to this session? y/N",
+        InputMode::Scp => "Discard this SCP transfer? y/N",
+        _ => "Discard new session? y/N",
     }
 }
 
@@ -217,11 +467,56 @@ fn handle_text_entry_key(
     }
 }
 
+/// Value copied by `yh`/`yt`/`yi`, with a label for the status line.
+fn copy_field_value(session: &Session, key: char) -> Option<(&'static str, String)> {
+    match key {
+        'h' => Some(("host", session.host.clone())),
+        't' => Some(("target", session.target())),
+        'i' => session
+            .identity_file
+            .as_ref()
+            .map(|path| ("identity", path.display().to_string())),
+        _ => None,
+    }
+}
+
+fn copy_selected_field(app: &mut AppState, key: char) {
+    let Some(session) = app.selected_session().cloned() else {
+        app.set_status("No session selected to copy");
+        return;
+    };
+    let Some((label, value)) = copy_field_value(&session, key) else {
+        app.set_status(format!("Session {} has no identity file", session.name));
+        return;
+    };
+    match clipboard::copy_to_clipboard(&value) {
+        Ok(_) => app.set_status(format!("Copied {}: {}", label, value)),
+        Err(_) => app.set_status(format!("No clipboard available; {}: {}", label, value)),
+    }
+}
+
 fn handle_normal_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<Session>>> {
+    if app.pending() == Some('E') {
+        app.set_pending(None);
+        if key.code == KeyCode::Char('y') {
+            open_identity_dir(app);
+        } else {
+            app.set_status("Cancelled");
+        }
+        return Ok(None);
+    }
+
     let mut handled = true;
     match key.code {
         KeyCode::Char('q') => return Ok(Some(None)),
-        KeyCode::Enter => return Ok(Some(app.selected_session().cloned())),
+        KeyCode::Enter => match app.enter_action() {
+            EnterAction::Connect => return run_session_action(app, SessionAction::Connect),
+            EnterAction::Menu => {
+                if !app.open_action_menu() {
+                    app.set_status("No session selected");
+                }
+            }
+        },
         KeyCode::Char('d') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
             app.page_down(PAGE_STEP)
         }
@@ -230,16 +525,30 @@ fn handle_normal_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<
         }
         KeyCode::Char('d') => {
             if app.pending() == Some('d') {
-                if app.start_delete() {
-                    app.set_status("Type session name to confirm deletion");
-                } else {
-                    app.set_status("No session selected to delete");
-                }
                 app.set_pending(None);
+                return run_session_action(app, SessionAction::Delete);
             } else {
                 app.set_pending(Some('d'));
             }
         }
+        KeyCode::Char(key @ ('l' | 's' | 't' | 'h')) if app.pending() == Some('z') => {
+            let panel = match key {
+                'l' => Panel::Logo,
+                's' => Panel::Search,
+                't' => Panel::Status,
+                _ => Panel::Help,
+            };
+            let shown = app.toggle_panel(panel);
+            app.set_status(format!(
+                "{} {}",
+                if shown { "Showing" } else { "Hiding" },
+                panel.label()
+            ));
+        }
+        KeyCode::Char('z') => app.set_pending(Some('z')),
+        KeyCode::Char(key @ ('h' | 't' | 'i')) if app.pending() == Some('y') => {
+            copy_selected_field(app, key);
+        }
         KeyCode::Char('y') => {
             if app.pending() == Some('y') {
                 if let Some(name) = app.yank_selected() {
@@ -274,28 +583,83 @@ fn handle_normal_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<
             app.start_add_session(default_user());
             set_form_mode_status(app, "Add");
         }
-        KeyCode::Char('e') => {
-            if let Some(session) = app.selected_session().cloned() {
-                app.start_edit_session(&session);
-                set_form_mode_status(app, "Edit");
+        KeyCode::Char('L') => match app.jump_to_recent(1) {
+            Some(_) => return Ok(Some(app.selected_session().cloned())),
+            None => app.set_status("No recently connected session to reconnect to"),
+        },
+        KeyCode::Char('c') => {
+            if app.start_clone_session() {
+                set_form_mode_status(app, "Clone");
             } else {
-                app.set_status("No session selected to edit");
+                app.set_status("No session selected to clone");
             }
         }
-        KeyCode::Char('s') => {
-            if let Some(session) = app.selected_session().cloned() {
-                app.start_scp(session);
-                app.set_status("SCP: Enter/Tab move fields, Esc cancel");
-            } else {
-                app.set_status("No session selected for SCP");
-            }
+        KeyCode::Char('e') => return run_session_action(app, SessionAction::Edit),
+        KeyCode::Char('s') => return run_session_action(app, SessionAction::Scp),
+        KeyCode::Char('X') => {
+            app.start_export_select();
+            app.set_status("Export: Space mark, a mark all, Enter choose format/path, Esc cancel");
         }
         KeyCode::Char('m') => app.toggle_monitor(),
+        KeyCode::Char('r') => {
+            let filter = app.cycle_reachability_filter();
+            app.set_status(format!("Showing {}", filter.label()));
+        }
+        KeyCode::Char('P') => match app.profile_base().map(list_profiles) {
+            Some(profiles) => {
+                let active = app.active_profile().to_string();
+                app.open_profile_picker(profiles, &active);
+            }
+            None => {
+                app.set_status("Profiles are unavailable while sessions come from SSHER_SESSIONS")
+            }
+        },
+        KeyCode::Char('S') => {
+            let mode = app.cycle_order_mode(now_epoch_seconds());
+            app.set_status(format!("Sorted by {}", mode.label()));
+        }
+        KeyCode::Char('E') => match app
+            .selected_session()
+            .map(|s| (s.identity_dir(), s.name.clone()))
+        {
+            Some((Some(dir), _)) => {
+                app.set_status(format!(
+                    "Open {} in the file manager? (y to confirm)",
+                    dir.display()
+                ));
+                app.set_pending(Some('E'));
+            }
+            Some((None, name)) => app.set_status(format!("{} has no identity file", name)),
+            None => app.set_status("No session selected"),
+        },
+        KeyCode::Char('i') => return run_session_action(app, SessionAction::Inspect),
+        KeyCode::Char(' ') => {
+            if !app.open_action_menu() {
+                app.set_status("No session selected");
+            }
+        }
+        KeyCode::Char('T') => {
+            if app.tag_filter().is_some() {
+                app.set_tag_filter(None);
+                app.set_status("Tag filter cleared");
+            }
+        }
+        KeyCode::Char('v') => {
+            let density = app.toggle_row_density();
+            app.set_status(format!("Row density: {}", density.label()));
+        }
         KeyCode::Up | KeyCode::Char('k') => app.move_prev(),
         KeyCode::Down | KeyCode::Char('j') => app.move_next(),
         KeyCode::Home => app.select_first(),
         KeyCode::End => app.select_last(),
         KeyCode::Char('G') => app.select_last(),
+        KeyCode::Char(digit @ '1'..='9') => {
+            let position = digit as usize - '0' as usize;
+            match app.jump_to_recent(position) {
+                Some(name) => app.set_status(format!("Jumped to {}", name)),
+                None => app.set_status(format!("No recent session #{}", position)),
+            }
+        }
         KeyCode::Char('g') => {
             if app.pending() == Some('g') {
                 app.select_first();
@@ -310,9 +674,7 @@ fn handle_normal_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<
             app.set_mode(InputMode::Search);
             app.set_status("Search mode: type to filter, Enter/Esc to exit");
         }
-        KeyCode::Char('?') => {
-            app.set_mode(InputMode::Help);
-        }
+        KeyCode::Char('?') => app.open_help(),
         KeyCode::Char('n') => app.move_next(),
         KeyCode::Char('N') => app.move_prev(),
         KeyCode::Esc => {
@@ -324,7 +686,11 @@ fn handle_normal_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<
 
     if handled {
         match key.code {
-            KeyCode::Char('g') | KeyCode::Char('d') | KeyCode::Char('y') => {}
+            KeyCode::Char('g')
+            | KeyCode::Char('d')
+            | KeyCode::Char('y')
+            | KeyCode::Char('z')
+            | KeyCode::Char('E') => {}
             _ => app.set_pending(None),
         }
     } else {
@@ -334,6 +700,22 @@ fn handle_normal_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<
     Ok(None)
 }
 
+/// Show the selected session's key directory in the file manager. Only the directory
+/// is opened, never the private key itself.
+fn open_identity_dir(app: &mut AppState) {
+    let Some(dir) = app.selected_session().and_then(Session::identity_dir) else {
+        return;
+    };
+    if !dir.is_dir() {
+        app.set_status(format!("{} does not exist", dir.display()));
+        return;
+    }
+    match file_manager::open_directory(&dir) {
+        Ok(program) => app.set_status(format!("Opened {} with {}", dir.display(), program)),
+        Err(err) => app.set_status(format!("Could not open {}: {}", dir.display(), err)),
+    }
+}
+
 fn handle_search_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<Session>>> {
     match key.code {
         KeyCode::Esc | KeyCode::Enter => {
@@ -355,17 +737,206 @@ fn handle_search_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<
 }
 
 fn handle_help_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<Session>>> {
+    let ctrl = key.modifiers.contains(event::KeyModifiers::CONTROL);
     match key.code {
-        KeyCode::Char('?') | KeyCode::Esc => {
+        KeyCode::Char('j') | KeyCode::Down => app.scroll_help_down(1),
+        KeyCode::Char('k') | KeyCode::Up => app.scroll_help_up(1),
+        KeyCode::Char('d') if ctrl => app.scroll_help_down(HELP_PAGE_LINES),
+        KeyCode::Char('u') if ctrl => app.scroll_help_up(HELP_PAGE_LINES),
+        KeyCode::PageDown => app.scroll_help_down(HELP_PAGE_LINES),
+        KeyCode::PageUp => app.scroll_help_up(HELP_PAGE_LINES),
+        KeyCode::Char('g') | KeyCode::Home => app.scroll_help_up(usize::MAX),
+        // Clamped to the last line when the overlay is drawn.
+        KeyCode::Char('G') | KeyCode::End => app.scroll_help_down(usize::MAX),
+        KeyCode::Char('q') => return Ok(Some(None)),
+        _ => {
             app.set_mode(InputMode::Normal);
             app.set_pending(None);
         }
+    }
+    Ok(None)
+}
+
+fn handle_details_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<Session>>> {
+    match key.code {
+        KeyCode::Char('i') | KeyCode::Esc => app.set_mode(InputMode::Normal),
+        KeyCode::Char('q') => return Ok(Some(None)),
+        _ => {}
+    }
+    Ok(None)
+}
+
+fn handle_profile_picker_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<Session>>> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => app.move_profile_selection(true),
+        KeyCode::Char('k') | KeyCode::Up => app.move_profile_selection(false),
+        KeyCode::Enter => app.choose_profile(),
+        KeyCode::Esc | KeyCode::Char('P') => app.close_profile_picker(),
+        KeyCode::Char('q') => return Ok(Some(None)),
+        _ => {}
+    }
+    Ok(None)
+}
+
+fn handle_action_menu_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<Session>>> {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => app.move_action_selection(true),
+        KeyCode::Char('k') | KeyCode::Up => app.move_action_selection(false),
+        KeyCode::Enter => {
+            if let Some(action) = app.choose_action() {
+                return run_session_action(app, action);
+            }
+        }
+        KeyCode::Esc | KeyCode::Char(' ') => app.close_action_menu(),
         KeyCode::Char('q') => return Ok(Some(None)),
         _ => {}
     }
     Ok(None)
 }
 
+/// Run `action` on the selected session, the same way its Normal-mode key does.
+fn run_session_action(
+    app: &mut AppState,
+    action: SessionAction,
+) -> Result<Option<Option<Session>>> {
+    match action {
+        SessionAction::Connect => match app.selected_session() {
+            Some(session) => return Ok(Some(Some(session.clone()))),
+            None => app.set_status("No session selected"),
+        },
+        SessionAction::Scp => {
+            if app.start_scp() {
+                app.set_status("SCP: Enter/Tab move fields, Esc cancel");
+            } else {
+                app.set_status("No session selected for SCP");
+            }
+        }
+        SessionAction::Edit => {
+            if let Some(session) = app.selected_session().cloned() {
+                app.start_edit_session(&session);
+                set_form_mode_status(app, "Edit");
+            } else {
+                app.set_status("No session selected to edit");
+            }
+        }
+        SessionAction::Delete => {
+            if app.start_delete() {
+                app.set_status("Type session name to confirm deletion");
+            } else {
+                app.set_status("No session selected to delete");
+            }
+        }
+        SessionAction::CopyTarget => copy_selected_field(app, 't'),
+        SessionAction::CopyCommand => match app.selected_session() {
+            Some(session) => {
+                let line = openssh::command_line(&openssh::ssh_command(session));
+                match clipboard::copy_to_clipboard(&line) {
+                    Ok(_) => app.set_status(format!("Copied command: {}", line)),
+                    Err(_) => app.set_status(format!("No clipboard available; command: {}", line)),
+                }
+            }
+            None => app.set_status("No session selected to copy"),
+        },
+        SessionAction::Inspect => {
+            if app.selected_session().is_some() {
+                app.set_mode(InputMode::Details);
+            } else {
+                app.set_status("No session selected");
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn handle_export_select_key(app: &mut AppState, key: KeyEvent) -> Result<Option<Option<Session>>> {
+    if let Some(prompt) = app.export_prompt_mut() {
+        if key.code == KeyCode::Tab {
+            prompt.cycle_format();
+            return Ok(None);
+        }
+        match handle_text_entry_key(prompt.entry_mut(), key) {
+            TextEntryAction::Cancel => app.close_export_prompt(),
+            TextEntryAction::Submit => {
+                let format = prompt.format();
+                let path = prompt.entry().value().trim().to_string();
+                submit_export_selection(app, format, &path);
+            }
+            TextEntryAction::Continue => {}
+        }
+        return Ok(None);
+    }
+
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel_export_select();
+            app.set_status("Export cancelled");
+        }
+        KeyCode::Char(' ') => {
+            if app.toggle_mark_selected().is_some() {
+                app.move_next();
+            }
+            app.set_status(format!("{} sessions marked", app.marked_count()));
+        }
+        KeyCode::Char('a') => {
+            app.mark_all_visible();
+            app.set_status(format!("{} sessions marked", app.marked_count()));
+        }
+        KeyCode::Enter if !app.open_export_prompt() => {
+            app.set_status("Mark at least one session with Space");
+        }
+        KeyCode::Up | KeyCode::Char('k') => app.move_prev(),
+        KeyCode::Down | KeyCode::Char('j') => app.move_next(),
+        KeyCode::Home | KeyCode::Char('g') => app.select_first(),
+        KeyCode::End | KeyCode::Char('G') => app.select_last(),
+        _ => {}
+    }
+    Ok(None)
+}
+
+/// Write the marked sessions to `path`, or copy them to the clipboard when `path` is empty.
+fn submit_export_selection(app: &mut AppState, format: ExportFormat, path: &str) {
+    let sessions = app.marked_sessions();
+    let content = match render_export(&sessions, format) {
+        Ok(content) => content,
+        Err(err) => {
+            show_error_popup(app, "Export failed", format!("{:#}", err));
+            return;
+        }
+    };
+
+    if path.is_empty() {
+        match clipboard::copy_to_clipboard(&content) {
+            Ok(_) => {
+                app.cancel_export_select();
+                app.set_status(format!(
+                    "Copied {} sessions as {} to the clipboard",
+                    sessions.len(),
+                    format.label()
+                ));
+            }
+            Err(_) => app.set_status("No clipboard available; enter an output path"),
+        }
+        return;
+    }
+
+    let target = PathBuf::from(expand_tilde(path));
+    if target.exists() {
+        app.set_status(format!("{} already exists; choose another path", path));
+        return;
+    }
+    match fs::write(&target, content) {
+        Ok(()) => {
+            app.cancel_export_select();
+            app.set_status(format!("Exported {} sessions to {}", sessions.len(), path));
+        }
+        Err(err) => show_error_popup(
+            app,
+            "Export failed",
+            format!("failed to write {}: {}", target.display(), err),
+        ),
+    }
+}
+
 fn handle_confirm_delete_key(
     app: &mut AppState,
     store: &dyn SessionStore,
@@ -411,8 +982,12 @@ fn handle_add_session_key(
     match form.edit_mode() {
         FormEditMode::Normal => match key.code {
             KeyCode::Esc => {
-                app.cancel_add_session();
-                app.clear_status();
+                if app.request_discard() {
+                    app.set_status(discard_prompt_text(app.mode()));
+                } else {
+                    app.cancel_add_session();
+                    app.clear_status();
+                }
             }
             KeyCode::Tab | KeyCode::Down | KeyCode::Char('j') => form.next_field(),
             KeyCode::BackTab | KeyCode::Up | KeyCode::Char('k') => form.prev_field(),
@@ -508,8 +1083,12 @@ fn handle_edit_session_key(
     match form.edit_mode() {
         FormEditMode::Normal => match key.code {
             KeyCode::Esc => {
-                app.cancel_add_session();
-                app.clear_status();
+                if app.request_discard() {
+                    app.set_status(discard_prompt_text(app.mode()));
+                } else {
+                    app.cancel_add_session();
+                    app.clear_status();
+                }
             }
             KeyCode::Tab | KeyCode::Down | KeyCode::Char('j') => form.next_field(),
             KeyCode::BackTab | KeyCode::Up | KeyCode::Char('k') => form.prev_field(),
@@ -595,6 +1174,7 @@ fn handle_edit_session_key(
 fn handle_scp_key(
     app: &mut AppState,
     store: &dyn SessionStore,
+    runner: &dyn CommandRunner,
     key: KeyEvent,
 ) -> Result<Option<Option<Session>>> {
     let mut submit_password = false;
@@ -622,8 +1202,12 @@ fn handle_scp_key(
 
             match key.code {
                 KeyCode::Esc => {
-                    app.cancel_scp();
-                    app.clear_status();
+                    if app.request_discard() {
+                        app.set_status(discard_prompt_text(app.mode()));
+                    } else {
+                        app.cancel_scp();
+                        app.clear_status();
+                    }
                 }
                 KeyCode::Tab => {
                     if matches!(field, ScpField::Local | ScpField::Remote)
@@ -642,8 +1226,8 @@ fn handle_scp_key(
                         matches!(form.field(), ScpField::Local | ScpField::Remote);
                 }
                 KeyCode::Enter => {
-                    if field == ScpField::Recursive {
-                        submit_scp(app, store)?;
+                    if field.is_toggle() {
+                        submit_scp(app, store, runner)?;
                     } else {
                         form.next_field();
                         refresh_autocomplete =
@@ -661,10 +1245,11 @@ fn handle_scp_key(
                 {
                     form.toggle_direction();
                 }
-                KeyCode::Left | KeyCode::Right | KeyCode::Char('r') | KeyCode::Char(' ')
-                    if field == ScpField::Recursive =>
-                {
-                    form.toggle_recursive();
+                KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') if field.is_toggle() => {
+                    form.toggle_active();
+                }
+                KeyCode::Char('r') if field == ScpField::Recursive => {
+                    form.toggle_active();
                 }
                 KeyCode::Backspace => {
                     if let Some(value) = form.active_editable_mut() {
@@ -691,36 +1276,48 @@ fn handle_scp_key(
     }
 
     if submit_password {
-        submit_scp(app, store)?;
+        submit_scp(app, store, runner)?;
     }
 
     Ok(None)
 }
 
 fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, theme: &Theme) {
+    let now = now_epoch_seconds();
     let show_inline_caret = inline_caret_visible();
     let size = frame.area();
     let mut constraints = Vec::new();
     let mut logo_index = None;
     let mut search_index = None;
     let mut cheat_index = None;
+    let panels = app.panels();
 
-    if config.layout.show_logo && config.logo.enabled {
+    if panels.logo && config.logo.enabled {
         logo_index = Some(constraints.len());
         constraints.push(Constraint::Length(config.layout.logo_height));
     }
-    if config.layout.show_search {
+    if panels.search {
         search_index = Some(constraints.len());
         constraints.push(Constraint::Length(config.layout.search_height));
     }
     let sessions_index = constraints.len();
     constraints.push(Constraint::Min(3));
-    if config.layout.show_status || config.layout.show_help {
+    if panels.status || panels.help {
         cheat_index = Some(constraints.len());
         let mut bar_height = config.layout.help_height;
-        if config.layout.show_status {
+        if panels.status {
             bar_height = bar_height.saturating_add(config.layout.status_height);
         }
+        // Grow the bar so wrapped help lines stay visible on narrow terminals.
+        let inner_width = size.width.saturating_sub(2);
+        let mut needed = 2 + wrapped_line_count(&navigation_line(), inner_width);
+        if panels.status {
+            needed += 1;
+        }
+        if panels.help {
+            needed += wrapped_line_count(mode_help_text(app.mode()), inner_width);
+        }
+        bar_height = bar_height.max(needed.min(size.height / 3));
         constraints.push(Constraint::Length(bar_height));
     }
 
@@ -768,25 +1365,74 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
         (sessions_area, None)
     };
 
-    let header = Row::new(vec![
-        Cell::from("Name"),
-        Cell::from("Target"),
-        Cell::from("Port"),
-        Cell::from("Identity"),
-        Cell::from("Tags"),
-        Cell::from("Pwd"),
-    ])
-    .style(
+    let compact = app.row_density() == RowDensity::Compact;
+    let header_cells = if compact {
+        vec![Cell::from("Name"), Cell::from("Target")]
+    } else {
+        vec![
+            Cell::from("Name"),
+            Cell::from("Target"),
+            Cell::from("Port"),
+            Cell::from("Identity"),
+            Cell::from("Tags"),
+            Cell::from("Pwd"),
+            Cell::from("Conns"),
+        ]
+    };
+    let header = Row::new(header_cells).style(
         Style::default()
             .fg(theme.header)
             .add_modifier(Modifier::BOLD),
     );
 
+    let columns = &config.columns;
+    let identity_width = match columns.identity {
+        ColumnWidth::Fixed(width) => usize::from(width),
+        ColumnWidth::Auto => IDENTITY_COLUMN_WIDTH,
+    };
+    let widths: Vec<Constraint> = if compact {
+        vec![
+            column_constraint(columns.name),
+            column_constraint(ColumnWidth::Auto),
+        ]
+    } else {
+        columns
+            .widths()
+            .into_iter()
+            .map(|(_, width)| column_constraint(width))
+            .collect()
+    };
+    // Resolve the constraints the way the table will, inside its borders, so long
+    // values can be cut with a visible "…" instead of silently.
+    let cell_widths: Vec<usize> = Layout::horizontal(widths.clone())
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(Rect::new(0, 0, table_area.width.saturating_sub(2), 1))
+        .iter()
+        .map(|rect| usize::from(rect.width))
+        .collect();
+    let fit = |column: usize, text: String| Cell::from(truncate_cell(&text, cell_widths[column]));
+    let selecting = app.mode() == InputMode::ExportSelect;
     let rows = app.filtered_sessions().into_iter().map(|session| {
+        let highlight_style = get_session_highlight(session, config, theme, now);
+        let name = match (selecting, app.is_marked(&session.name)) {
+            (false, _) => session.name.clone(),
+            (true, true) => format!("[x] {}", session.name),
+            (true, false) => format!("[ ] {}", session.name),
+        };
+        if compact {
+            return Row::new(vec![fit(0, name), fit(1, session.target())]).style(highlight_style);
+        }
         let identity = session
             .identity_file
             .as_ref()
-            .map(|path| path.display().to_string())
+            .map(|path| {
+                format_identity(
+                    &path.display().to_string(),
+                    config.layout.identity_display,
+                    identity_width,
+                )
+            })
             .unwrap_or_else(|| "-".to_string());
         let tags = if session.tags.is_empty() {
             "-".to_string()
@@ -798,42 +1444,33 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
         } else {
             "-"
         };
-        let highlight_style = get_session_highlight(session, config, theme);
         Row::new(vec![
-            Cell::from(session.name.clone()),
-            Cell::from(session.target()),
+            fit(0, name),
+            fit(1, session.target()),
             Cell::from(session.port.to_string()),
-            Cell::from(identity),
-            Cell::from(tags),
+            fit(3, identity),
+            fit(4, tags),
             Cell::from(password_indicator),
+            Cell::from(session.connect_count.to_string()),
         ])
         .style(highlight_style)
     });
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(20),
-            Constraint::Length(30),
-            Constraint::Length(6),
-            Constraint::Length(18),
-            Constraint::Min(10),
-            Constraint::Length(5),
-        ],
-    )
-    .header(header)
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(theme.border))
-            .title("Sessions"),
-    )
-    .highlight_style(
-        Style::default()
-            .fg(theme.text)
-            .bg(theme.highlight)
-            .add_modifier(Modifier::BOLD),
-    );
+    let table = Table::new(rows, widths)
+        .flex(Flex::Start)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border))
+                .title(sessions_title(app)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(theme.text)
+                .bg(theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        );
 
     let mut state = TableState::default();
     if let Some(selected) = app.selected_index() {
@@ -850,9 +1487,9 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
             } else {
                 let connections = entries
                     .iter()
-                    .map(|entry| {
-                        let tty = entry.tty.as_deref().unwrap_or("?");
-                        format!("{}@{}", entry.pid, tty)
+                    .map(|entry| match &entry.tty {
+                        Some(tty) => format!("{} ({})", entry.pid, tty),
+                        None => entry.pid.to_string(),
                     })
                     .collect::<Vec<String>>()
                     .join(", ");
@@ -860,7 +1497,7 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
             };
             let last_text = format!(
                 "Last connected: {}",
-                format_last_connected(session.last_connected_at)
+                format_last_connected(session.last_connected_at, now)
             );
             format!("Host: {}\n{}\n{}", session.host, connection_text, last_text)
         } else {
@@ -878,7 +1515,7 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
 
     if let Some(index) = cheat_index {
         let mut lines = Vec::new();
-        if config.layout.show_status {
+        if panels.status {
             let total = app.filtered_sessions().len();
             let selected_session = app.selected_session().cloned();
             if let Some(session) = selected_session.as_ref() {
@@ -895,29 +1532,41 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
                 }
                 None => "not connected".to_string(),
             };
-            let mut status_line =
-                format!("Focus: Session | {} sessions | {}", total, connections_text);
+            let mut status_line = format!(
+                "Focus: Session | {} sessions | sort: {} | {}",
+                total,
+                app.order_mode().label(),
+                connections_text
+            );
+            if app.reachability_filter() != ReachabilityFilter::All {
+                status_line.push_str(" | ");
+                status_line.push_str(app.reachability_filter().label());
+            }
             if !app.status().is_empty() {
                 status_line.push_str(" | ");
                 status_line.push_str(app.status());
             }
             lines.push(Line::styled(status_line, Style::default().fg(theme.status)));
         }
-        if config.layout.show_help {
+        if panels.help {
             lines.push(Line::styled(
                 mode_help_text(app.mode()),
                 Style::default().fg(theme.help),
             ));
         }
-        let nav_line = format!("Navigation: {}", NAVIGATION_NOTES);
-        lines.push(Line::styled(nav_line, Style::default().fg(theme.help)));
+        lines.push(Line::styled(
+            navigation_line(),
+            Style::default().fg(theme.help),
+        ));
 
-        let info = Paragraph::new(Text::from(lines)).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme.border))
-                .title("Info"),
-        );
+        let info = Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border))
+                    .title("Info"),
+            );
         frame.render_widget(info, chunks[index]);
     }
 
@@ -937,6 +1586,25 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
         );
     }
 
+    if app.mode() == InputMode::ExportSelect
+        && let Some(prompt) = app.export_prompt()
+    {
+        render_popup_panel(
+            frame,
+            size,
+            theme,
+            build_text_entry_popup(
+                prompt.entry(),
+                &[
+                    format!("{} sessions marked", app.marked_count()),
+                    format!("Format: {} ([Tab] to change)", prompt.format().label()),
+                ],
+                60,
+                35,
+            ),
+        );
+    }
+
     if app.mode() == InputMode::AddSession
         && let Some(form) = app.add_form()
     {
@@ -952,6 +1620,7 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
                 height_percent: 50,
                 cursor: None,
                 wrap: false,
+                scroll: 0,
             },
         );
     }
@@ -971,6 +1640,7 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
                 height_percent: 50,
                 cursor: None,
                 wrap: false,
+                scroll: 0,
             },
         );
     }
@@ -1006,27 +1676,148 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
                     height_percent: 45,
                     cursor: None,
                     wrap: false,
+                    scroll: 0,
                 },
             );
         }
     }
 
+    if app.mode() == InputMode::Details
+        && let Some(session) = app.selected_session()
+    {
+        render_popup_panel(
+            frame,
+            size,
+            theme,
+            PopupPanel {
+                title: Line::from(format!("Session: {}", session.name)),
+                body_lines: session_detail_lines(session, now),
+                accent_lines: vec![],
+                width_percent: 60,
+                height_percent: 50,
+                cursor: None,
+                wrap: true,
+                scroll: 0,
+            },
+        );
+    }
+
+    if app.mode() == InputMode::ProfilePicker
+        && let Some(picker) = app.profile_picker()
+    {
+        let body_lines = picker
+            .profiles()
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let marker = if index == picker.selected() { ">" } else { " " };
+                let active = if name == app.active_profile() {
+                    " (active)"
+                } else {
+                    ""
+                };
+                format!("{} {}{}", marker, name, active)
+            })
+            .collect();
+        render_popup_panel(
+            frame,
+            size,
+            theme,
+            PopupPanel {
+                title: Line::from("Switch profile"),
+                body_lines,
+                accent_lines: vec![picker.selected()],
+                width_percent: 40,
+                height_percent: 40,
+                cursor: None,
+                wrap: false,
+                scroll: 0,
+            },
+        );
+    }
+
+    if app.mode() == InputMode::ActionMenu
+        && let Some(selected) = app.action_menu()
+    {
+        let body_lines = SessionAction::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, action)| {
+                let marker = if index == selected { ">" } else { " " };
+                format!("{} {:<18}{}", marker, action.label(), action.key_hint())
+            })
+            .collect();
+        let title = match app.selected_session() {
+            Some(session) => format!("Actions: {}", session.name),
+            None => "Actions".to_string(),
+        };
+        render_popup_panel(
+            frame,
+            size,
+            theme,
+            PopupPanel {
+                title: Line::from(title),
+                body_lines,
+                accent_lines: vec![selected],
+                width_percent: 40,
+                height_percent: 40,
+                cursor: None,
+                wrap: false,
+                scroll: 0,
+            },
+        );
+    }
+
     if app.mode() == InputMode::Help {
+        let (body_lines, accent_lines) = help_panel_lines();
+        let visible = centered_rect(70, 60, size).height.saturating_sub(2) as usize;
+        let overflow = body_lines.len().saturating_sub(visible);
+        app.clamp_help_scroll(overflow);
+        let title = if overflow > 0 {
+            format!(
+                "Help ({}-{} of {}, j/k to scroll)",
+                app.help_scroll() + 1,
+                app.help_scroll() + visible,
+                body_lines.len()
+            )
+        } else {
+            "Help".to_string()
+        };
         render_popup_panel(
             frame,
             size,
             theme,
             PopupPanel {
-                title: Line::from("Help"),
-                body_lines: HELP_PANEL_LINES
-                    .iter()
-                    .map(|line| (*line).to_string())
-                    .collect(),
-                accent_lines: (0..HELP_PANEL_LINES.len()).collect(),
+                title: Line::from(title),
+                body_lines,
+                accent_lines,
                 width_percent: 70,
                 height_percent: 60,
                 cursor: None,
                 wrap: false,
+                scroll: u16::try_from(app.help_scroll()).unwrap_or(u16::MAX),
+            },
+        );
+    }
+
+    if app.discard_prompt() {
+        render_popup_panel(
+            frame,
+            size,
+            theme,
+            PopupPanel {
+                title: Line::from("Discard"),
+                body_lines: vec![
+                    discard_prompt_text(app.mode()).to_string(),
+                    String::new(),
+                    "[y] Discard  [any other key] Keep editing".to_string(),
+                ],
+                accent_lines: vec![0],
+                width_percent: 50,
+                height_percent: 25,
+                cursor: None,
+                wrap: true,
+                scroll: 0,
             },
         );
     }
@@ -1050,14 +1841,69 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &mut AppState, config: &UiConfig, th
                 height_percent: 70,
                 cursor: None,
                 wrap: true,
+                scroll: 0,
             },
         );
     }
 }
 
-fn get_session_highlight(session: &Session, config: &UiConfig, _theme: &Theme) -> Style {
+/// Shorten an identity path for a `width`-column cell according to `mode`.
+/// Cut `text` to `width` terminal columns, ending in "…" when anything was dropped.
+/// Wide characters count double, so CJK names do not overflow into the next column.
+fn truncate_cell(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut kept = String::new();
+    let mut used = 0;
+    for ch in text.chars() {
+        let ch_width = ch.width().unwrap_or(0);
+        if used + ch_width + 1 > width {
+            break;
+        }
+        kept.push(ch);
+        used += ch_width;
+    }
+    if width > 0 {
+        kept.push('…');
+    }
+    kept
+}
+
+fn format_identity(path: &str, mode: IdentityDisplay, width: usize) -> String {
+    let basename = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    match mode {
+        IdentityDisplay::Full => path.to_string(),
+        IdentityDisplay::Basename => basename.to_string(),
+        IdentityDisplay::Middle => {
+            if path.chars().count() <= width {
+                return path.to_string();
+            }
+            let name_len = basename.chars().count();
+            if name_len + 2 > width {
+                let tail: String = basename
+                    .chars()
+                    .skip((name_len + 1).saturating_sub(width))
+                    .collect();
+                return format!("…{}", tail);
+            }
+            // Add parent directories from the end while they still fit after "…/".
+            let mut kept = basename.to_string();
+            for component in path.rsplit(['/', '\\']).skip(1) {
+                let candidate = format!("{}/{}", component, kept);
+                if component.is_empty() || candidate.chars().count() + 2 > width {
+                    break;
+                }
+                kept = candidate;
+            }
+            format!("…/{}", kept)
+        }
+    }
+}
+
+fn get_session_highlight(session: &Session, config: &UiConfig, _theme: &Theme, now: i64) -> Style {
     let highlight =
-        SessionHighlight::classify(session, config.ordering.lifetime.dying_threshold_days);
+        SessionHighlight::classify(session, config.ordering.lifetime.dying_threshold_days, now);
 
     let color = match highlight {
         SessionHighlight::Hot => parse_color(&config.highlights.hot),
@@ -1093,7 +1939,7 @@ fn parse_color(name: &str) -> Color {
 fn mode_help_text(mode: InputMode) -> &'static str {
     match mode {
         InputMode::Normal => {
-            "j/k move | gg top | G bottom | Ctrl-d/u page | / search | o/O add | e edit | s scp | m monitor | yy yank | p paste | dd delete | Enter connect | q quit"
+            "j/k move | gg top | G bottom | 1-9 recent | Ctrl-d/u page | / search | o/O add | c clone | e edit | s scp | i details | E key dir | m monitor | r reachable | v density | yy yank | yh/yt/yi copy | p paste | dd delete | Enter connect | L last | q quit"
         }
         InputMode::Search => "Type to filter | Enter/Esc to exit | j/k move",
         InputMode::ConfirmDelete => "Type name | Enter confirm | Esc cancel",
@@ -1104,10 +1950,43 @@ fn mode_help_text(mode: InputMode) -> &'static str {
             "NORMAL: i/a insert, h/l cursor, j/k field, Enter next/save, Esc cancel | INSERT: type, Backspace, Esc normal"
         }
         InputMode::Scp => "Tab/Enter next | Space toggle | Esc cancel",
-        InputMode::Help => "? or Esc close | Ctrl-c exit",
+        InputMode::Help => "j/k scroll | any other key close | q quit",
+        InputMode::Details => "i or Esc close | q quit",
+        InputMode::ProfilePicker => "j/k move | Enter switch | Esc cancel | q quit",
+        InputMode::ActionMenu => "j/k move | Enter run | Esc cancel | q quit",
+        InputMode::ExportSelect => {
+            "Space mark | a mark all | j/k move | Enter format/path | Tab format | Esc cancel"
+        }
     }
 }
 
+fn navigation_line() -> String {
+    format!("Navigation: {}", NAVIGATION_NOTES)
+}
+
+/// Number of rows `text` occupies when word-wrapped to `width` columns.
+fn wrapped_line_count(text: &str, width: u16) -> u16 {
+    let width = usize::from(width.max(1));
+    let mut rows = 1u16;
+    let mut current = 0usize;
+    for word in text.split_whitespace() {
+        let len = word.chars().count();
+        let needed = if current == 0 { len } else { current + 1 + len };
+        if needed <= width {
+            current = needed;
+        } else {
+            if current > 0 {
+                rows = rows.saturating_add(1);
+            }
+            // Words longer than the line are broken across rows.
+            let extra = len.saturating_sub(1) / width;
+            rows = rows.saturating_add(extra as u16);
+            current = len - extra * width;
+        }
+    }
+    rows
+}
+
 fn form_panel_title(panel: &str, mode: FormEditMode, theme: &Theme) -> Line<'static> {
     Line::from(vec![
         Span::styled(
@@ -1162,6 +2041,22 @@ fn set_form_mode_status(app: &mut AppState, panel: &str) {
     }
 }
 
+/// Help overlay body with a blank line between sections; returns the lines and the
+/// indexes of the section headings to accent.
+fn help_panel_lines() -> (Vec<String>, Vec<usize>) {
+    let mut lines = Vec::new();
+    let mut headings = Vec::new();
+    for (heading, bindings) in HELP_SECTIONS {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        headings.push(lines.len());
+        lines.push((*heading).to_string());
+        lines.extend(bindings.iter().map(|binding| format!("  {}", binding)));
+    }
+    (lines, headings)
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, rect: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -1214,6 +2109,7 @@ fn render_popup_panel(
     if panel.wrap {
         paragraph = paragraph.wrap(Wrap { trim: false });
     }
+    paragraph = paragraph.scroll((panel.scroll, 0));
     frame.render_widget(paragraph, modal_area);
 
     if let Some(cursor) = panel.cursor {
@@ -1262,6 +2158,7 @@ fn build_text_entry_popup(
             column: input_column,
         }),
         wrap: false,
+        scroll: 0,
     }
 }
 
@@ -1281,68 +2178,55 @@ fn build_add_form_lines(form: &AddSessionForm, show_inline_caret: bool) -> Vec<S
         PasswdUnsafeMode::Simple => "simple (XOR)",
     };
 
-    let mut lines = vec![
-        field_line(
-            "Name",
-            &form.name,
-            caret_for(form, AddField::Name, active_caret),
-        ),
-        field_line(
-            "Host",
-            &form.host,
-            caret_for(form, AddField::Host, active_caret),
-        ),
-        field_line(
-            "User",
-            &form.user,
-            caret_for(form, AddField::User, active_caret),
-        ),
-        field_line(
-            "Port",
-            &form.port,
-            caret_for(form, AddField::Port, active_caret),
-        ),
-        field_line(
-            "Identity",
-            &form.identity_file,
-            caret_for(form, AddField::Identity, active_caret),
-        ),
-        field_line(
-            "Password",
-            &password_display,
-            caret_for(form, AddField::Password, active_caret),
-        ),
-    ];
-
-    // Only show password mode selector when password is entered
-    if form.show_passwd_mode() {
+    let mut lines = Vec::new();
+    for field in form.fields() {
+        let (label, value) = match field {
+            AddField::Name => ("Name", form.name.as_str()),
+            AddField::Host => ("Host", form.host.as_str()),
+            AddField::User => ("User", form.user.as_str()),
+            AddField::Port => ("Port", form.port.as_str()),
+            AddField::Identity => ("Identity", form.identity_file.as_str()),
+            AddField::ProxyJump => ("Jump Host", form.proxy_jump.as_str()),
+            AddField::ExtraArgs => ("SSH Args", form.extra_args.as_str()),
+            AddField::LocalForwards => ("Local Fwd", form.local_forwards.as_str()),
+            AddField::RemoteForwards => ("Remote Fwd", form.remote_forwards.as_str()),
+            AddField::Password => ("Password", password_display.as_str()),
+            AddField::Tags => ("Tags", form.tags.as_str()),
+            // Only show password mode selector when password is entered
+            AddField::PasswdMode => {
+                if form.show_passwd_mode() {
+                    lines.push(field_line(
+                        "Pwd Mode",
+                        passwd_mode_label,
+                        if form.field() == AddField::PasswdMode {
+                            Some(Caret::new(
+                                FormEditMode::Insert,
+                                passwd_mode_label.chars().count(),
+                                show_inline_caret,
+                            ))
+                        } else {
+                            None
+                        },
+                    ));
+                }
+                continue;
+            }
+        };
         lines.push(field_line(
-            "Pwd Mode",
-            passwd_mode_label,
-            if form.field() == AddField::PasswdMode {
-                Some(Caret::new(
-                    FormEditMode::Insert,
-                    passwd_mode_label.chars().count(),
-                    show_inline_caret,
-                ))
-            } else {
-                None
-            },
+            label,
+            value,
+            caret_for(form, *field, active_caret),
         ));
     }
 
-    lines.push(field_line(
-        "Tags",
-        &form.tags,
-        caret_for(form, AddField::Tags, active_caret),
-    ));
-
-    let identity_status = match form.identity_exists() {
-        Some(true) => "yes",
-        Some(false) => "missing",
-        None => "-",
-    };
-    lines.push(format!("  Identity exists: {}", identity_status));
+    if form.fields().contains(&AddField::Identity) {
+        let identity_status = match form.identity_exists() {
+            Some(true) => "yes",
+            Some(false) => "missing",
+            None => "-",
+        };
+        lines.push(format!("  Identity exists: {}", identity_status));
+    }
 
     if !form.identity_suggestions().is_empty() {
         lines.push("  Suggestions:".to_string());
@@ -1404,20 +2288,39 @@ fn build_scp_form_lines(form: &ScpForm, show_inline_caret: bool) -> Vec<String>
             None
         },
     ));
-    let recursive_value = if form.recursive { "yes" } else { "no" };
     lines.push(field_line(
-        "Recursive",
-        recursive_value,
-        if form.field() == ScpField::Recursive {
+        "Limit KB/s",
+        &form.limit,
+        if form.field() == ScpField::Limit {
             Some(Caret::new(
                 FormEditMode::Insert,
-                recursive_value.chars().count(),
+                form.limit.chars().count(),
                 show_inline_caret,
             ))
         } else {
             None
         },
     ));
+    for (label, field, enabled) in [
+        ("Recursive", ScpField::Recursive, form.recursive),
+        ("Preserve", ScpField::Preserve, form.preserve),
+        ("Compress", ScpField::Compress, form.compress),
+    ] {
+        let value = if enabled { "yes" } else { "no" };
+        lines.push(field_line(
+            label,
+            value,
+            if form.field() == field {
+                Some(Caret::new(
+                    FormEditMode::Insert,
+                    value.chars().count(),
+                    show_inline_caret,
+                ))
+            } else {
+                None
+            },
+        ));
+    }
     if !form.active_suggestions().is_empty() {
         lines.push("  Suggestions:".to_string());
         for (index, suggestion) in form.active_suggestions().iter().take(5).enumerate() {
@@ -1431,7 +2334,7 @@ fn build_scp_form_lines(form: &ScpForm, show_inline_caret: bool) -> Vec<String>
     }
     lines.push("  Tab applies the selected suggestion, then moves on".to_string());
     lines.push("  Up/Down cycle path suggestions".to_string());
-    lines.push("  Space toggles Direction/Recursive".to_string());
+    lines.push("  Space toggles Direction/Recursive/Preserve/Compress".to_string());
     lines.push("  Enter starts a password panel before transfer".to_string());
     lines
 }
@@ -1524,6 +2427,17 @@ fn submit_add_session(app: &mut AppState, store: &dyn SessionStore) -> Result<()
     let user = form.user.trim().to_string();
     let port_input = form.port.trim().to_string();
     let identity_input = form.identity_file.trim().to_string();
+    let proxy_jump = Some(form.proxy_jump.trim().to_string()).filter(|jump| !jump.is_empty());
+    let extra_args = form
+        .extra_args()
+        .map_err(|err| format!("SSH args: {}", err));
+    let forwards = split_forwards(&form.local_forwards)
+        .map_err(|err| format!("Local forward {}", err))
+        .and_then(|local| {
+            split_forwards(&form.remote_forwards)
+                .map(|remote| (local, remote))
+                .map_err(|err| format!("Remote forward {}", err))
+        });
     let password = form.password.clone();
     let passwd_mode = form.passwd_mode.clone();
     let tags_input = form.tags.clone();
@@ -1532,6 +2446,20 @@ fn submit_add_session(app: &mut AppState, store: &dyn SessionStore) -> Result<()
         app.set_status("Name, host, and user are required");
         return Ok(());
     }
+    let (local_forwards, remote_forwards) = match forwards {
+        Ok(forwards) => forwards,
+        Err(err) => {
+            app.set_status(err);
+            return Ok(());
+        }
+    };
+    let extra_args = match extra_args {
+        Ok(args) => args,
+        Err(err) => {
+            app.set_status(err);
+            return Ok(());
+        }
+    };
 
     let port = if port_input.is_empty() {
         22
@@ -1609,6 +2537,18 @@ fn submit_add_session(app: &mut AppState, store: &dyn SessionStore) -> Result<()
         has_stored_password,
         passwd_unsafe_mode: session_passwd_mode,
         stored_password,
+        extra_args,
+        local_forwards,
+        remote_forwards,
+        options: BTreeMap::new(),
+        connect_count: 0,
+        proxy_jump,
+        scp_compress: false,
+        last_duration_secs: None,
+        avg_duration_secs: None,
+        default_local_dir: None,
+        control_master: false,
+        control_path: None,
     };
 
     if let Err(err) = store.add(session.clone()) {
@@ -1641,6 +2581,17 @@ fn submit_edit_session(app: &mut AppState, store: &dyn SessionStore) -> Result<(
     let user = form.user.trim().to_string();
     let port_input = form.port.trim().to_string();
     let identity_input = form.identity_file.trim().to_string();
+    let proxy_jump = Some(form.proxy_jump.trim().to_string()).filter(|jump| !jump.is_empty());
+    let extra_args = form
+        .extra_args()
+        .map_err(|err| format!("SSH args: {}", err));
+    let forwards = split_forwards(&form.local_forwards)
+        .map_err(|err| format!("Local forward {}", err))
+        .and_then(|local| {
+            split_forwards(&form.remote_forwards)
+                .map(|remote| (local, remote))
+                .map_err(|err| format!("Remote forward {}", err))
+        });
     let password = form.password.clone();
     let passwd_mode = form.passwd_mode.clone();
     let tags_input = form.tags.clone();
@@ -1649,6 +2600,20 @@ fn submit_edit_session(app: &mut AppState, store: &dyn SessionStore) -> Result<(
         app.set_status("Name, host, and user are required");
         return Ok(());
     }
+    let (local_forwards, remote_forwards) = match forwards {
+        Ok(forwards) => forwards,
+        Err(err) => {
+            app.set_status(err);
+            return Ok(());
+        }
+    };
+    let extra_args = match extra_args {
+        Ok(args) => args,
+        Err(err) => {
+            app.set_status(err);
+            return Ok(());
+        }
+    };
 
     let port = if port_input.is_empty() {
         22
@@ -1670,8 +2635,14 @@ fn submit_edit_session(app: &mut AppState, store: &dyn SessionStore) -> Result<(
 
     let tags = split_tags(&tags_input);
 
-    // Get existing session for password preservation
-    let existing_session = store.list()?.into_iter().find(|s| s.name == original_name);
+    let Some(existing_session) = store.list()?.into_iter().find(|s| s.name == original_name) else {
+        show_error_popup(
+            app,
+            "Failed to update session (Esc closes error details)",
+            format!("Failed to update session: session '{original_name}' not found"),
+        );
+        return Ok(());
+    };
 
     // Handle password update - preserve existing if no new password provided
     let mut password_warning: Option<String> = None;
@@ -1713,19 +2684,15 @@ fn submit_edit_session(app: &mut AppState, store: &dyn SessionStore) -> Result<(
         }
     } else {
         // No new password - preserve existing
-        if let Some(ref existing) = existing_session {
-            (
-                existing.has_stored_password,
-                existing.passwd_unsafe_mode.clone(),
-            )
-        } else {
-            (false, None)
-        }
+        (
+            existing_session.has_stored_password,
+            existing_session.passwd_unsafe_mode.clone(),
+        )
     };
 
     // Preserve stored_password from existing session if not updating
     if password.is_empty() {
-        stored_password = existing_session.and_then(|s| s.stored_password);
+        stored_password = existing_session.stored_password.clone();
     }
 
     let session = Session {
@@ -1735,10 +2702,15 @@ fn submit_edit_session(app: &mut AppState, store: &dyn SessionStore) -> Result<(
         port,
         identity_file,
         tags,
-        last_connected_at: None,
         has_stored_password,
         passwd_unsafe_mode: session_passwd_mode,
         stored_password,
+        proxy_jump,
+        extra_args,
+        local_forwards,
+        remote_forwards,
+        // Fields the form does not edit (history, connection options) carry over.
+        ..existing_session
     };
 
     if let Err(err) = store.update(session.clone()) {
@@ -1772,7 +2744,11 @@ fn try_store_password_for_ui(session_name: &str, value: &str) -> Result<Option<S
     }
 }
 
-fn submit_scp(app: &mut AppState, store: &dyn SessionStore) -> Result<()> {
+fn submit_scp(
+    app: &mut AppState,
+    store: &dyn SessionStore,
+    runner: &dyn CommandRunner,
+) -> Result<()> {
     let Some(form) = app.scp_form() else {
         return Ok(());
     };
@@ -1786,7 +2762,19 @@ fn submit_scp(app: &mut AppState, store: &dyn SessionStore) -> Result<()> {
 
     let session = form.session.clone();
     let direction = form.direction;
-    let recursive = form.recursive;
+    let options = match form.options() {
+        Ok(options) => options,
+        Err(message) => {
+            app.set_status(message);
+            return Ok(());
+        }
+    };
+    // Only uploads take several sources; a download target is one path, which need
+    // not exist yet, so it is never split.
+    let local_paths = match direction {
+        ScpDirection::To => split_local_paths(&local_path),
+        ScpDirection::From => vec![local_path],
+    };
     let prompt_open = form.password_prompt().is_some();
     let prompted_password = form
         .password_prompt()
@@ -1810,11 +2798,13 @@ fn submit_scp(app: &mut AppState, store: &dyn SessionStore) -> Result<()> {
         return Ok(());
     }
 
+    let started = Instant::now();
     let output = run_scp_transfer(
+        runner,
         &session,
         direction,
-        recursive,
-        &local_path,
+        options,
+        &local_paths,
         &remote_path,
         password.as_deref(),
     )?;
@@ -1833,42 +2823,62 @@ fn submit_scp(app: &mut AppState, store: &dyn SessionStore) -> Result<()> {
         return Ok(());
     }
 
-    store.touch_last_connected(&session.name, now_epoch_seconds())?;
+    let (now, elapsed) = (now_epoch_seconds(), started.elapsed().as_secs());
+    let recorded = store.record_connection(&session.name, ConnectionKind::Scp, now, elapsed);
     if let Some(form) = app.scp_form_mut() {
         form.close_password_prompt();
     }
     app.cancel_scp();
-    app.set_status(format!("SCP complete: {}", session.name));
+    match recorded {
+        Ok(()) => app.set_status(format!("SCP complete: {}", session.name)),
+        Err(err) => app.set_status(format!(
+            "SCP complete: {} (history not saved: {:#})",
+            session.name, err
+        )),
+    }
     Ok(())
 }
 
+/// Split the SCP form's local field into upload sources. A value naming an existing
+/// path is kept whole (so paths with spaces still work); anything else is a comma or
+/// whitespace separated list.
+fn split_local_paths(input: &str) -> Vec<String> {
+    let input = input.trim();
+    if Path::new(&expand_tilde(input)).exists() {
+        return vec![input.to_string()];
+    }
+    input
+        .split(|ch: char| ch == ',' || ch.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Several sources all go before the destination, which scp then requires to be
+/// a directory.
 fn run_scp_transfer(
+    runner: &dyn CommandRunner,
     session: &Session,
     direction: ScpDirection,
-    recursive: bool,
-    local_path: &str,
+    options: ScpOptions,
+    local_paths: &[String],
     remote_path: &str,
     password: Option<&str>,
 ) -> Result<std::process::Output> {
-    let mut command = std::process::Command::new("scp");
-    if recursive {
-        command.arg("-r");
-    }
-    if let Some(identity) = &session.identity_file {
-        command.arg("-i").arg(identity);
-    }
-    command.arg("-P").arg(session.port.to_string());
-
-    let remote_target = format!("{}@{}:{}", session.user, session.host, remote_path);
+    let mut command = openssh::scp_command(session, options);
+    let remote_target = openssh::remote_spec(session, remote_path);
     match direction {
         ScpDirection::To => {
-            command.arg(local_path).arg(remote_target);
+            command.args(local_paths).arg(remote_target);
         }
         ScpDirection::From => {
-            command.arg(remote_target).arg(local_path);
+            command.arg(remote_target).args(local_paths);
         }
     }
 
+    // Logged before the askpass environment is attached so no password leaks.
+    log::debug!("running {:?}", command);
+
     if let Some(password) = password {
         let script_path = write_askpass_script()?;
         command
@@ -1880,11 +2890,11 @@ fn run_scp_transfer(
                 env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string()),
             )
             .stdin(Stdio::null());
-        let output = command.output()?;
+        let output = runner.output(&mut command);
         let _ = fs::remove_file(script_path);
-        Ok(output)
+        Ok(output?)
     } else {
-        Ok(command.output()?)
+        Ok(runner.output(&mut command)?)
     }
 }
 
@@ -1963,6 +2973,35 @@ fn local_path_suggestions(input: &str) -> Vec<String> {
     suggestions
 }
 
+/// Table title: the tag filter, if any, followed by the digit jump list
+/// (`1 web  2 db ...`).
+fn sessions_title(app: &AppState) -> String {
+    let mut title = match app.tag_filter() {
+        Some(tag) => format!("Sessions [tag: {tag}, T to clear]"),
+        None => "Sessions".to_string(),
+    };
+    if app.active_profile() != DEFAULT_PROFILE {
+        title.push_str(&format!(" [profile: {}]", app.active_profile()));
+    }
+    let recent = app.recent_sessions();
+    if !recent.is_empty() {
+        let jumps: Vec<String> = recent
+            .iter()
+            .enumerate()
+            .map(|(index, session)| format!("{} {}", index + 1, session.name))
+            .collect();
+        title.push_str(&format!(" | jump: {}", jumps.join("  ")));
+    }
+    title
+}
+
+fn column_constraint(width: ColumnWidth) -> Constraint {
+    match width {
+        ColumnWidth::Auto => Constraint::Min(AUTO_COLUMN_MIN_WIDTH),
+        ColumnWidth::Fixed(width) => Constraint::Length(width),
+    }
+}
+
 fn remote_path_suggestions(
     store: &dyn SessionStore,
     session: &Session,
@@ -2176,9 +3215,23 @@ fn split_tags(input: &str) -> Vec<String> {
         .collect()
 }
 
+/// Form input for port forwards, separated by commas or spaces; the error names the
+/// first malformed spec.
+fn split_forwards(input: &str) -> std::result::Result<Vec<String>, String> {
+    let specs: Vec<String> = input
+        .split(|ch: char| ch == ',' || ch.is_whitespace())
+        .filter(|spec| !spec.is_empty())
+        .map(str::to_string)
+        .collect();
+    for spec in &specs {
+        openssh::validate_forward(spec).map_err(|err| format!("'{}' is invalid: {}", spec, err))?;
+    }
+    Ok(specs)
+}
+
 fn refresh_monitor(app: &mut AppState, session: &Session) {
     let now = Instant::now();
-    if !app.monitor_should_refresh(now, Duration::from_secs(1)) {
+    if !app.monitor_should_refresh(now, app.monitor_interval()) {
         return;
     }
     let entries = fetch_ssh_connections(&session.host);
@@ -2197,9 +3250,14 @@ fn fetch_ssh_connections(host: &str) -> Vec<MonitorEntry> {
         return Vec::new();
     }
 
+    parse_ssh_connections(&String::from_utf8_lossy(&output.stdout), host)
+}
+
+/// ssh/scp/sftp processes to `host` in `ps -eo pid=,tty=,command=` output. ps prints
+/// `?` for processes without a terminal, which becomes `tty: None`.
+fn parse_ssh_connections(ps_output: &str, host: &str) -> Vec<MonitorEntry> {
     let mut entries = Vec::new();
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    for line in stdout.lines() {
+    for line in ps_output.lines() {
         let mut parts = line.split_whitespace();
         let pid_str = parts.next().unwrap_or("");
         let tty_str = parts.next().unwrap_or("");
@@ -2225,14 +3283,42 @@ fn fetch_ssh_connections(host: &str) -> Vec<MonitorEntry> {
     entries
 }
 
-fn format_last_connected(timestamp: Option<i64>) -> String {
+/// Body of the `i` details popup.
+fn session_detail_lines(session: &Session, now: i64) -> Vec<String> {
+    let duration = |secs: Option<u64>| secs.map(format_duration).unwrap_or_else(|| "-".into());
+    vec![
+        format!("Target:          {}:{}", session.target(), session.port),
+        format!(
+            "Identity:        {}",
+            session
+                .identity_file
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "-".to_string())
+        ),
+        format!(
+            "Tags:            {}",
+            if session.tags.is_empty() {
+                "-".to_string()
+            } else {
+                session.tags.join(", ")
+            }
+        ),
+        format!(
+            "Last connected:  {}",
+            format_last_connected(session.last_connected_at, now)
+        ),
+        format!("Last duration:   {}", duration(session.last_duration_secs)),
+        format!("Avg duration:    {}", duration(session.avg_duration_secs)),
+        String::new(),
+        "[i/Esc] Close".to_string(),
+    ]
+}
+
+fn format_last_connected(timestamp: Option<i64>, now: i64) -> String {
     let Some(timestamp) = timestamp else {
         return "-".to_string();
     };
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_secs() as i64)
-        .unwrap_or(0);
     let delta = now.saturating_sub(timestamp);
 
     if delta < 60 {
@@ -2246,13 +3332,6 @@ fn format_last_connected(timestamp: Option<i64>) -> String {
     }
 }
 
-fn now_epoch_seconds() -> i64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_secs() as i64)
-        .unwrap_or(0)
-}
-
 fn default_user() -> Option<String> {
     env::var("USER").or_else(|_| env::var("USERNAME")).ok()
 }
@@ -2260,11 +3339,21 @@ fn default_user() -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::{
-        build_scp_form_lines, build_text_entry_popup, filter_remote_suggestion_candidates,
-        parse_remote_autocomplete_input, should_skip_remote_autocomplete_lookup,
+        build_scp_form_lines, build_text_entry_popup, copy_field_value,
+        filter_remote_suggestion_candidates, format_identity, format_last_connected,
+        handle_action_menu_key, handle_help_key, handle_normal_key, help_panel_lines,
+        parse_remote_autocomplete_input, parse_ssh_connections, session_detail_lines,
+        should_skip_remote_autocomplete_lookup, split_forwards, split_local_paths,
+        submit_edit_session, submit_scp, switch_profile, truncate_cell, wrapped_line_count,
     };
     use crate::model::Session;
-    use crate::ui::state::{ScpForm, TextEntryPanel};
+    use crate::runner::fake::FakeRunner;
+    use crate::store::{JsonFileStore, SessionStore, SqliteStore, StoreOpener};
+    use crate::ui::state::{
+        AppState, InputMode, MonitorEntry, ScpDirection, ScpForm, TextEntryPanel,
+    };
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::collections::BTreeMap;
 
     fn sample_session() -> Session {
         Session {
@@ -2278,7 +3367,322 @@ mod tests {
             has_stored_password: false,
             passwd_unsafe_mode: None,
             stored_password: None,
+            local_forwards: Vec::new(),
+            remote_forwards: Vec::new(),
+            extra_args: Vec::new(),
+            options: BTreeMap::new(),
+            connect_count: 0,
+            proxy_jump: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            control_master: false,
+            control_path: None,
+        }
+    }
+
+    /// An app with the SCP form open on `office`, past the password prompt.
+    fn scp_app(store: &JsonFileStore) -> AppState {
+        store.add(sample_session()).expect("add session");
+        let mut app = AppState::new(&store.list().expect("list"));
+        assert!(app.start_scp());
+        let form = app.scp_form_mut().expect("scp form");
+        form.local_path = "notes.txt".to_string();
+        form.remote_path = "/tmp/".to_string();
+        form.limit = "512".to_string();
+        form.open_password_prompt();
+        app
+    }
+
+    #[test]
+    fn switch_profile_loads_the_profile_store() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base = dir.path().join("sessions.json");
+        let work = JsonFileStore::new(dir.path().join("profiles/work.json"));
+        let mut remote = sample_session();
+        remote.name = "remote".to_string();
+        work.add(remote).expect("add");
+
+        let mut app = AppState::new(&[sample_session()]);
+        app.set_profile_context(Some(base), "default");
+        let opener = StoreOpener::default();
+        let (store, path) = switch_profile(&mut app, "work", &opener).expect("switch");
+
+        assert_eq!(path, dir.path().join("profiles/work.json"));
+        assert_eq!(store.list().expect("list").len(), 1);
+        assert_eq!(app.selected_session().unwrap().name, "remote");
+        assert_eq!(app.active_profile(), "work");
+        assert_eq!(app.status(), "Switched to profile 'work' (1 sessions)");
+        assert!(switch_profile(&mut app, "../escape", &opener).is_err());
+    }
+
+    #[test]
+    fn switch_profile_opens_sqlite_stores() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let base = dir.path().join("sessions.db");
+        SqliteStore::new(base.clone())
+            .add(sample_session())
+            .expect("add");
+
+        let mut app = AppState::new(&[]);
+        app.set_profile_context(Some(base.clone()), "work");
+        let (store, path) =
+            switch_profile(&mut app, "default", &StoreOpener::default()).expect("switch");
+
+        assert_eq!(path, base);
+        assert_eq!(store.list().expect("list").len(), 1);
+        assert_eq!(app.active_profile(), "default");
+    }
+
+    #[test]
+    fn submit_scp_runs_scp_through_runner() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        let mut app = scp_app(&store);
+        let runner = FakeRunner::succeeding();
+
+        submit_scp(&mut app, &store, &runner).expect("submit");
+
+        let commands = runner.commands.lock().unwrap();
+        assert_eq!(commands.len(), 1);
+        assert!(commands[0].starts_with("scp "), "{}", commands[0]);
+        assert!(commands[0].contains("-l 4096"), "{}", commands[0]);
+        assert!(commands[0].ends_with("notes.txt alice@example.com:/tmp/"));
+        assert_eq!(app.status(), "SCP complete: office");
+        assert!(app.scp_form().is_none());
+        let session = &store.list().expect("list")[0];
+        assert!(session.last_connected_at.is_some());
+    }
+
+    #[test]
+    fn submit_scp_reports_failures_from_runner() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        let mut app = scp_app(&store);
+        let runner = FakeRunner::exiting(1, "Permission denied");
+
+        submit_scp(&mut app, &store, &runner).expect("submit");
+
+        assert_eq!(app.error_popup(), Some("Permission denied"));
+        assert!(app.scp_form().is_some());
+        assert!(store.list().expect("list")[0].last_connected_at.is_none());
+    }
+
+    #[test]
+    fn submit_scp_keeps_a_new_download_path_whole() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        let mut app = scp_app(&store);
+        let form = app.scp_form_mut().expect("scp form");
+        form.direction = ScpDirection::From;
+        form.local_path = "~/my file,v2.txt".to_string();
+        let runner = FakeRunner::succeeding();
+
+        submit_scp(&mut app, &store, &runner).expect("submit");
+
+        let commands = runner.commands.lock().unwrap();
+        assert_eq!(commands.len(), 1);
+        assert!(
+            commands[0].ends_with("alice@example.com:/tmp/ ~/my file,v2.txt"),
+            "{}",
+            commands[0]
+        );
+        assert_eq!(app.status(), "SCP complete: office");
+    }
+
+    #[test]
+    fn submit_scp_survives_a_store_that_cannot_record_it() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("sessions.json");
+        let store = JsonFileStore::new(path.clone());
+        let mut app = scp_app(&store);
+        std::fs::remove_file(&path).expect("remove store");
+        std::fs::create_dir(&path).expect("block store");
+
+        submit_scp(&mut app, &store, &FakeRunner::succeeding()).expect("submit");
+
+        assert!(
+            app.status()
+                .starts_with("SCP complete: office (history not saved:"),
+            "{}",
+            app.status()
+        );
+        assert!(app.scp_form().is_none());
+    }
+
+    #[test]
+    fn help_lines_group_bindings_under_headings() {
+        let (lines, headings) = help_panel_lines();
+        assert_eq!(lines[0], "Navigation");
+        assert_eq!(headings[0], 0);
+        for heading in &headings[1..] {
+            assert!(lines[heading - 1].is_empty());
+            assert!(!lines[*heading].starts_with(' '));
+        }
+        assert!(lines.iter().any(|line| line.contains("Open SCP form")));
+    }
+
+    #[test]
+    fn help_keys_scroll_and_any_other_key_closes() {
+        let mut app = AppState::new(&[sample_session()]);
+        app.open_help();
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        handle_help_key(&mut app, press(KeyCode::Char('j'))).unwrap();
+        handle_help_key(&mut app, press(KeyCode::Down)).unwrap();
+        assert_eq!(app.help_scroll(), 2);
+        handle_help_key(
+            &mut app,
+            KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL),
+        )
+        .unwrap();
+        assert_eq!(app.help_scroll(), 0);
+        assert_eq!(app.mode(), InputMode::Help);
+
+        handle_help_key(&mut app, press(KeyCode::Char('x'))).unwrap();
+        assert_eq!(app.mode(), InputMode::Normal);
+    }
+
+    #[test]
+    fn session_details_show_durations() {
+        let mut session = sample_session();
+        let lines = session_detail_lines(&session, 0);
+        assert!(lines.contains(&"Last duration:   -".to_string()));
+
+        session.last_duration_secs = Some(192);
+        session.avg_duration_secs = Some(45);
+        let lines = session_detail_lines(&session, 0);
+        assert!(lines.contains(&"Last duration:   3m12s".to_string()));
+        assert!(lines.contains(&"Avg duration:    45s".to_string()));
+    }
+
+    #[test]
+    fn last_connected_is_relative_to_now() {
+        let now = 1_700_000_000;
+        assert_eq!(format_last_connected(None, now), "-");
+        assert_eq!(format_last_connected(Some(now - 42), now), "42s ago");
+        assert_eq!(format_last_connected(Some(now - 7_200), now), "2h ago");
+        assert_eq!(format_last_connected(Some(now - 3 * 86_400), now), "3d ago");
+    }
+
+    #[test]
+    fn session_details_show_last_connected() {
+        let mut session = sample_session();
+        session.last_connected_at = Some(1_000);
+        let lines = session_detail_lines(&session, 1_000 + 300);
+        assert!(lines.contains(&"Last connected:  5m ago".to_string()));
+    }
+
+    #[test]
+    fn copy_field_value_picks_host_target_and_identity() {
+        let mut session = sample_session();
+        assert_eq!(
+            copy_field_value(&session, 'h'),
+            Some(("host", "example.com".to_string()))
+        );
+        assert_eq!(
+            copy_field_value(&session, 't'),
+            Some(("target", "alice@example.com".to_string()))
+        );
+        assert_eq!(copy_field_value(&session, 'i'), None);
+
+        session.identity_file = Some("/home/alice/.ssh/id_ed25519".into());
+        assert_eq!(
+            copy_field_value(&session, 'i'),
+            Some(("identity", "/home/alice/.ssh/id_ed25519".to_string()))
+        );
+    }
+
+    #[test]
+    fn truncate_cell_marks_cut_values_with_an_ellipsis() {
+        assert_eq!(truncate_cell("web", 5), "web");
+        assert_eq!(truncate_cell("office", 6), "office");
+        assert_eq!(truncate_cell("office-backup", 6), "offic…");
+        assert_eq!(truncate_cell("office", 1), "…");
+        assert_eq!(truncate_cell("office", 0), "");
+        // Each of these takes two columns: 2 + 2 + "…" fits 5, a third would not.
+        assert_eq!(truncate_cell("服务器名", 5), "服务…");
+        assert_eq!(truncate_cell("服务器名", 6), "服务…");
+    }
+
+    #[test]
+    fn space_menu_routes_to_the_existing_actions() {
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = AppState::new(&[sample_session()]);
+
+        handle_normal_key(&mut app, press(KeyCode::Char(' '))).unwrap();
+        assert_eq!(app.mode(), InputMode::ActionMenu);
+        for _ in 0..6 {
+            handle_action_menu_key(&mut app, press(KeyCode::Char('j'))).unwrap();
         }
+        assert!(
+            handle_action_menu_key(&mut app, press(KeyCode::Enter))
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(app.mode(), InputMode::Details);
+
+        app.set_mode(InputMode::Normal);
+        handle_normal_key(&mut app, press(KeyCode::Char(' '))).unwrap();
+        let chosen = handle_action_menu_key(&mut app, press(KeyCode::Enter)).unwrap();
+        assert_eq!(chosen, Some(Some(sample_session())));
+
+        handle_normal_key(&mut app, press(KeyCode::Char(' '))).unwrap();
+        handle_action_menu_key(&mut app, press(KeyCode::Esc)).unwrap();
+        assert_eq!(app.mode(), InputMode::Normal);
+    }
+
+    #[test]
+    fn parse_ssh_connections_keeps_the_tty_of_each_match() {
+        let ps = [
+            "  101 pts/3    ssh -p 22 me@web.example.com",
+            "  202 ?        ssh -N -f me@web.example.com",
+            "  303 pts/4    vim notes.md",
+            "  404 pts/5    ssh me@db.internal",
+        ]
+        .join("\n");
+        assert_eq!(
+            parse_ssh_connections(&ps, "web.example.com"),
+            vec![
+                MonitorEntry {
+                    pid: 101,
+                    tty: Some("pts/3".to_string()),
+                },
+                MonitorEntry {
+                    pid: 202,
+                    tty: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn format_identity_keeps_the_meaningful_end() {
+        use crate::ui::config::IdentityDisplay;
+        let path = "/home/me/.ssh/keys/company/prod/id_ed25519";
+
+        assert_eq!(format_identity(path, IdentityDisplay::Full, 18), path);
+        assert_eq!(
+            format_identity(path, IdentityDisplay::Basename, 18),
+            "id_ed25519"
+        );
+        assert_eq!(
+            format_identity(path, IdentityDisplay::Middle, 18),
+            "…/prod/id_ed25519"
+        );
+        assert_eq!(
+            format_identity("~/.ssh/id_rsa", IdentityDisplay::Middle, 18),
+            "~/.ssh/id_rsa"
+        );
+        assert_eq!(
+            format_identity(
+                "/keys/a_very_long_key_name_here",
+                IdentityDisplay::Middle,
+                10
+            ),
+            "…name_here"
+        );
     }
 
     #[test]
@@ -2317,6 +3721,69 @@ mod tests {
         assert_eq!(popup.cursor.expect("cursor").line, 5);
     }
 
+    #[test]
+    fn editing_keeps_ssh_args_and_reads_the_field_as_shell_words() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store = JsonFileStore::new(dir.path().join("sessions.json"));
+        let mut session = sample_session();
+        session.extra_args = vec![
+            "-oKexAlgorithms=a,b".to_string(),
+            "-oRemoteCommand=tmux new -A".to_string(),
+        ];
+        store.add(session.clone()).expect("add");
+        let mut app = AppState::new(&store.list().expect("list"));
+
+        app.start_edit_session(&session);
+        assert_eq!(
+            app.add_form().expect("form").extra_args,
+            "-oKexAlgorithms=a,b '-oRemoteCommand=tmux new -A'"
+        );
+        submit_edit_session(&mut app, &store).expect("submit");
+        assert_eq!(
+            store.list().expect("list")[0].extra_args,
+            session.extra_args
+        );
+
+        app.start_edit_session(&session);
+        app.add_form_mut().expect("form").extra_args = r#"-A -o "SetEnv=A=b c""#.to_string();
+        submit_edit_session(&mut app, &store).expect("submit");
+        assert_eq!(
+            store.list().expect("list")[0].extra_args,
+            ["-A", "-o", "SetEnv=A=b c"]
+        );
+
+        app.start_edit_session(&session);
+        app.add_form_mut().expect("form").extra_args = "-o 'oops".to_string();
+        submit_edit_session(&mut app, &store).expect("submit");
+        assert_eq!(app.status(), "SSH args: unterminated single quote");
+    }
+
+    #[test]
+    fn split_forwards_names_the_malformed_spec() {
+        assert_eq!(
+            split_forwards("5432:localhost:5432, 6379:cache:6379").unwrap(),
+            ["5432:localhost:5432", "6379:cache:6379"]
+        );
+        assert_eq!(
+            split_forwards("5432:localhost:5432 web:80").unwrap_err(),
+            "'web:80' is invalid: expected [bind_address:]port:host:hostport"
+        );
+    }
+
+    #[test]
+    fn split_local_paths_keeps_existing_paths_whole() {
+        assert_eq!(
+            split_local_paths(" a.txt, b.txt  c.txt "),
+            ["a.txt", "b.txt", "c.txt"]
+        );
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let spaced = dir.path().join("my notes.txt");
+        std::fs::write(&spaced, "notes").expect("write");
+        let spaced = spaced.to_string_lossy().to_string();
+        assert_eq!(split_local_paths(&spaced), [spaced.clone()]);
+    }
+
     #[test]
     fn scp_form_lines_show_autocomplete_candidates_for_active_field() {
         let mut form = ScpForm::new(sample_session());
@@ -2366,4 +3833,12 @@ mod tests {
         assert!(!should_skip_remote_autocomplete_lookup(".", "ab"));
         assert!(!should_skip_remote_autocomplete_lookup("/var", ""));
     }
+
+    #[test]
+    fn wrapped_line_count_flows_words_onto_new_rows() {
+        assert_eq!(wrapped_line_count("j/k move | q quit", 80), 1);
+        assert_eq!(wrapped_line_count("j/k move | q quit", 8), 2);
+        assert_eq!(wrapped_line_count("abcdefghij", 4), 3);
+        assert_eq!(wrapped_line_count("", 10), 1);
+    }
 }
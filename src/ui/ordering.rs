@@ -1,9 +1,8 @@
 use crate::model::Session;
 use crate::ui::config::SessionOrderMode;
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn sort_sessions(sessions: &mut [Session], mode: SessionOrderMode) {
+/// Order `sessions` for display; `now` (epoch seconds) anchors the recency weights.
+pub fn sort_sessions(sessions: &mut [Session], mode: SessionOrderMode, now: i64) {
     match mode {
         SessionOrderMode::LatestFirst => {
             sessions.sort_by(|a, b| {
@@ -13,35 +12,7 @@ pub fn sort_sessions(sessions: &mut [Session], mode: SessionOrderMode) {
             });
         }
         SessionOrderMode::FrequencyBased => {
-            // Count connections per session (using timestamps as proxy)
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64;
-
-            // Group sessions by connection patterns
-            let mut frequency_map: HashMap<String, usize> = HashMap::new();
-            for session in sessions.iter() {
-                if let Some(ts) = session.last_connected_at {
-                    // Sessions connected in last 24 hours count as "hot"
-                    let age_hours = (now - ts) / 3600;
-                    let count = frequency_map.entry(session.name.clone()).or_insert(0);
-                    if age_hours < 24 {
-                        *count += 10; // Weight recent connections higher
-                    } else if age_hours < 168 {
-                        // 7 days
-                        *count += 5;
-                    } else {
-                        *count += 1;
-                    }
-                }
-            }
-
-            sessions.sort_by(|a, b| {
-                let a_freq = frequency_map.get(&a.name).copied().unwrap_or(0);
-                let b_freq = frequency_map.get(&b.name).copied().unwrap_or(0);
-                b_freq.cmp(&a_freq) // Descending: most frequent first
-            });
+            sessions.sort_by_key(|session| std::cmp::Reverse(frequency_score(session, now)));
         }
         SessionOrderMode::Alphabetical => {
             sessions.sort_by(|a, b| a.name.cmp(&b.name));
@@ -49,24 +20,36 @@ pub fn sort_sessions(sessions: &mut [Session], mode: SessionOrderMode) {
     }
 }
 
+/// Connection count weighted by how recently the session was last used, so a host
+/// used daily outranks one used often long ago. Never-connected sessions score 0.
+fn frequency_score(session: &Session, now: i64) -> u64 {
+    let Some(ts) = session.last_connected_at else {
+        return 0;
+    };
+    let age_hours = (now - ts) / 3600;
+    let recency = if age_hours < 24 {
+        10
+    } else if age_hours < 168 {
+        // 7 days
+        5
+    } else {
+        1
+    };
+    // Stores from before connect_count existed count each session at least once.
+    recency * session.connect_count.max(1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::model::Session;
 
+    const NOW: i64 = 1_700_000_000;
+
     fn session(name: &str, ts: Option<i64>) -> Session {
-        Session {
-            name: name.to_string(),
-            host: "example.com".to_string(),
-            user: "user".to_string(),
-            port: 22,
-            identity_file: None,
-            tags: vec![],
-            last_connected_at: ts,
-            has_stored_password: false,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        }
+        let mut session = Session::builder(name, "example.com", "user").build();
+        session.last_connected_at = ts;
+        session
     }
 
     #[test]
@@ -76,7 +59,7 @@ mod tests {
             session("new", Some(200)),
             session("medium", Some(150)),
         ];
-        sort_sessions(&mut sessions, SessionOrderMode::LatestFirst);
+        sort_sessions(&mut sessions, SessionOrderMode::LatestFirst, NOW);
         assert_eq!(sessions[0].name, "new");
         assert_eq!(sessions[1].name, "medium");
         assert_eq!(sessions[2].name, "old");
@@ -85,7 +68,7 @@ mod tests {
     #[test]
     fn sort_latest_first_with_none() {
         let mut sessions = vec![session("with_time", Some(100)), session("no_time", None)];
-        sort_sessions(&mut sessions, SessionOrderMode::LatestFirst);
+        sort_sessions(&mut sessions, SessionOrderMode::LatestFirst, NOW);
         assert_eq!(sessions[0].name, "with_time");
         assert_eq!(sessions[1].name, "no_time");
     }
@@ -97,7 +80,7 @@ mod tests {
             session("alpha", Some(200)),
             session("beta", Some(150)),
         ];
-        sort_sessions(&mut sessions, SessionOrderMode::Alphabetical);
+        sort_sessions(&mut sessions, SessionOrderMode::Alphabetical, NOW);
         assert_eq!(sessions[0].name, "alpha");
         assert_eq!(sessions[1].name, "beta");
         assert_eq!(sessions[2].name, "zebra");
@@ -106,14 +89,14 @@ mod tests {
     #[test]
     fn sort_alphabetical_with_empty_list() {
         let mut sessions: Vec<Session> = vec![];
-        sort_sessions(&mut sessions, SessionOrderMode::Alphabetical);
+        sort_sessions(&mut sessions, SessionOrderMode::Alphabetical, NOW);
         assert!(sessions.is_empty());
     }
 
     #[test]
     fn sort_latest_first_empty_list() {
         let mut sessions: Vec<Session> = vec![];
-        sort_sessions(&mut sessions, SessionOrderMode::LatestFirst);
+        sort_sessions(&mut sessions, SessionOrderMode::LatestFirst, NOW);
         assert!(sessions.is_empty());
     }
 
@@ -124,43 +107,51 @@ mod tests {
             session("second", None),
             session("third", None),
         ];
-        sort_sessions(&mut sessions, SessionOrderMode::LatestFirst);
+        sort_sessions(&mut sessions, SessionOrderMode::LatestFirst, NOW);
         // All have same value (0), order should be stable
         assert_eq!(sessions.len(), 3);
     }
 
     #[test]
     fn sort_frequency_based_recent_connections() {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let recent = now - 1000; // Very recent
-        let older = now - 100000; // Older (but within 7 days)
-        let ancient = now - 1000000; // Ancient
+        let recent = NOW - 1000; // Very recent
+        let older = NOW - 100000; // Older (but within 7 days)
+        let ancient = NOW - 1000000; // Ancient
 
         let mut sessions = vec![
             session("ancient", Some(ancient)),
             session("recent", Some(recent)),
             session("older", Some(older)),
         ];
-        sort_sessions(&mut sessions, SessionOrderMode::FrequencyBased);
+        sort_sessions(&mut sessions, SessionOrderMode::FrequencyBased, NOW);
         // recent should be first (score 10), older second (score 5), ancient last (score 1)
         assert_eq!(sessions[0].name, "recent");
         assert_eq!(sessions[1].name, "older");
         assert_eq!(sessions[2].name, "ancient");
     }
 
+    #[test]
+    fn sort_frequency_based_weighs_connection_counts() {
+        let mut daily = session("daily", Some(NOW - 1000));
+        daily.connect_count = 3;
+        let mut busy_last_month = session("busy_last_month", Some(NOW - 30 * 86400));
+        busy_last_month.connect_count = 50;
+        let mut weekly = session("weekly", Some(NOW - 3 * 86400));
+        weekly.connect_count = 2;
+
+        let mut sessions = vec![weekly, daily, busy_last_month];
+        sort_sessions(&mut sessions, SessionOrderMode::FrequencyBased, NOW);
+        // 50 * 1 beats 3 * 10, which beats 2 * 5
+        let names: Vec<_> = sessions.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["busy_last_month", "daily", "weekly"]);
+    }
+
     #[test]
     fn sort_frequency_based_with_none() {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let recent = now - 1000;
+        let recent = NOW - 1000;
 
         let mut sessions = vec![session("with_time", Some(recent)), session("no_time", None)];
-        sort_sessions(&mut sessions, SessionOrderMode::FrequencyBased);
+        sort_sessions(&mut sessions, SessionOrderMode::FrequencyBased, NOW);
         // Session with recent connection should come first
         assert_eq!(sessions[0].name, "with_time");
         assert_eq!(sessions[1].name, "no_time");
@@ -0,0 +1,57 @@
+use crate::model::Session;
+use crate::parallel::run_bounded;
+use crate::ssh::probe_tcp;
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Runs TCP reachability probes off the UI thread and hands back results.
+pub struct ReachabilityProber {
+    sender: Sender<(String, bool)>,
+    receiver: Receiver<(String, bool)>,
+    in_flight: HashSet<String>,
+    parallel: usize,
+}
+
+impl ReachabilityProber {
+    pub fn new(parallel: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            in_flight: HashSet::new(),
+            parallel,
+        }
+    }
+
+    /// Start probes for sessions that are not already being probed.
+    pub fn request(&mut self, sessions: Vec<Session>) {
+        let pending: Vec<Session> = sessions
+            .into_iter()
+            .filter(|session| self.in_flight.insert(session.name.clone()))
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+        let sender = self.sender.clone();
+        let parallel = self.parallel;
+        thread::spawn(move || {
+            run_bounded(pending, parallel, |session| {
+                let reachable = probe_tcp(&session.host, session.port, PROBE_TIMEOUT).is_ok();
+                let _ = sender.send((session.name, reachable));
+            });
+        });
+    }
+
+    /// Collect finished probes without blocking.
+    pub fn drain(&mut self) -> Vec<(String, bool)> {
+        let results: Vec<(String, bool)> = self.receiver.try_iter().collect();
+        for (name, _) in &results {
+            self.in_flight.remove(name);
+        }
+        results
+    }
+}
@@ -1,5 +1,12 @@
+use crate::export::ExportFormat;
 use crate::model::{PasswdUnsafeMode, Session};
+use crate::openssh::{self, ScpOptions};
+use crate::store::DEFAULT_PROFILE;
+use crate::ui::config::{EnterAction, RowDensity, SessionOrderMode};
 use crate::ui::filter::filter_sessions;
+use crate::ui::ordering::sort_sessions;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,6 +18,128 @@ pub enum InputMode {
     EditSession,
     Help,
     Scp,
+    ExportSelect,
+    Details,
+    ProfilePicker,
+    ActionMenu,
+}
+
+/// An entry in the action menu (Space, or Enter with `input.enter_action: "menu"`),
+/// run against the selected session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionAction {
+    Connect,
+    Scp,
+    Edit,
+    Delete,
+    CopyTarget,
+    CopyCommand,
+    Inspect,
+}
+
+impl SessionAction {
+    pub const ALL: [SessionAction; 7] = [
+        SessionAction::Connect,
+        SessionAction::Scp,
+        SessionAction::Edit,
+        SessionAction::Delete,
+        SessionAction::CopyTarget,
+        SessionAction::CopyCommand,
+        SessionAction::Inspect,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionAction::Connect => "Connect (ssh)",
+            SessionAction::Scp => "SCP transfer",
+            SessionAction::Edit => "Edit session",
+            SessionAction::Delete => "Delete session",
+            SessionAction::CopyTarget => "Copy user@host",
+            SessionAction::CopyCommand => "Copy ssh command",
+            SessionAction::Inspect => "Inspect details",
+        }
+    }
+
+    /// Normal-mode key that runs the same action, shown next to the label.
+    pub fn key_hint(self) -> &'static str {
+        match self {
+            SessionAction::Connect => "",
+            SessionAction::Scp => "s",
+            SessionAction::Edit => "e",
+            SessionAction::Delete => "dd",
+            SessionAction::CopyTarget => "yt",
+            SessionAction::CopyCommand => "",
+            SessionAction::Inspect => "i",
+        }
+    }
+}
+
+/// The `P` popup listing profiles to switch the TUI to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfilePicker {
+    profiles: Vec<String>,
+    selected: usize,
+}
+
+impl ProfilePicker {
+    pub fn profiles(&self) -> &[String] {
+        &self.profiles
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+}
+
+/// Optional screen panels that can be shown or hidden at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    Logo,
+    Search,
+    Status,
+    Help,
+}
+
+impl Panel {
+    pub fn label(self) -> &'static str {
+        match self {
+            Panel::Logo => "logo",
+            Panel::Search => "search bar",
+            Panel::Status => "status line",
+            Panel::Help => "help",
+        }
+    }
+}
+
+/// Live panel visibility, seeded from `layout.show_*` in the UI config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PanelVisibility {
+    pub logo: bool,
+    pub search: bool,
+    pub status: bool,
+    pub help: bool,
+}
+
+impl Default for PanelVisibility {
+    fn default() -> Self {
+        Self {
+            logo: true,
+            search: true,
+            status: true,
+            help: true,
+        }
+    }
+}
+
+impl PanelVisibility {
+    fn slot(&mut self, panel: Panel) -> &mut bool {
+        match panel {
+            Panel::Logo => &mut self.logo,
+            Panel::Search => &mut self.search,
+            Panel::Status => &mut self.status,
+            Panel::Help => &mut self.help,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,6 +148,45 @@ pub struct MonitorEntry {
     pub tty: Option<String>,
 }
 
+/// Which sessions to show based on background reachability probes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReachabilityFilter {
+    #[default]
+    All,
+    Reachable,
+    Unreachable,
+}
+
+impl ReachabilityFilter {
+    pub fn next(self) -> Self {
+        match self {
+            ReachabilityFilter::All => ReachabilityFilter::Reachable,
+            ReachabilityFilter::Reachable => ReachabilityFilter::Unreachable,
+            ReachabilityFilter::Unreachable => ReachabilityFilter::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReachabilityFilter::All => "all sessions",
+            ReachabilityFilter::Reachable => "reachable only",
+            ReachabilityFilter::Unreachable => "unreachable only",
+        }
+    }
+
+    /// Sessions that have not been probed yet stay visible.
+    fn allows(self, reachable: Option<bool>) -> bool {
+        match (self, reachable) {
+            (ReachabilityFilter::All, _) | (_, None) => true,
+            (ReachabilityFilter::Reachable, Some(ok)) => ok,
+            (ReachabilityFilter::Unreachable, Some(ok)) => !ok,
+        }
+    }
+}
+
+/// Number of recent sessions reachable with the digit keys `1`-`9`.
+pub const JUMP_LIST_LEN: usize = 9;
+
 pub struct AppState {
     sessions: Vec<Session>,
     pub filter: String,
@@ -31,11 +199,34 @@ pub struct AppState {
     delete_dialog: Option<DeleteDialog>,
     add_form: Option<AddSessionForm>,
     form_default_mode: FormEditMode,
+    form_fields: Vec<AddField>,
     yank_buffer: Option<Session>,
     scp_form: Option<ScpForm>,
     monitor_enabled: bool,
+    panels: PanelVisibility,
     monitor_last_update: Option<Instant>,
+    monitor_interval: Duration,
     monitor_entries: Vec<MonitorEntry>,
+    row_density: RowDensity,
+    confirm_discard: bool,
+    discard_prompt: bool,
+    reachability: HashMap<String, bool>,
+    reachability_filter: ReachabilityFilter,
+    tag_filter: Option<String>,
+    marked: HashSet<String>,
+    export_prompt: Option<ExportPrompt>,
+    /// First visible line of the help overlay
+    help_scroll: usize,
+    order_mode: SessionOrderMode,
+    profile_picker: Option<ProfilePicker>,
+    /// Profile chosen in the picker, waiting for the store to be swapped
+    profile_request: Option<String>,
+    /// Base store that profiles live next to; `None` when they cannot be switched
+    profile_base: Option<PathBuf>,
+    active_profile: String,
+    enter_action: EnterAction,
+    /// Highlighted row of the open action menu
+    action_menu: Option<usize>,
 }
 
 impl AppState {
@@ -52,23 +243,70 @@ impl AppState {
             delete_dialog: None,
             add_form: None,
             form_default_mode: FormEditMode::Normal,
+            form_fields: DEFAULT_ADD_FIELDS.to_vec(),
             yank_buffer: None,
             scp_form: None,
             monitor_enabled: false,
+            panels: PanelVisibility::default(),
             monitor_last_update: None,
+            monitor_interval: Duration::from_secs(1),
             monitor_entries: Vec::new(),
+            row_density: RowDensity::Full,
+            confirm_discard: false,
+            discard_prompt: false,
+            reachability: HashMap::new(),
+            reachability_filter: ReachabilityFilter::All,
+            tag_filter: None,
+            help_scroll: 0,
+            marked: HashSet::new(),
+            export_prompt: None,
+            order_mode: SessionOrderMode::default(),
+            profile_picker: None,
+            profile_request: None,
+            profile_base: None,
+            active_profile: DEFAULT_PROFILE.to_string(),
+            enter_action: EnterAction::default(),
+            action_menu: None,
         };
         state.refresh_filter();
         state
     }
 
     pub fn refresh_filter(&mut self) {
-        self.filtered_indices = filter_sessions(&self.sessions, &self.filter);
+        self.filtered_indices = filter_sessions(&self.sessions, &self.filter)
+            .into_iter()
+            .filter(|index| {
+                let session = &self.sessions[*index];
+                self.reachability_filter
+                    .allows(self.reachability.get(&session.name).copied())
+                    && self
+                        .tag_filter
+                        .as_ref()
+                        .is_none_or(|tag| session.tags.contains(tag))
+            })
+            .collect();
         if self.selected >= self.filtered_indices.len() {
             self.selected = 0;
         }
     }
 
+    /// Replace the search text (e.g. from `se tui --filter`) and re-filter from the top.
+    pub fn set_filter(&mut self, filter: impl Into<String>) {
+        self.filter = filter.into();
+        self.selected = 0;
+        self.refresh_filter();
+    }
+
+    /// Show only sessions carrying exactly this tag (`None` shows all).
+    pub fn set_tag_filter(&mut self, tag: Option<String>) {
+        self.tag_filter = tag;
+        self.refresh_filter_keeping_selection();
+    }
+
+    pub fn tag_filter(&self) -> Option<&str> {
+        self.tag_filter.as_deref()
+    }
+
     pub fn mode(&self) -> InputMode {
         self.mode
     }
@@ -77,6 +315,30 @@ impl AppState {
         self.mode = mode;
     }
 
+    /// Show the help overlay from its first line.
+    pub fn open_help(&mut self) {
+        self.mode = InputMode::Help;
+        self.help_scroll = 0;
+    }
+
+    pub fn help_scroll(&self) -> usize {
+        self.help_scroll
+    }
+
+    pub fn scroll_help_down(&mut self, lines: usize) {
+        self.help_scroll = self.help_scroll.saturating_add(lines);
+    }
+
+    pub fn scroll_help_up(&mut self, lines: usize) {
+        self.help_scroll = self.help_scroll.saturating_sub(lines);
+    }
+
+    /// Keep the overlay from scrolling past its last line; `max` is the content
+    /// height minus the rows that fit on screen.
+    pub fn clamp_help_scroll(&mut self, max: usize) {
+        self.help_scroll = self.help_scroll.min(max);
+    }
+
     pub fn set_pending(&mut self, pending: Option<char>) {
         self.pending = pending;
     }
@@ -146,6 +408,62 @@ impl AppState {
         }
     }
 
+    /// Select the session called `name` if it is visible; returns whether it was found.
+    pub fn select_by_name(&mut self, name: &str) -> bool {
+        match self
+            .filtered_indices
+            .iter()
+            .position(|index| self.sessions[*index].name == name)
+        {
+            Some(position) => {
+                self.selected = position;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Select a session named on the command line (`se tui --select`), falling back to
+    /// the first row with a status note when it is missing or filtered out.
+    pub fn select_requested(&mut self, name: &str) {
+        if !self.select_by_name(name) {
+            self.select_first();
+            self.set_status(format!(
+                "Session '{}' not found; selected the first row",
+                name
+            ));
+        }
+    }
+
+    /// Visible sessions that have been connected to, most recent first, capped at
+    /// [`JUMP_LIST_LEN`]; digit `n` jumps to entry `n - 1`.
+    pub fn recent_sessions(&self) -> Vec<&Session> {
+        let mut recent: Vec<&Session> = self
+            .filtered_sessions()
+            .into_iter()
+            .filter(|session| session.last_connected_at.is_some())
+            .collect();
+        recent.sort_by(|a, b| {
+            b.last_connected_at
+                .cmp(&a.last_connected_at)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        recent.truncate(JUMP_LIST_LEN);
+        recent
+    }
+
+    /// Select the `position`-th (1-based) entry of [`Self::recent_sessions`]; returns its
+    /// name, or `None` when the jump list is shorter than that.
+    pub fn jump_to_recent(&mut self, position: usize) -> Option<String> {
+        let name = self
+            .recent_sessions()
+            .get(position.checked_sub(1)?)?
+            .name
+            .clone();
+        self.select_by_name(&name);
+        Some(name)
+    }
+
     pub fn select_last(&mut self) {
         if !self.filtered_indices.is_empty() {
             self.selected = self.filtered_indices.len() - 1;
@@ -218,7 +536,11 @@ impl AppState {
     }
 
     pub fn start_add_session(&mut self, default_user: Option<String>) {
-        self.add_form = Some(AddSessionForm::new(default_user, self.form_default_mode));
+        self.add_form = Some(AddSessionForm::new(
+            default_user,
+            self.form_default_mode,
+            &self.form_fields,
+        ));
         self.mode = InputMode::AddSession;
     }
 
@@ -234,9 +556,28 @@ impl AppState {
             return false;
         };
 
-        let mut form = AddSessionForm::from_session(session, self.form_default_mode);
+        let mut form =
+            AddSessionForm::from_session(session, self.form_default_mode, &self.form_fields);
         form.name = name;
         form.set_cursor_to_end();
+        form.mark_pristine();
+        self.add_form = Some(form);
+        self.mode = InputMode::AddSession;
+        true
+    }
+
+    /// Open the add form prefilled from the selected session, leaving the name
+    /// blank for the new one. Returns false when nothing is selected.
+    pub fn start_clone_session(&mut self) -> bool {
+        let Some(session) = self.selected_session() else {
+            return false;
+        };
+
+        let mut form =
+            AddSessionForm::from_session(session, self.form_default_mode, &self.form_fields);
+        form.name.clear();
+        form.set_cursor_to_end();
+        form.mark_pristine();
         self.add_form = Some(form);
         self.mode = InputMode::AddSession;
         true
@@ -279,6 +620,7 @@ impl AppState {
         self.add_form = Some(AddSessionForm::from_session(
             session,
             self.form_default_mode,
+            &self.form_fields,
         ));
         self.mode = InputMode::EditSession;
     }
@@ -287,8 +629,121 @@ impl AppState {
         self.form_default_mode = mode;
     }
 
+    /// Fields the add/edit form shows, in Tab order. The password mode selector
+    /// always follows Password. An empty list keeps the default.
+    pub fn set_form_fields(&mut self, fields: &[AddField]) {
+        if fields.is_empty() {
+            return;
+        }
+        self.form_fields = fields
+            .iter()
+            .filter(|field| **field != AddField::PasswdMode)
+            .flat_map(|field| match field {
+                AddField::Password => vec![AddField::Password, AddField::PasswdMode],
+                other => vec![*other],
+            })
+            .collect();
+    }
+
     pub fn cancel_add_session(&mut self) {
         self.add_form = None;
+        self.discard_prompt = false;
+        self.mode = InputMode::Normal;
+    }
+
+    pub fn set_confirm_discard(&mut self, enabled: bool) {
+        self.confirm_discard = enabled;
+    }
+
+    /// Ask before cancelling when the open add/edit/SCP form has unsaved input.
+    /// Returns true when a confirmation prompt was raised instead of cancelling.
+    pub fn request_discard(&mut self) -> bool {
+        let dirty = match self.mode {
+            InputMode::AddSession | InputMode::EditSession => {
+                self.add_form.as_ref().is_some_and(AddSessionForm::is_dirty)
+            }
+            InputMode::Scp => self.scp_form.as_ref().is_some_and(ScpForm::is_dirty),
+            _ => false,
+        };
+        self.discard_prompt = self.confirm_discard && dirty;
+        self.discard_prompt
+    }
+
+    pub fn discard_prompt(&self) -> bool {
+        self.discard_prompt
+    }
+
+    pub fn dismiss_discard_prompt(&mut self) {
+        self.discard_prompt = false;
+    }
+
+    pub fn start_export_select(&mut self) {
+        self.export_prompt = None;
+        self.mode = InputMode::ExportSelect;
+    }
+
+    /// Toggle the mark on the selected session; returns the new state, or `None`
+    /// when nothing is selected.
+    pub fn toggle_mark_selected(&mut self) -> Option<bool> {
+        let name = self.selected_session()?.name.clone();
+        if self.marked.remove(&name) {
+            Some(false)
+        } else {
+            self.marked.insert(name);
+            Some(true)
+        }
+    }
+
+    pub fn mark_all_visible(&mut self) {
+        let names: Vec<String> = self
+            .filtered_indices
+            .iter()
+            .map(|index| self.sessions[*index].name.clone())
+            .collect();
+        self.marked.extend(names);
+    }
+
+    pub fn is_marked(&self, name: &str) -> bool {
+        self.marked.contains(name)
+    }
+
+    pub fn marked_count(&self) -> usize {
+        self.marked.len()
+    }
+
+    /// Marked sessions in list order.
+    pub fn marked_sessions(&self) -> Vec<Session> {
+        self.sessions
+            .iter()
+            .filter(|session| self.marked.contains(&session.name))
+            .cloned()
+            .collect()
+    }
+
+    /// Ask for the export format and destination; refuses while nothing is marked.
+    pub fn open_export_prompt(&mut self) -> bool {
+        if self.marked.is_empty() {
+            return false;
+        }
+        self.export_prompt = Some(ExportPrompt::new());
+        true
+    }
+
+    pub fn export_prompt(&self) -> Option<&ExportPrompt> {
+        self.export_prompt.as_ref()
+    }
+
+    pub fn export_prompt_mut(&mut self) -> Option<&mut ExportPrompt> {
+        self.export_prompt.as_mut()
+    }
+
+    pub fn close_export_prompt(&mut self) {
+        self.export_prompt = None;
+    }
+
+    pub fn cancel_export_select(&mut self) {
+        self.marked.clear();
+        self.export_prompt = None;
         self.mode = InputMode::Normal;
     }
 
@@ -300,13 +755,19 @@ impl AppState {
         self.add_form.as_mut()
     }
 
-    pub fn start_scp(&mut self, session: Session) {
+    /// Open the SCP form for the current selection; returns `false` when nothing is selected.
+    pub fn start_scp(&mut self) -> bool {
+        let Some(session) = self.selected_session().cloned() else {
+            return false;
+        };
         self.scp_form = Some(ScpForm::new(session));
         self.mode = InputMode::Scp;
+        true
     }
 
     pub fn cancel_scp(&mut self) {
         self.scp_form = None;
+        self.discard_prompt = false;
         self.mode = InputMode::Normal;
     }
 
@@ -322,6 +783,21 @@ impl AppState {
         self.monitor_enabled = enabled;
     }
 
+    pub fn set_panels(&mut self, panels: PanelVisibility) {
+        self.panels = panels;
+    }
+
+    pub fn panels(&self) -> PanelVisibility {
+        self.panels
+    }
+
+    /// Flip a panel's visibility and return whether it is now shown.
+    pub fn toggle_panel(&mut self, panel: Panel) -> bool {
+        let slot = self.panels.slot(panel);
+        *slot = !*slot;
+        *slot
+    }
+
     pub fn toggle_monitor(&mut self) {
         self.monitor_enabled = !self.monitor_enabled;
     }
@@ -330,6 +806,196 @@ impl AppState {
         self.monitor_enabled
     }
 
+    pub fn cycle_reachability_filter(&mut self) -> ReachabilityFilter {
+        self.reachability_filter = self.reachability_filter.next();
+        self.refresh_filter_keeping_selection();
+        self.reachability_filter
+    }
+
+    pub fn reachability_filter(&self) -> ReachabilityFilter {
+        self.reachability_filter
+    }
+
+    pub fn set_profile_context(&mut self, base: Option<PathBuf>, active: impl Into<String>) {
+        self.profile_base = base;
+        self.active_profile = active.into();
+    }
+
+    pub fn profile_base(&self) -> Option<&Path> {
+        self.profile_base.as_deref()
+    }
+
+    pub fn active_profile(&self) -> &str {
+        &self.active_profile
+    }
+
+    pub fn set_active_profile(&mut self, profile: impl Into<String>) {
+        self.active_profile = profile.into();
+    }
+
+    /// Show the profile picker with `active` preselected.
+    pub fn open_profile_picker(&mut self, profiles: Vec<String>, active: &str) {
+        let selected = profiles.iter().position(|name| name == active).unwrap_or(0);
+        self.profile_picker = Some(ProfilePicker { profiles, selected });
+        self.mode = InputMode::ProfilePicker;
+    }
+
+    pub fn profile_picker(&self) -> Option<&ProfilePicker> {
+        self.profile_picker.as_ref()
+    }
+
+    pub fn move_profile_selection(&mut self, down: bool) {
+        if let Some(picker) = &mut self.profile_picker {
+            let last = picker.profiles.len().saturating_sub(1);
+            picker.selected = if down {
+                (picker.selected + 1).min(last)
+            } else {
+                picker.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Close the picker, remembering the highlighted profile for `take_profile_request`.
+    pub fn choose_profile(&mut self) {
+        if let Some(picker) = self.profile_picker.take() {
+            self.profile_request = picker.profiles.get(picker.selected).cloned();
+        }
+        self.mode = InputMode::Normal;
+    }
+
+    pub fn close_profile_picker(&mut self) {
+        self.profile_picker = None;
+        self.mode = InputMode::Normal;
+    }
+
+    pub fn take_profile_request(&mut self) -> Option<String> {
+        self.profile_request.take()
+    }
+
+    pub fn set_enter_action(&mut self, action: EnterAction) {
+        self.enter_action = action;
+    }
+
+    pub fn enter_action(&self) -> EnterAction {
+        self.enter_action
+    }
+
+    /// Show the action menu for the selected session; false when nothing is selected.
+    pub fn open_action_menu(&mut self) -> bool {
+        if self.selected_session().is_none() {
+            return false;
+        }
+        self.action_menu = Some(0);
+        self.mode = InputMode::ActionMenu;
+        true
+    }
+
+    pub fn action_menu(&self) -> Option<usize> {
+        self.action_menu
+    }
+
+    pub fn move_action_selection(&mut self, down: bool) {
+        if let Some(selected) = &mut self.action_menu {
+            let last = SessionAction::ALL.len() - 1;
+            *selected = if down {
+                (*selected + 1).min(last)
+            } else {
+                selected.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Close the menu and return the highlighted action.
+    pub fn choose_action(&mut self) -> Option<SessionAction> {
+        let action = self
+            .action_menu
+            .take()
+            .and_then(|selected| SessionAction::ALL.get(selected).copied());
+        self.mode = InputMode::Normal;
+        action
+    }
+
+    pub fn close_action_menu(&mut self) {
+        self.action_menu = None;
+        self.mode = InputMode::Normal;
+    }
+
+    /// Swap in another store's sessions: marks and probe results belong to the old
+    /// list, so they are dropped and the first row is selected.
+    pub fn replace_sessions(&mut self, sessions: Vec<Session>, now: i64) {
+        self.sessions = sessions;
+        self.filtered_indices.clear();
+        self.marked.clear();
+        self.reachability.clear();
+        self.selected = 0;
+        self.set_order_mode(self.order_mode, now);
+    }
+
+    /// Reorder the sessions by `mode`, keeping the selected session selected.
+    /// `now` (epoch seconds) anchors the recency weights.
+    pub fn set_order_mode(&mut self, mode: SessionOrderMode, now: i64) {
+        self.order_mode = mode;
+        let selected = self.selected_session().map(|session| session.name.clone());
+        sort_sessions(&mut self.sessions, mode, now);
+        self.refresh_filter();
+        if let Some(name) = selected {
+            self.select_by_name(&name);
+        }
+    }
+
+    pub fn cycle_order_mode(&mut self, now: i64) -> SessionOrderMode {
+        self.set_order_mode(self.order_mode.next(), now);
+        self.order_mode
+    }
+
+    pub fn order_mode(&self) -> SessionOrderMode {
+        self.order_mode
+    }
+
+    pub fn set_reachability(&mut self, name: &str, reachable: bool) {
+        self.reachability.insert(name.to_string(), reachable);
+        self.refresh_filter_keeping_selection();
+    }
+
+    /// Sessions matching the search text whose reachability is still unknown.
+    pub fn probe_candidates(&self) -> Vec<Session> {
+        filter_sessions(&self.sessions, &self.filter)
+            .into_iter()
+            .map(|index| &self.sessions[index])
+            .filter(|session| !self.reachability.contains_key(&session.name))
+            .cloned()
+            .collect()
+    }
+
+    fn refresh_filter_keeping_selection(&mut self) {
+        let selected = self.selected_session().map(|session| session.name.clone());
+        self.refresh_filter();
+        if let Some(name) = selected {
+            self.select_by_name(&name);
+        }
+    }
+
+    pub fn set_row_density(&mut self, density: RowDensity) {
+        self.row_density = density;
+    }
+
+    pub fn toggle_row_density(&mut self) -> RowDensity {
+        self.row_density = self.row_density.toggled();
+        self.row_density
+    }
+
+    pub fn row_density(&self) -> RowDensity {
+        self.row_density
+    }
+
+    pub fn set_monitor_interval(&mut self, interval: Duration) {
+        self.monitor_interval = interval;
+    }
+
+    pub fn monitor_interval(&self) -> Duration {
+        self.monitor_interval
+    }
+
     pub fn monitor_should_refresh(&self, now: Instant, interval: Duration) -> bool {
         match self.monitor_last_update {
             Some(last) => now.duration_since(last) >= interval,
@@ -379,38 +1045,30 @@ pub enum AddField {
     User,
     Port,
     Identity,
+    ProxyJump,
+    ExtraArgs,
+    LocalForwards,
+    RemoteForwards,
     Password,
     PasswdMode,
     Tags,
 }
 
-impl AddField {
-    pub fn next(self) -> Self {
-        match self {
-            AddField::Name => AddField::Host,
-            AddField::Host => AddField::User,
-            AddField::User => AddField::Port,
-            AddField::Port => AddField::Identity,
-            AddField::Identity => AddField::Password,
-            AddField::Password => AddField::PasswdMode,
-            AddField::PasswdMode => AddField::Tags,
-            AddField::Tags => AddField::Name,
-        }
-    }
-
-    pub fn prev(self) -> Self {
-        match self {
-            AddField::Name => AddField::Tags,
-            AddField::Host => AddField::Name,
-            AddField::User => AddField::Host,
-            AddField::Port => AddField::User,
-            AddField::Identity => AddField::Port,
-            AddField::Password => AddField::Identity,
-            AddField::PasswdMode => AddField::Password,
-            AddField::Tags => AddField::PasswdMode,
-        }
-    }
-}
+/// Add/edit form fields in their default Tab order.
+pub const DEFAULT_ADD_FIELDS: [AddField; 12] = [
+    AddField::Name,
+    AddField::Host,
+    AddField::User,
+    AddField::Port,
+    AddField::Identity,
+    AddField::ProxyJump,
+    AddField::ExtraArgs,
+    AddField::LocalForwards,
+    AddField::RemoteForwards,
+    AddField::Password,
+    AddField::PasswdMode,
+    AddField::Tags,
+];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormEditMode {
@@ -427,44 +1085,80 @@ impl FormEditMode {
     }
 }
 
+/// Snapshot of the editable form values, used to detect unsaved edits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AddFormValues {
+    name: String,
+    host: String,
+    user: String,
+    port: String,
+    identity_file: String,
+    proxy_jump: String,
+    extra_args: String,
+    local_forwards: String,
+    remote_forwards: String,
+    password: String,
+    passwd_mode: PasswdUnsafeMode,
+    tags: String,
+}
+
 pub struct AddSessionForm {
     pub name: String,
     pub host: String,
     pub user: String,
     pub port: String,
     pub identity_file: String,
+    pub proxy_jump: String,
+    /// Raw ssh arguments as shell words (quote an argument to keep spaces in it)
+    pub extra_args: String,
+    /// `-L` forward specs, separated by commas or spaces
+    pub local_forwards: String,
+    /// `-R` forward specs, separated by commas or spaces
+    pub remote_forwards: String,
     pub password: String,
     pub passwd_mode: PasswdUnsafeMode,
     pub tags: String,
+    fields: Vec<AddField>,
     field: AddField,
     edit_mode: FormEditMode,
     field_cursor: usize,
     identity_exists: Option<bool>,
     identity_suggestions: Vec<String>,
+    initial: Option<AddFormValues>,
+    /// Arguments of the session being edited, kept as-is while the field is untouched
+    saved_extra_args: Vec<String>,
 }
 
 impl AddSessionForm {
-    fn new(default_user: Option<String>, edit_mode: FormEditMode) -> Self {
+    fn new(default_user: Option<String>, edit_mode: FormEditMode, fields: &[AddField]) -> Self {
         let mut form = Self {
             name: String::new(),
             host: String::new(),
             user: default_user.unwrap_or_default(),
             port: "22".to_string(),
             identity_file: String::new(),
+            proxy_jump: String::new(),
+            extra_args: String::new(),
+            local_forwards: String::new(),
+            remote_forwards: String::new(),
             password: String::new(),
             passwd_mode: PasswdUnsafeMode::Normal,
             tags: String::new(),
-            field: AddField::Name,
+            fields: fields.to_vec(),
+            field: fields[0],
             edit_mode,
             field_cursor: 0,
             identity_exists: None,
             identity_suggestions: Vec::new(),
+            initial: None,
+            saved_extra_args: Vec::new(),
         };
         form.set_cursor_to_end();
+        form.mark_pristine();
         form
     }
 
-    fn from_session(session: &Session, edit_mode: FormEditMode) -> Self {
+    fn from_session(session: &Session, edit_mode: FormEditMode, fields: &[AddField]) -> Self {
         let mut form = Self {
             name: session.name.clone(),
             host: session.host.clone(),
@@ -475,33 +1169,89 @@ impl AddSessionForm {
                 .as_ref()
                 .map(|p| p.display().to_string())
                 .unwrap_or_default(),
+            proxy_jump: session.proxy_jump.clone().unwrap_or_default(),
+            extra_args: openssh::join_shell_words(&session.extra_args),
+            local_forwards: session.local_forwards.join(", "),
+            remote_forwards: session.remote_forwards.join(", "),
             password: String::new(), // Don't load existing password
             passwd_mode: session
                 .passwd_unsafe_mode
                 .clone()
                 .unwrap_or(PasswdUnsafeMode::Normal),
             tags: session.tags.join(","),
-            field: AddField::Name,
+            fields: fields.to_vec(),
+            field: fields[0],
             edit_mode,
             field_cursor: 0,
             identity_exists: None,
             identity_suggestions: Vec::new(),
+            initial: None,
+            saved_extra_args: session.extra_args.clone(),
         };
         form.set_cursor_to_end();
+        form.mark_pristine();
         form
     }
 
+    /// The SSH args field split into arguments. While it still shows the session's
+    /// saved arguments they are returned unchanged rather than re-parsed.
+    pub fn extra_args(&self) -> Result<Vec<String>, String> {
+        if self.extra_args == openssh::join_shell_words(&self.saved_extra_args) {
+            return Ok(self.saved_extra_args.clone());
+        }
+        openssh::split_shell_words(&self.extra_args)
+    }
+
     pub fn field(&self) -> AddField {
         self.field
     }
 
+    pub fn fields(&self) -> &[AddField] {
+        &self.fields
+    }
+
+    fn values(&self) -> AddFormValues {
+        AddFormValues {
+            name: self.name.clone(),
+            host: self.host.clone(),
+            user: self.user.clone(),
+            port: self.port.clone(),
+            identity_file: self.identity_file.clone(),
+            proxy_jump: self.proxy_jump.clone(),
+            extra_args: self.extra_args.clone(),
+            local_forwards: self.local_forwards.clone(),
+            remote_forwards: self.remote_forwards.clone(),
+            password: self.password.clone(),
+            passwd_mode: self.passwd_mode.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// Treat the current values as the untouched starting point.
+    fn mark_pristine(&mut self) {
+        self.initial = Some(self.values());
+    }
+
+    /// Whether any value differs from what the form was opened with.
+    pub fn is_dirty(&self) -> bool {
+        self.initial.as_ref() != Some(&self.values())
+    }
+
     pub fn next_field(&mut self) {
-        self.field = self.field.next();
-        self.set_cursor_to_end();
+        self.step_field(1);
     }
 
     pub fn prev_field(&mut self) {
-        self.field = self.field.prev();
+        self.step_field(self.fields.len() - 1);
+    }
+
+    fn step_field(&mut self, offset: usize) {
+        let position = self
+            .fields
+            .iter()
+            .position(|field| *field == self.field)
+            .unwrap_or(0);
+        self.field = self.fields[(position + offset) % self.fields.len()];
         self.set_cursor_to_end();
     }
 
@@ -575,6 +1325,10 @@ impl AddSessionForm {
             AddField::User => &mut self.user,
             AddField::Port => &mut self.port,
             AddField::Identity => &mut self.identity_file,
+            AddField::ProxyJump => &mut self.proxy_jump,
+            AddField::ExtraArgs => &mut self.extra_args,
+            AddField::LocalForwards => &mut self.local_forwards,
+            AddField::RemoteForwards => &mut self.remote_forwards,
             AddField::Password => &mut self.password,
             AddField::PasswdMode => &mut self.password, // Not editable directly
             AddField::Tags => &mut self.tags,
@@ -601,6 +1355,10 @@ impl AddSessionForm {
             AddField::User => &self.user,
             AddField::Port => &self.port,
             AddField::Identity => &self.identity_file,
+            AddField::ProxyJump => &self.proxy_jump,
+            AddField::ExtraArgs => &self.extra_args,
+            AddField::LocalForwards => &self.local_forwards,
+            AddField::RemoteForwards => &self.remote_forwards,
             AddField::Password => &self.password,
             AddField::PasswdMode => "", // Not a text field
             AddField::Tags => &self.tags,
@@ -698,6 +1456,42 @@ impl TextEntryPanel {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportPrompt {
+    format: ExportFormat,
+    entry: TextEntryPanel,
+}
+
+impl ExportPrompt {
+    fn new() -> Self {
+        Self {
+            format: ExportFormat::Json,
+            entry: TextEntryPanel::new(
+                "Export Sessions",
+                "Output path (leave blank to copy to the clipboard)",
+                "Export",
+                false,
+            ),
+        }
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    pub fn cycle_format(&mut self) {
+        self.format = self.format.next();
+    }
+
+    pub fn entry(&self) -> &TextEntryPanel {
+        &self.entry
+    }
+
+    pub fn entry_mut(&mut self) -> &mut TextEntryPanel {
+        &mut self.entry
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DeleteDialog {
     target: String,
@@ -739,7 +1533,10 @@ pub enum ScpField {
     Direction,
     Local,
     Remote,
+    Limit,
     Recursive,
+    Preserve,
+    Compress,
 }
 
 impl ScpField {
@@ -747,19 +1544,33 @@ impl ScpField {
         match self {
             ScpField::Direction => ScpField::Local,
             ScpField::Local => ScpField::Remote,
-            ScpField::Remote => ScpField::Recursive,
-            ScpField::Recursive => ScpField::Direction,
+            ScpField::Remote => ScpField::Limit,
+            ScpField::Limit => ScpField::Recursive,
+            ScpField::Recursive => ScpField::Preserve,
+            ScpField::Preserve => ScpField::Compress,
+            ScpField::Compress => ScpField::Direction,
         }
     }
 
     pub fn prev(self) -> Self {
         match self {
-            ScpField::Direction => ScpField::Recursive,
+            ScpField::Direction => ScpField::Compress,
             ScpField::Local => ScpField::Direction,
             ScpField::Remote => ScpField::Local,
-            ScpField::Recursive => ScpField::Remote,
+            ScpField::Limit => ScpField::Remote,
+            ScpField::Recursive => ScpField::Limit,
+            ScpField::Preserve => ScpField::Recursive,
+            ScpField::Compress => ScpField::Preserve,
         }
     }
+
+    /// Yes/no fields toggled with Space or Left/Right.
+    pub fn is_toggle(self) -> bool {
+        matches!(
+            self,
+            ScpField::Recursive | ScpField::Preserve | ScpField::Compress
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -787,9 +1598,15 @@ impl ScpDirection {
 pub struct ScpForm {
     pub session: Session,
     pub local_path: String,
+    /// Local path the form opened with (the session's default local dir, if any)
+    initial_local_path: String,
     pub remote_path: String,
     pub direction: ScpDirection,
     pub recursive: bool,
+    pub preserve: bool,
+    pub compress: bool,
+    /// Bandwidth cap in KB/s as typed; empty means unlimited
+    pub limit: String,
     field: ScpField,
     password_prompt: Option<TextEntryPanel>,
     local_suggestions: Vec<String>,
@@ -802,12 +1619,26 @@ pub struct ScpForm {
 
 impl ScpForm {
     pub(crate) fn new(session: Session) -> Self {
+        let local_path = session
+            .local_dir()
+            .map(|dir| {
+                let mut dir = dir.display().to_string();
+                if !dir.ends_with('/') {
+                    dir.push('/');
+                }
+                dir
+            })
+            .unwrap_or_default();
         Self {
+            compress: session.scp_compress,
             session,
-            local_path: String::new(),
+            initial_local_path: local_path.clone(),
+            local_path,
             remote_path: String::new(),
             direction: ScpDirection::To,
             recursive: false,
+            preserve: false,
+            limit: String::new(),
             field: ScpField::Local,
             password_prompt: None,
             local_suggestions: Vec::new(),
@@ -823,6 +1654,17 @@ impl ScpForm {
         self.field
     }
 
+    /// Whether the user has entered anything beyond the initial defaults.
+    pub fn is_dirty(&self) -> bool {
+        self.local_path != self.initial_local_path
+            || !self.remote_path.is_empty()
+            || self.direction != ScpDirection::To
+            || self.recursive
+            || self.preserve
+            || self.compress != self.session.scp_compress
+            || !self.limit.is_empty()
+    }
+
     pub fn next_field(&mut self) {
         self.field = self.field.next();
     }
@@ -835,14 +1677,45 @@ impl ScpForm {
         self.direction = self.direction.toggle();
     }
 
-    pub fn toggle_recursive(&mut self) {
-        self.recursive = !self.recursive;
+    /// Flip whichever yes/no field is active.
+    pub fn toggle_active(&mut self) {
+        match self.field {
+            ScpField::Recursive => self.recursive = !self.recursive,
+            ScpField::Preserve => self.preserve = !self.preserve,
+            ScpField::Compress => self.compress = !self.compress,
+            _ => {}
+        }
+    }
+
+    /// Transfer flags from the form; fails when the limit is not a positive number.
+    pub fn options(&self) -> Result<ScpOptions, String> {
+        let limit = self.limit.trim();
+        let limit_kbps = if limit.is_empty() {
+            None
+        } else {
+            match limit.parse::<u32>() {
+                Ok(limit) if limit > 0 => Some(limit),
+                _ => {
+                    return Err(format!(
+                        "Limit must be a positive number of KB/s, not '{}'",
+                        limit
+                    ));
+                }
+            }
+        };
+        Ok(ScpOptions {
+            recursive: self.recursive,
+            preserve: self.preserve,
+            compress: self.compress,
+            limit_kbps,
+        })
     }
 
     pub fn active_editable_mut(&mut self) -> Option<&mut String> {
         match self.field {
             ScpField::Local => Some(&mut self.local_path),
             ScpField::Remote => Some(&mut self.remote_path),
+            ScpField::Limit => Some(&mut self.limit),
             _ => None,
         }
     }
@@ -991,18 +1864,11 @@ mod tests {
     use super::*;
 
     fn sample_session(name: &str) -> Session {
-        Session {
-            name: name.to_string(),
-            host: "example.com".to_string(),
-            user: "alice".to_string(),
-            port: 22,
-            identity_file: None,
-            tags: vec!["prod".to_string()],
-            last_connected_at: None,
-            has_stored_password: true,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        }
+        let mut session = Session::builder(name, "example.com", "alice")
+            .tag("prod")
+            .build();
+        session.has_stored_password = true;
+        session
     }
 
     #[test]
@@ -1016,6 +1882,30 @@ mod tests {
         assert_eq!(app.next_copy_name("office"), "office-copy-3");
     }
 
+    #[test]
+    fn digit_jumps_follow_connection_recency() {
+        let mut sessions = vec![
+            sample_session("never"),
+            sample_session("old"),
+            sample_session("new"),
+        ];
+        sessions[1].last_connected_at = Some(100);
+        sessions[2].last_connected_at = Some(200);
+        let mut app = AppState::new(&sessions);
+
+        let recent: Vec<&str> = app
+            .recent_sessions()
+            .iter()
+            .map(|session| session.name.as_str())
+            .collect();
+        assert_eq!(recent, ["new", "old"]);
+
+        assert_eq!(app.jump_to_recent(2).as_deref(), Some("old"));
+        assert_eq!(app.selected_session().unwrap().name, "old");
+        assert_eq!(app.jump_to_recent(3), None);
+        assert_eq!(app.selected_session().unwrap().name, "old");
+    }
+
     #[test]
     fn next_copy_name_uses_first_copy_when_available() {
         let sessions = vec![sample_session("office")];
@@ -1041,6 +1931,25 @@ mod tests {
         assert!(form.password.is_empty());
     }
 
+    #[test]
+    fn clone_prefills_add_form_without_a_name() {
+        let sessions = vec![sample_session("office")];
+        let mut app = AppState::new(&sessions);
+
+        assert!(app.start_clone_session());
+        assert_eq!(app.mode(), InputMode::AddSession);
+        let form = app.add_form().expect("clone should open add form");
+        assert!(form.name.is_empty());
+        assert_eq!(form.host, "example.com");
+        assert_eq!(form.user, "alice");
+        assert_eq!(form.tags, "prod");
+        assert!(!form.is_dirty());
+
+        let mut empty = AppState::new(&[]);
+        assert!(!empty.start_clone_session());
+        assert_eq!(empty.mode(), InputMode::Normal);
+    }
+
     #[test]
     fn paste_without_yank_fails() {
         let sessions = vec![sample_session("office")];
@@ -1158,6 +2067,56 @@ mod tests {
         assert_eq!(prompt.display_value(), "");
     }
 
+    #[test]
+    fn scp_form_prefills_default_local_dir() {
+        let mut session = sample_session("office");
+        session.default_local_dir = Some("/srv/transfers".to_string());
+        let mut form = ScpForm::new(session);
+
+        assert_eq!(form.local_path, "/srv/transfers/");
+        assert!(!form.is_dirty());
+        form.local_path.push_str("report.csv");
+        assert!(form.is_dirty());
+    }
+
+    #[test]
+    fn scp_form_toggles_start_from_session_defaults() {
+        let mut session = sample_session("office");
+        session.scp_compress = true;
+        let mut form = ScpForm::new(session);
+        assert_eq!(
+            form.options(),
+            Ok(ScpOptions {
+                recursive: false,
+                preserve: false,
+                compress: true,
+                limit_kbps: None,
+            })
+        );
+        assert!(!form.is_dirty());
+
+        while form.field() != ScpField::Compress {
+            form.next_field();
+        }
+        form.toggle_active();
+        form.prev_field();
+        form.toggle_active();
+        assert!(form.preserve);
+        assert!(!form.compress);
+        assert!(form.is_dirty());
+    }
+
+    #[test]
+    fn scp_form_limit_must_be_positive() {
+        let mut form = ScpForm::new(sample_session("office"));
+        form.limit = "512".to_string();
+        assert_eq!(form.options().unwrap().limit_kbps, Some(512));
+        for bad in ["0", "-5", "fast"] {
+            form.limit = bad.to_string();
+            assert!(form.options().unwrap_err().contains("positive number"));
+        }
+    }
+
     #[test]
     fn scp_form_applies_local_autocomplete_suggestion() {
         let session = sample_session("office");
@@ -1230,4 +2189,365 @@ mod tests {
         assert_eq!(form.remote_suggestion_cache_directory(), None);
         assert!(form.remote_suggestion_cache_suggestions().is_empty());
     }
+
+    #[test]
+    fn toggle_row_density_switches_between_full_and_compact() {
+        let mut state = AppState::new(&[sample_session("office")]);
+        assert_eq!(state.row_density(), RowDensity::Full);
+        assert_eq!(state.toggle_row_density(), RowDensity::Compact);
+        assert_eq!(state.toggle_row_density(), RowDensity::Full);
+    }
+
+    #[test]
+    fn select_by_name_falls_back_to_first_when_missing() {
+        let sessions = vec![sample_session("alpha"), sample_session("beta")];
+        let mut state = AppState::new(&sessions);
+
+        assert!(state.select_by_name("beta"));
+        assert_eq!(state.selected_session().unwrap().name, "beta");
+
+        let mut state = AppState::new(&sessions);
+        assert!(!state.select_by_name("gone"));
+        assert_eq!(state.selected_session().unwrap().name, "alpha");
+    }
+
+    #[test]
+    fn select_requested_notes_missing_session() {
+        let sessions = vec![sample_session("alpha"), sample_session("beta")];
+        let mut state = AppState::new(&sessions);
+
+        state.select_requested("beta");
+        assert_eq!(state.selected_session().unwrap().name, "beta");
+        assert!(state.status().is_empty());
+
+        state.select_requested("gone");
+        assert_eq!(state.selected_session().unwrap().name, "alpha");
+        assert_eq!(
+            state.status(),
+            "Session 'gone' not found; selected the first row"
+        );
+    }
+
+    #[test]
+    fn set_filter_limits_sessions_and_selects_first_match() {
+        let mut db = sample_session("db");
+        db.host = "db.internal".to_string();
+        let sessions = vec![sample_session("alpha"), db, sample_session("beta")];
+        let mut state = AppState::new(&sessions);
+        state.select_last();
+
+        state.set_filter("db.int");
+        assert_eq!(state.filtered_sessions().len(), 1);
+        assert_eq!(state.selected_session().unwrap().name, "db");
+        assert!(!state.select_by_name("beta"));
+        assert_eq!(state.selected_session().unwrap().name, "db");
+
+        state.set_filter("nothing-matches");
+        assert!(state.selected_session().is_none());
+    }
+
+    #[test]
+    fn cycle_order_mode_reorders_and_keeps_selection() {
+        let mut recent = sample_session("recent");
+        recent.last_connected_at = Some(1_000);
+        let mut busy = sample_session("busy");
+        busy.last_connected_at = Some(500);
+        busy.connect_count = 40;
+        let sessions = vec![sample_session("alpha"), busy, recent];
+        let mut state = AppState::new(&sessions);
+        state.set_order_mode(SessionOrderMode::LatestFirst, 1_000);
+        let names = |state: &AppState| -> Vec<String> {
+            state
+                .filtered_sessions()
+                .iter()
+                .map(|session| session.name.clone())
+                .collect()
+        };
+        assert_eq!(names(&state), ["recent", "busy", "alpha"]);
+        state.select_by_name("alpha");
+
+        assert_eq!(
+            state.cycle_order_mode(1_000),
+            SessionOrderMode::FrequencyBased
+        );
+        assert_eq!(names(&state), ["busy", "recent", "alpha"]);
+        assert_eq!(
+            state.cycle_order_mode(1_000),
+            SessionOrderMode::Alphabetical
+        );
+        assert_eq!(names(&state), ["alpha", "busy", "recent"]);
+        assert_eq!(state.selected_session().unwrap().name, "alpha");
+        assert_eq!(state.cycle_order_mode(1_000), SessionOrderMode::LatestFirst);
+    }
+
+    #[test]
+    fn profile_picker_requests_the_highlighted_profile() {
+        let mut state = AppState::new(&[sample_session("office")]);
+        let profiles = vec![
+            "default".to_string(),
+            "home".to_string(),
+            "work".to_string(),
+        ];
+        state.open_profile_picker(profiles, "home");
+        assert_eq!(state.mode(), InputMode::ProfilePicker);
+        assert_eq!(state.profile_picker().unwrap().selected(), 1);
+
+        state.move_profile_selection(true);
+        state.move_profile_selection(true);
+        assert_eq!(state.profile_picker().unwrap().selected(), 2);
+        state.choose_profile();
+        assert_eq!(state.mode(), InputMode::Normal);
+        assert_eq!(state.take_profile_request().as_deref(), Some("work"));
+        assert!(state.take_profile_request().is_none());
+    }
+
+    #[test]
+    fn action_menu_returns_the_highlighted_action() {
+        let mut state = AppState::new(&[sample_session("office")]);
+        assert!(state.open_action_menu());
+        assert_eq!(state.mode(), InputMode::ActionMenu);
+        assert_eq!(state.action_menu(), Some(0));
+
+        state.move_action_selection(false);
+        assert_eq!(state.action_menu(), Some(0));
+        state.move_action_selection(true);
+        state.move_action_selection(true);
+        assert_eq!(state.choose_action(), Some(SessionAction::Edit));
+        assert_eq!(state.mode(), InputMode::Normal);
+        assert_eq!(state.action_menu(), None);
+    }
+
+    #[test]
+    fn action_menu_needs_a_selected_session() {
+        let mut state = AppState::new(&[]);
+        assert!(!state.open_action_menu());
+        assert_eq!(state.mode(), InputMode::Normal);
+    }
+
+    #[test]
+    fn replace_sessions_resets_selection_and_marks() {
+        let mut state = AppState::new(&[sample_session("alpha"), sample_session("beta")]);
+        state.select_by_name("beta");
+        state.toggle_mark_selected();
+        state.replace_sessions(vec![sample_session("gamma"), sample_session("delta")], 0);
+
+        assert_eq!(state.filtered_sessions().len(), 2);
+        assert_eq!(state.selected_index(), Some(0));
+        assert_eq!(state.marked_count(), 0);
+    }
+
+    #[test]
+    fn tag_filter_matches_exact_tags_and_clears() {
+        let mut staging = sample_session("staging");
+        staging.tags = vec!["production-like".to_string()];
+        let sessions = vec![sample_session("alpha"), staging];
+        let mut state = AppState::new(&sessions);
+
+        state.set_tag_filter(Some("prod".to_string()));
+        assert_eq!(state.tag_filter(), Some("prod"));
+        let names: Vec<&str> = state
+            .filtered_sessions()
+            .iter()
+            .map(|session| session.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["alpha"]);
+
+        state.set_tag_filter(None);
+        assert_eq!(state.filtered_sessions().len(), 2);
+    }
+
+    #[test]
+    fn selection_actions_refuse_when_filter_empties_list() {
+        let mut state = AppState::new(&[sample_session("office")]);
+        state.set_filter("no-such-session");
+
+        assert!(!state.start_scp());
+        assert!(state.scp_form().is_none());
+        assert_eq!(state.mode(), InputMode::Normal);
+        assert!(!state.start_delete());
+
+        state.set_filter("");
+        assert!(state.start_scp());
+        assert_eq!(state.scp_form().unwrap().session.name, "office");
+    }
+
+    #[test]
+    fn export_selection_marks_sessions_in_list_order() {
+        let sessions = vec![
+            sample_session("alpha"),
+            sample_session("beta"),
+            sample_session("gamma"),
+        ];
+        let mut state = AppState::new(&sessions);
+        state.start_export_select();
+        assert!(!state.open_export_prompt());
+
+        state.select_last();
+        assert_eq!(state.toggle_mark_selected(), Some(true));
+        state.select_first();
+        assert_eq!(state.toggle_mark_selected(), Some(true));
+        let names: Vec<String> = state
+            .marked_sessions()
+            .into_iter()
+            .map(|session| session.name)
+            .collect();
+        assert_eq!(names, vec!["alpha", "gamma"]);
+
+        assert_eq!(state.toggle_mark_selected(), Some(false));
+        assert!(!state.is_marked("alpha"));
+
+        assert!(state.open_export_prompt());
+        let prompt = state.export_prompt_mut().unwrap();
+        prompt.cycle_format();
+        assert_eq!(prompt.format(), ExportFormat::Yaml);
+
+        state.cancel_export_select();
+        assert_eq!(state.marked_count(), 0);
+        assert!(state.export_prompt().is_none());
+        assert_eq!(state.mode(), InputMode::Normal);
+    }
+
+    #[test]
+    fn mark_all_visible_respects_filter() {
+        let mut db = sample_session("db");
+        db.host = "db.internal".to_string();
+        let mut state = AppState::new(&[sample_session("alpha"), db]);
+        state.set_filter("internal");
+        state.mark_all_visible();
+        assert!(state.is_marked("db"));
+        assert!(!state.is_marked("alpha"));
+    }
+
+    #[test]
+    fn toggle_panel_flips_only_that_panel() {
+        let mut state = AppState::new(&[sample_session("office")]);
+        state.set_panels(PanelVisibility {
+            logo: false,
+            ..PanelVisibility::default()
+        });
+
+        assert!(state.toggle_panel(Panel::Logo));
+        assert!(!state.toggle_panel(Panel::Help));
+        assert_eq!(
+            state.panels(),
+            PanelVisibility {
+                logo: true,
+                search: true,
+                status: true,
+                help: false,
+            }
+        );
+    }
+
+    #[test]
+    fn help_scroll_resets_on_open_and_stays_in_range() {
+        let mut state = AppState::new(&[sample_session("office")]);
+        state.open_help();
+        state.scroll_help_up(3);
+        assert_eq!(state.help_scroll(), 0);
+        state.scroll_help_down(usize::MAX);
+        state.clamp_help_scroll(12);
+        assert_eq!(state.help_scroll(), 12);
+        state.scroll_help_up(2);
+        assert_eq!(state.help_scroll(), 10);
+
+        state.set_mode(InputMode::Normal);
+        state.open_help();
+        assert_eq!(state.mode(), InputMode::Help);
+        assert_eq!(state.help_scroll(), 0);
+    }
+
+    #[test]
+    fn form_fields_follow_configured_order() {
+        let mut state = AppState::new(&[sample_session("office")]);
+        state.set_form_fields(&[AddField::Host, AddField::Name, AddField::Password]);
+        state.start_add_session(None);
+        let form = state.add_form_mut().unwrap();
+        assert_eq!(
+            form.fields(),
+            [
+                AddField::Host,
+                AddField::Name,
+                AddField::Password,
+                AddField::PasswdMode
+            ]
+        );
+        assert_eq!(form.field(), AddField::Host);
+        form.next_field();
+        assert_eq!(form.field(), AddField::Name);
+        form.prev_field();
+        form.prev_field();
+        assert_eq!(form.field(), AddField::PasswdMode);
+    }
+
+    #[test]
+    fn request_discard_only_prompts_for_dirty_forms_when_enabled() {
+        let mut state = AppState::new(&[sample_session("office")]);
+        state.start_add_session(Some("alice".to_string()));
+        assert!(!state.request_discard());
+
+        state.add_form_mut().unwrap().host = "example.com".to_string();
+        assert!(!state.request_discard());
+
+        state.set_confirm_discard(true);
+        assert!(state.request_discard());
+        assert!(state.discard_prompt());
+
+        state.cancel_add_session();
+        assert!(!state.discard_prompt());
+        assert_eq!(state.mode(), InputMode::Normal);
+    }
+
+    #[test]
+    fn edit_form_is_clean_until_a_value_changes() {
+        let session = sample_session("office");
+        let mut state = AppState::new(std::slice::from_ref(&session));
+        state.set_confirm_discard(true);
+        state.start_edit_session(&session);
+        assert!(!state.request_discard());
+
+        state.add_form_mut().unwrap().port = "2222".to_string();
+        assert!(state.request_discard());
+    }
+
+    #[test]
+    fn reachability_filter_keeps_unprobed_sessions_visible() {
+        let sessions = vec![
+            sample_session("alpha"),
+            sample_session("beta"),
+            sample_session("gamma"),
+        ];
+        let mut state = AppState::new(&sessions);
+        state.set_reachability("alpha", true);
+        state.set_reachability("beta", false);
+
+        assert_eq!(
+            state.cycle_reachability_filter(),
+            ReachabilityFilter::Reachable
+        );
+        let names: Vec<_> = state
+            .filtered_sessions()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["alpha", "gamma"]);
+
+        assert_eq!(
+            state.cycle_reachability_filter(),
+            ReachabilityFilter::Unreachable
+        );
+        let names: Vec<_> = state
+            .filtered_sessions()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["beta", "gamma"]);
+
+        let candidates: Vec<_> = state
+            .probe_candidates()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(candidates, vec!["gamma".to_string()]);
+    }
 }
@@ -149,6 +149,51 @@ fn scp_fails_for_nonexistent_session() {
         .stderr(contains("not found"));
 }
 
+#[test]
+fn connect_reports_missing_session() {
+    let (_dir, store_path) = store_path();
+
+    ssher_cmd(&store_path)
+        .args(["connect", "--name", "nonexistent"])
+        .assert()
+        .failure()
+        .stderr(contains("session 'nonexistent' not found"));
+}
+
+#[test]
+fn last_fails_when_nothing_was_connected() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .arg("last")
+        .assert()
+        .failure()
+        .stderr(contains("no session has been connected to yet"));
+}
+
+#[test]
+fn exec_reports_tag_without_sessions() {
+    let (_dir, store_path) = store_path();
+
+    ssher_cmd(&store_path)
+        .args(["exec", "--tag", "missing", "--", "uptime"])
+        .assert()
+        .failure()
+        .stderr(contains("no sessions are tagged 'missing'"));
+}
+
 #[test]
 fn scp_to_direction_generates_correct_command() {
     let (_dir, store_path) = store_path();
@@ -382,3 +427,1860 @@ fn update_preserves_unset_fields() {
         .stdout(contains("me@newhost.example.com"))
         .stdout(contains("2222"));
 }
+
+#[test]
+fn store_repair_removes_duplicates() {
+    let (_dir, store_path) = store_path();
+    std::fs::write(
+        &store_path,
+        r#"[
+            {"name":"office","host":"a.example.com","user":"me","port":22},
+            {"name":"office","host":"b.example.com","user":"me","port":22}
+        ]"#,
+    )
+    .expect("write");
+
+    ssher_cmd(&store_path)
+        .args(["list"])
+        .assert()
+        .failure()
+        .stderr(contains("duplicate session 'office'"));
+
+    ssher_cmd(&store_path)
+        .args(["store", "repair"])
+        .assert()
+        .success()
+        .stdout(contains("Removed duplicate session 'office'"));
+
+    ssher_cmd(&store_path)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(contains("me@a.example.com"))
+        .stdout(contains("b.example.com").not());
+}
+
+#[test]
+fn export_ssh_config_groups_by_tag() {
+    let (_dir, store_path) = store_path();
+
+    for (name, tag) in [("web", Some("prod")), ("db", Some("prod")), ("box", None)] {
+        let mut args = vec![
+            "add",
+            "--name",
+            name,
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ];
+        if let Some(tag) = tag {
+            args.extend(["--tag", tag]);
+        }
+        ssher_cmd(&store_path).args(args).assert().success();
+    }
+
+    let output = ssher_cmd(&store_path)
+        .args([
+            "export",
+            "--format",
+            "ssh-config",
+            "--group-by",
+            "tag",
+            "--sort",
+        ])
+        .output()
+        .expect("export");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let prod = stdout.find("# === prod ===").expect("prod header");
+    let misc = stdout.find("# === misc ===").expect("misc header");
+    let db = stdout.find("Host db").expect("db host");
+    let web = stdout.find("Host web").expect("web host");
+    let boxed = stdout.find("Host box").expect("box host");
+    assert!(prod < db && db < web && web < misc && misc < boxed);
+}
+
+#[test]
+fn export_ssh_config_minimal_template() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add", "--name", "web", "--host", "web", "--user", "me", "--tag", "prod",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["export", "--format", "ssh-config", "--template", "minimal"])
+        .assert()
+        .success()
+        .stdout("Host web\n    User me\n\n");
+
+    ssher_cmd(&store_path)
+        .args([
+            "export",
+            "--format",
+            "ssh-config",
+            "--template",
+            "minimal",
+            "--include-hostname-always",
+        ])
+        .assert()
+        .success()
+        .stdout("Host web\n    HostName web\n    User me\n\n");
+
+    ssher_cmd(&store_path)
+        .args([
+            "export",
+            "--format",
+            "ssh-config",
+            "--header",
+            "team hosts",
+            "--no-tags-comment",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("# team hosts\n"))
+        .stdout(contains("    Port 22\n"))
+        .stdout(contains("# Tags").not());
+}
+
+#[test]
+fn encrypted_store_needs_the_right_passphrase() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .env("SSHER_PASSPHRASE", "open sesame")
+        .args([
+            "--encrypted",
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.internal",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+    let raw = std::fs::read(&store_path).expect("read store");
+    assert!(raw.starts_with(b"SSHER-ENCRYPTED-1"));
+    assert!(!String::from_utf8_lossy(&raw).contains("web.internal"));
+
+    // Detected from the file header, without --encrypted.
+    ssher_cmd(&store_path)
+        .env("SSHER_PASSPHRASE", "open sesame")
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(contains("me@web.internal"));
+
+    ssher_cmd(&store_path)
+        .env("SSHER_PASSPHRASE", "wrong")
+        .args(["list"])
+        .assert()
+        .failure()
+        .stderr(contains("wrong passphrase"));
+}
+
+#[test]
+fn export_and_import_round_trip_through_gzip() {
+    let (dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.internal",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    let archive = dir.path().join("sessions.json.gz");
+    ssher_cmd(&store_path)
+        .args(["export", "--output"])
+        .arg(&archive)
+        .assert()
+        .success();
+    let raw = std::fs::read(&archive).expect("read archive");
+    assert!(raw.starts_with(&[0x1f, 0x8b]));
+
+    let other_store = dir.path().join("other.json");
+    ssher_cmd(&other_store)
+        .args(["import", "--input"])
+        .arg(&archive)
+        .assert()
+        .success();
+    ssher_cmd(&other_store)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(contains("me@web.internal"));
+}
+
+#[test]
+fn sqlite_store_is_picked_by_extension() {
+    let dir = tempdir().expect("tempdir");
+    let store_path = dir.path().join("sessions.db");
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.internal",
+            "--user",
+            "me",
+            "--tag",
+            "prod",
+        ])
+        .assert()
+        .success();
+    let raw = std::fs::read(&store_path).expect("read store");
+    assert!(raw.starts_with(b"SQLite format 3"));
+
+    ssher_cmd(&store_path)
+        .args(["list", "--tag", "prod"])
+        .assert()
+        .success()
+        .stdout(contains("me@web.internal"));
+    ssher_cmd(&store_path)
+        .args(["remove", "--name", "web"])
+        .assert()
+        .success();
+    ssher_cmd(&store_path)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(contains("web.internal").not());
+}
+
+#[test]
+fn export_anonymize_replaces_hosts_and_users() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "office",
+            "--host",
+            "gw.corp.internal",
+            "--user",
+            "alice",
+            "--identity-file",
+            "/home/alice/.ssh/corp_ed25519",
+            "--tag",
+            "work",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["export", "--format", "ssh-config", "--anonymize"])
+        .assert()
+        .success()
+        .stdout(contains("Host office"))
+        .stdout(contains("HostName host1.example.com"))
+        .stdout(contains("User user1"))
+        .stdout(contains("IdentityFile ~/.ssh/id_key1"))
+        .stdout(contains("corp").not())
+        .stdout(contains("alice").not());
+}
+
+#[test]
+fn export_csv_columns_selects_fields() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "office",
+            "--host",
+            "office.example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["export", "--format", "csv", "--columns", "host,name"])
+        .assert()
+        .success()
+        .stdout("host,name\noffice.example.com,office\n");
+
+    ssher_cmd(&store_path)
+        .args(["export", "--format", "json", "--columns", "name"])
+        .assert()
+        .failure()
+        .stderr(contains("--columns supports only the csv format"));
+}
+
+#[test]
+fn export_clipboard_uses_a_clipboard_helper_or_falls_back_to_stdout() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args(["add", "--name", "office", "--host", "office.example.com"])
+        .args(["--user", "me"])
+        .assert()
+        .success();
+
+    // No helper on PATH: the export is printed with a warning.
+    let empty_bin = dir.path().join("empty-bin");
+    std::fs::create_dir(&empty_bin).unwrap();
+    ssher_cmd(&store_path)
+        .env("PATH", &empty_bin)
+        .env_remove("WAYLAND_DISPLAY")
+        .args(["export", "--format", "ssh-config", "--clipboard"])
+        .assert()
+        .success()
+        .stdout(contains("Host office\n"))
+        .stderr(contains("Warning: no clipboard helper available"));
+
+    // A stand-in xclip receives the text instead.
+    let bin = dir.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    let clip = dir.path().join("clipboard.txt");
+    let xclip = bin.join("xclip");
+    std::fs::write(&xclip, format!("#!/bin/sh\ncat > '{}'\n", clip.display())).unwrap();
+    std::fs::set_permissions(&xclip, std::fs::Permissions::from_mode(0o755)).unwrap();
+    ssher_cmd(&store_path)
+        .env("PATH", format!("{}:/bin:/usr/bin", bin.display()))
+        .env_remove("WAYLAND_DISPLAY")
+        .args(["export", "--format", "ssh-config", "--clipboard"])
+        .assert()
+        .success()
+        .stdout(contains("Copied 1 sessions to the clipboard (via xclip)"));
+    assert!(
+        std::fs::read_to_string(&clip)
+            .unwrap()
+            .contains("    HostName office.example.com\n")
+    );
+
+    ssher_cmd(&store_path)
+        .args(["export", "--clipboard", "--output", "x.json"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn export_output_dir_writes_one_file_per_session() {
+    let (dir, store_path) = store_path();
+
+    for name in ["web", "db"] {
+        ssher_cmd(&store_path)
+            .args([
+                "add",
+                "--name",
+                name,
+                "--host",
+                "example.com",
+                "--user",
+                "me",
+            ])
+            .assert()
+            .success();
+    }
+
+    let hosts_dir = dir.path().join("hosts");
+    ssher_cmd(&store_path)
+        .args(["export", "--format", "ssh-config", "--output-dir"])
+        .arg(&hosts_dir)
+        .assert()
+        .success()
+        .stdout(contains("Exported 2 sessions"));
+
+    let web = std::fs::read_to_string(hosts_dir.join("web.conf")).expect("web.conf");
+    assert!(web.contains("Host web"));
+    assert!(!web.contains("Host db"));
+    assert!(hosts_dir.join("db.conf").exists());
+
+    ssher_cmd(&store_path)
+        .args(["export", "--output", "all.json", "--output-dir"])
+        .arg(&hosts_dir)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn export_refuses_to_overwrite_without_force() {
+    let (dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    let output = dir.path().join("export.json");
+    std::fs::write(&output, "keep me").expect("seed output");
+
+    ssher_cmd(&store_path)
+        .args(["export", "--output"])
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(contains("already exists (use --force to overwrite)"));
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "keep me");
+
+    ssher_cmd(&store_path)
+        .args(["export", "--force", "--output"])
+        .arg(&output)
+        .assert()
+        .success();
+    assert!(
+        std::fs::read_to_string(&output)
+            .unwrap()
+            .contains("\"web\"")
+    );
+}
+
+#[test]
+fn export_append_skips_existing_hosts() {
+    let (dir, store_path) = store_path();
+    for (name, host) in [("prod", "new.example.com"), ("web", "web.example.com")] {
+        ssher_cmd(&store_path)
+            .args(["add", "--name", name, "--host", host, "--user", "me"])
+            .assert()
+            .success();
+    }
+
+    let config = dir.path().join("ssh_config");
+    std::fs::write(&config, "Host prod\n    HostName old.example.com\n").expect("seed config");
+
+    ssher_cmd(&store_path)
+        .args(["export", "--format", "ssh-config", "--append", "--output"])
+        .arg(&config)
+        .assert()
+        .success()
+        .stdout(contains("Appended 1 hosts"))
+        .stdout(contains("Skipped existing hosts: prod"));
+    let content = std::fs::read_to_string(&config).unwrap();
+    assert_eq!(content.matches("Host prod").count(), 1);
+    assert!(content.contains("old.example.com"));
+    assert!(content.contains("Host web"));
+
+    ssher_cmd(&store_path)
+        .args([
+            "export",
+            "--format",
+            "ssh-config",
+            "--append",
+            "--force",
+            "--output",
+        ])
+        .arg(&config)
+        .assert()
+        .success()
+        .stdout(contains("Updated existing hosts: prod, web"));
+    let content = std::fs::read_to_string(&config).unwrap();
+    assert_eq!(content.matches("Host prod").count(), 1);
+    assert_eq!(content.matches("Host web").count(), 1);
+    assert!(content.contains("new.example.com"));
+    assert!(!content.contains("old.example.com"));
+}
+
+#[test]
+fn control_master_round_trips_through_ssh_config() {
+    let (dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.example.com",
+            "--user",
+            "me",
+            "--control-master",
+            "--control-path",
+            "/tmp/cm-%r@%h:%p",
+        ])
+        .assert()
+        .success();
+
+    let config = dir.path().join("ssh_config");
+    ssher_cmd(&store_path)
+        .args(["export", "--format", "ssh-config", "--output"])
+        .arg(&config)
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(&config).unwrap();
+    assert!(content.contains("ControlMaster auto"));
+    assert!(content.contains("ControlPath /tmp/cm-%r@%h:%p"));
+
+    let imported = dir.path().join("imported.json");
+    ssher_cmd(&imported)
+        .args(["import", "--format", "ssh-config", "--input"])
+        .arg(&config)
+        .assert()
+        .success();
+    let stored = std::fs::read_to_string(&imported).unwrap();
+    assert!(stored.contains("\"control_master\": true"));
+    assert!(stored.contains("/tmp/cm-%r@%h:%p"));
+}
+
+#[test]
+fn proxy_jump_round_trips_through_ssh_config() {
+    let (dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "internal",
+            "--host",
+            "10.0.0.5",
+            "--user",
+            "me",
+            "--proxy-jump",
+            "ops@bastion.example.com:2222",
+        ])
+        .assert()
+        .success();
+
+    let config = dir.path().join("ssh_config");
+    ssher_cmd(&store_path)
+        .args(["export", "--format", "ssh-config", "--output"])
+        .arg(&config)
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(&config).unwrap();
+    assert!(content.contains("ProxyJump ops@bastion.example.com:2222"));
+
+    let imported = dir.path().join("imported.json");
+    ssher_cmd(&imported)
+        .args(["import", "--format", "ssh-config", "--input"])
+        .arg(&config)
+        .assert()
+        .success();
+    let stored = std::fs::read_to_string(&imported).unwrap();
+    assert!(stored.contains("\"proxy_jump\": \"ops@bastion.example.com:2222\""));
+
+    ssher_cmd(&store_path)
+        .args(["update", "--name", "internal", "--proxy-jump", ""])
+        .assert()
+        .success();
+    let stored = std::fs::read_to_string(&store_path).unwrap();
+    assert!(!stored.contains("proxy_jump"));
+}
+
+#[test]
+fn import_ssh_config_keeps_hosts_without_hostname() {
+    let (dir, store_path) = store_path();
+    let config = dir.path().join("ssh_config");
+    std::fs::write(
+        &config,
+        "Host server\n    User admin\n    Port 2222\n\nHost web\n    HostName web.example.com\n",
+    )
+    .unwrap();
+
+    ssher_cmd(&store_path)
+        .args(["import", "--format", "ssh-config", "--input"])
+        .arg(&config)
+        .assert()
+        .success();
+    ssher_cmd(&store_path)
+        .args(["list", "--tabs"])
+        .assert()
+        .success()
+        .stdout(contains("server\tadmin@server\t2222"))
+        .stdout(contains("web\troot@web.example.com\t22"));
+}
+
+#[test]
+fn list_tag_filter_requires_every_tag() {
+    let (_dir, store_path) = store_path();
+    std::fs::write(
+        &store_path,
+        r#"[
+  {"name": "api", "host": "a.example.com", "user": "me", "port": 22, "tags": ["prod", "eu"]},
+  {"name": "web", "host": "w.example.com", "user": "me", "port": 22, "tags": ["prod"]},
+  {"name": "scratch", "host": "s.example.com", "user": "me", "port": 22}
+]"#,
+    )
+    .unwrap();
+
+    let names = |tags: &[&str]| -> Vec<String> {
+        let mut cmd = ssher_cmd(&store_path);
+        cmd.args(["list", "--tabs"]);
+        for tag in tags {
+            cmd.args(["--tag", tag]);
+        }
+        let output = cmd.output().unwrap();
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|line| line.split('\t').next().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(names(&[]), ["api", "scratch", "web"]);
+    assert_eq!(names(&["prod"]), ["api", "web"]);
+    assert_eq!(names(&["prod", "eu"]), ["api"]);
+    assert!(names(&["staging"]).is_empty());
+}
+
+#[test]
+fn list_group_by_tag_prints_sessions_under_tag_headers() {
+    let (_dir, store_path) = store_path();
+    std::fs::write(
+        &store_path,
+        r#"[
+  {"name": "api", "host": "a.example.com", "user": "me", "port": 22, "tags": ["prod", "backend"]},
+  {"name": "scratch", "host": "s.example.com", "user": "me", "port": 22}
+]"#,
+    )
+    .unwrap();
+
+    ssher_cmd(&store_path)
+        .args(["list", "--group-by", "tag"])
+        .assert()
+        .success()
+        .stdout(contains("backend\n  api "))
+        .stdout(contains("prod\n  api "))
+        .stdout(contains("(none)\n  scratch "));
+
+    ssher_cmd(&store_path)
+        .args(["list", "--group-by", "tag", "--first-tag-only"])
+        .assert()
+        .success()
+        .stdout(contains("backend\n").not())
+        .stdout(contains("prod\n  api "));
+
+    ssher_cmd(&store_path)
+        .args(["list", "--group-by", "tag", "--tabs"])
+        .assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
+
+#[test]
+fn list_sort_recent_puts_connected_sessions_first() {
+    let (_dir, store_path) = store_path();
+    std::fs::write(
+        &store_path,
+        r#"[
+  {"name": "alpha", "host": "a.example.com", "user": "me", "port": 22},
+  {"name": "beta", "host": "b.example.com", "user": "me", "port": 22, "last_connected_at": 100, "connect_count": 9},
+  {"name": "gamma", "host": "c.example.com", "user": "me", "port": 22, "last_connected_at": 200, "connect_count": 1}
+]"#,
+    )
+    .unwrap();
+
+    let names = |sort: &str| -> Vec<String> {
+        let output = ssher_cmd(&store_path)
+            .args(["list", "--tabs", "--sort", sort])
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|line| line.split('\t').next().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(names("name"), ["alpha", "beta", "gamma"]);
+    assert_eq!(names("recent"), ["gamma", "beta", "alpha"]);
+    assert_eq!(names("frequency"), ["beta", "gamma", "alpha"]);
+}
+
+#[test]
+fn disconnect_without_control_master_is_a_no_op() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["disconnect", "--name", "web"])
+        .assert()
+        .success()
+        .stdout(contains("web does not use ControlMaster"));
+    ssher_cmd(&store_path)
+        .args(["disconnect", "--all"])
+        .assert()
+        .success()
+        .stdout(contains("No sessions use ControlMaster"));
+    ssher_cmd(&store_path).arg("disconnect").assert().failure();
+}
+
+#[test]
+fn scp_requires_local_without_default_local_dir() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["scp", "--name", "web", "--remote", "/tmp/app.log"])
+        .assert()
+        .failure()
+        .stderr(contains("session 'web' has no default local dir"));
+
+    ssher_cmd(&store_path)
+        .args([
+            "scp",
+            "--name",
+            "web",
+            "--local",
+            "a.log",
+            "--local",
+            "b.log",
+            "--remote",
+            "/tmp/",
+            "--direction",
+            "from",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "several --local paths only work with --direction to",
+        ));
+
+    ssher_cmd(&store_path)
+        .args([
+            "update",
+            "--name",
+            "web",
+            "--default-local-dir",
+            "~/transfers",
+        ])
+        .assert()
+        .success();
+    let stored = std::fs::read_to_string(&store_path).unwrap();
+    assert!(stored.contains("\"default_local_dir\": \"~/transfers\""));
+}
+
+#[test]
+fn update_new_name_renames_and_edits_in_one_call() {
+    let (_dir, store_path) = store_path();
+    for name in ["old", "taken"] {
+        ssher_cmd(&store_path)
+            .args([
+                "add",
+                "--name",
+                name,
+                "--host",
+                "web.example.com",
+                "--user",
+                "me",
+            ])
+            .assert()
+            .success();
+    }
+
+    ssher_cmd(&store_path)
+        .args(["update", "--name", "old", "--new-name", "taken"])
+        .assert()
+        .failure()
+        .stderr(contains("session 'taken' already exists"));
+
+    ssher_cmd(&store_path)
+        .args([
+            "update",
+            "--name",
+            "old",
+            "--new-name",
+            "new",
+            "--user",
+            "deploy",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Renamed session: old -> new"));
+
+    ssher_cmd(&store_path)
+        .args(["list", "--tabs"])
+        .assert()
+        .success()
+        .stdout(contains("new\tdeploy@web.example.com"))
+        .stdout(contains("old\t").not());
+}
+
+#[test]
+fn update_new_name_force_replaces_the_colliding_session() {
+    let (_dir, store_path) = store_path();
+    std::fs::write(
+        &store_path,
+        r#"[
+  {"name": "old", "host": "new.example.com", "user": "me", "port": 22, "last_connected_at": 100, "connect_count": 2},
+  {"name": "taken", "host": "stale.example.com", "user": "me", "port": 22, "last_connected_at": 300, "connect_count": 5, "last_duration_secs": 60, "avg_duration_secs": 45}
+]"#,
+    )
+    .unwrap();
+
+    ssher_cmd(&store_path)
+        .args(["update", "--name", "old", "--new-name", "taken"])
+        .assert()
+        .failure()
+        .stderr(contains("use --force to replace it"));
+
+    ssher_cmd(&store_path)
+        .args(["update", "--name", "old", "--new-name", "taken", "--force"])
+        .assert()
+        .success()
+        .stdout(contains("Replacing existing session: taken"))
+        .stdout(contains("Renamed session: old -> taken"));
+
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+    let sessions = stored["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 1);
+    let taken = &sessions[0];
+    assert_eq!(taken["name"], "taken");
+    assert_eq!(taken["host"], "new.example.com");
+    assert_eq!(taken["last_connected_at"], 300);
+    assert_eq!(taken["connect_count"], 7);
+    assert_eq!(taken["last_duration_secs"], 60);
+}
+
+#[test]
+fn add_and_update_manage_extra_ssh_options() {
+    let (_dir, store_path) = store_path();
+    let add = |extra: &[&str]| {
+        let mut cmd = ssher_cmd(&store_path);
+        cmd.args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.example.com",
+            "--user",
+            "me",
+        ])
+        .args(extra);
+        cmd
+    };
+    add(&["--option", "ServerAliveInterval"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid ssh option 'ServerAliveInterval'"));
+    add(&[
+        "--option",
+        "ServerAliveInterval=30",
+        "--option",
+        "StrictHostKeyChecking=accept-new",
+    ])
+    .assert()
+    .success();
+
+    ssher_cmd(&store_path)
+        .args([
+            "update",
+            "--name",
+            "web",
+            "--remove-option",
+            "StrictHostKeyChecking",
+            "--option",
+            "ServerAliveInterval=60",
+        ])
+        .assert()
+        .success();
+    ssher_cmd(&store_path)
+        .args(["update", "--name", "web", "--remove-option", "Compression"])
+        .assert()
+        .failure()
+        .stderr(contains("session 'web' has no ssh option 'Compression'"));
+
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+    assert_eq!(
+        stored["sessions"][0]["options"],
+        serde_json::json!({"ServerAliveInterval": "60"})
+    );
+}
+
+#[test]
+fn add_and_update_manage_port_forwards() {
+    let (_dir, store_path) = store_path();
+    let add = |extra: &[&str]| {
+        let mut cmd = ssher_cmd(&store_path);
+        cmd.args([
+            "add",
+            "--name",
+            "db",
+            "--host",
+            "db.example.com",
+            "--user",
+            "me",
+        ])
+        .args(extra);
+        cmd
+    };
+    add(&["--local-forward", "5432:localhost"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid forward '5432:localhost'"));
+    add(&[
+        "--local-forward",
+        "5432:localhost:5432",
+        "--local-forward",
+        "6379:cache:6379",
+        "--remote-forward",
+        "9000:localhost:3000",
+    ])
+    .assert()
+    .success();
+
+    ssher_cmd(&store_path)
+        .args([
+            "update",
+            "--name",
+            "db",
+            "--local-forward",
+            "15432:localhost:5432",
+            "--remote-forward",
+            "",
+        ])
+        .assert()
+        .success();
+
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+    let db = &stored["sessions"][0];
+    assert_eq!(
+        db["local_forwards"],
+        serde_json::json!(["15432:localhost:5432"])
+    );
+    assert_eq!(db["remote_forwards"], serde_json::Value::Null);
+}
+
+#[test]
+fn tunnel_needs_a_valid_forward() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "db",
+            "--host",
+            "db.example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["tunnel", "--name", "db", "-N"])
+        .assert()
+        .failure()
+        .stderr(contains("give at least one --local or --remote forward"));
+    ssher_cmd(&store_path)
+        .args(["tunnel", "--name", "db", "-L", "5432"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid forward '5432'"));
+    ssher_cmd(&store_path)
+        .args(["tunnel", "--name", "nope", "-L", "5432:localhost:5432"])
+        .assert()
+        .failure()
+        .stderr(contains("session 'nope' not found"));
+}
+
+#[test]
+fn add_and_update_keep_each_ssh_arg_separate() {
+    let (_dir, store_path) = store_path();
+    let read_args = || {
+        let stored: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+        stored["sessions"][0]["extra_args"].clone()
+    };
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.example.com",
+            "--user",
+            "me",
+            "--ssh-arg",
+            "-o",
+            "--ssh-arg",
+            "SetEnv=GREETING=hello world",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        read_args(),
+        serde_json::json!(["-o", "SetEnv=GREETING=hello world"])
+    );
+
+    ssher_cmd(&store_path)
+        .args(["update", "--name", "web", "--ssh-arg=-A"])
+        .assert()
+        .success();
+    assert_eq!(read_args(), serde_json::json!(["-A"]));
+
+    ssher_cmd(&store_path)
+        .args(["update", "--name", "web", "--ssh-arg", ""])
+        .assert()
+        .success();
+    assert_eq!(read_args(), serde_json::Value::Null);
+}
+
+#[test]
+fn tag_add_and_remove_keep_the_other_tags() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.example.com",
+            "--user",
+            "me",
+            "--tag",
+            "frontend",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args([
+            "tag",
+            "add",
+            "--name",
+            "web",
+            "--tag",
+            "prod,frontend",
+            "--tag",
+            " eu ",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Tags for web: frontend, prod, eu"));
+    ssher_cmd(&store_path)
+        .args([
+            "tag",
+            "remove",
+            "--name",
+            "web",
+            "--tag",
+            "frontend,staging",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Tags for web: prod, eu"));
+    ssher_cmd(&store_path)
+        .args(["tag", "add", "--name", "nope", "--tag", "prod"])
+        .assert()
+        .failure()
+        .stderr(contains("session 'nope' not found"));
+
+    ssher_cmd(&store_path)
+        .args(["list", "--tabs"])
+        .assert()
+        .success()
+        .stdout(contains("\tprod,eu\t"));
+}
+
+#[test]
+fn clone_copies_settings_without_history() {
+    let (_dir, store_path) = store_path();
+    std::fs::write(
+        &store_path,
+        r#"[
+  {"name": "web", "host": "web.example.com", "user": "deploy", "port": 2222, "tags": ["prod"], "last_connected_at": 300, "connect_count": 4},
+  {"name": "db", "host": "db.example.com", "user": "me", "port": 22}
+]"#,
+    )
+    .unwrap();
+
+    ssher_cmd(&store_path)
+        .args(["clone", "--name", "missing", "--new-name", "web2"])
+        .assert()
+        .failure()
+        .stderr(contains("session 'missing' not found"));
+    ssher_cmd(&store_path)
+        .args(["clone", "--name", "web", "--new-name", "db"])
+        .assert()
+        .failure()
+        .stderr(contains("session 'db' already exists"));
+
+    ssher_cmd(&store_path)
+        .args(["clone", "--name", "web", "--new-name", "web2"])
+        .assert()
+        .success()
+        .stdout(contains("Cloned session: web -> web2"));
+
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+    let web2 = stored["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|session| session["name"] == "web2")
+        .expect("clone stored");
+    assert_eq!(web2["host"], "web.example.com");
+    assert_eq!(web2["port"], 2222);
+    assert_eq!(web2["tags"], serde_json::json!(["prod"]));
+    assert!(web2.get("last_connected_at").is_none());
+    assert!(web2.get("connect_count").is_none());
+}
+
+#[test]
+fn doctor_warns_about_placeholder_names() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout(contains("No problems found"));
+
+    for name in ["host", "web"] {
+        ssher_cmd(&store_path)
+            .args([
+                "add",
+                "--name",
+                name,
+                "--host",
+                "web.example.com",
+                "--user",
+                "me",
+            ])
+            .assert()
+            .success();
+    }
+    ssher_cmd(&store_path)
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout(contains("warning: host: name looks like a placeholder"))
+        .stdout(contains(
+            "warning: host, web: 2 sessions share the target me@web.example.com:22",
+        ));
+}
+
+#[test]
+fn identity_lists_the_key_directory() {
+    let (dir, store_path) = store_path();
+    let keys = dir.path().join("keys");
+    std::fs::create_dir(&keys).expect("keys dir");
+    std::fs::write(keys.join("id_ed25519"), "PRIVATE KEY BODY").expect("key");
+    std::fs::write(keys.join("id_ed25519.pub"), "public").expect("pub key");
+
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "web.example.com",
+            "--user",
+            "me",
+        ])
+        .arg("--identity-file")
+        .arg(keys.join("id_ed25519"))
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["identity", "--name", "web"])
+        .assert()
+        .success()
+        .stdout(contains("(exists)"))
+        .stdout(contains("  id_ed25519\n"))
+        .stdout(contains("  id_ed25519.pub (public key)"))
+        .stdout(contains("PRIVATE KEY BODY").not());
+}
+
+#[test]
+fn stats_lists_durations_longest_average_first() {
+    let (_dir, store_path) = store_path();
+    std::fs::write(
+        &store_path,
+        r#"[
+            {"name":"short","host":"a","user":"me","port":22,"last_duration_secs":30,"avg_duration_secs":40},
+            {"name":"long","host":"b","user":"me","port":22,"last_duration_secs":192,"avg_duration_secs":3700},
+            {"name":"never","host":"c","user":"me","port":22}
+        ]"#,
+    )
+    .expect("seed store");
+
+    let output = ssher_cmd(&store_path)
+        .arg("stats")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let lines: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            "NAME LAST AVERAGE",
+            "long 3m12s 1h01m",
+            "short 30s 40s",
+            "never - -"
+        ]
+    );
+}
+
+#[test]
+fn history_filters_by_date_window() {
+    let (_dir, store_path) = store_path();
+    std::fs::write(
+        &store_path,
+        r#"[
+            {"name":"jan","host":"a","user":"me","port":22,"last_connected_at":1704456000},
+            {"name":"feb","host":"b","user":"me","port":22,"last_connected_at":1707134400},
+            {"name":"never","host":"c","user":"me","port":22}
+        ]"#,
+    )
+    .expect("seed store");
+
+    ssher_cmd(&store_path)
+        .args(["history", "--since", "2024-01-01", "--until", "2024-01-31"])
+        .assert()
+        .success()
+        .stdout(contains("2024-01-05 12:00  jan  me@a"))
+        .stdout(contains("feb").not())
+        .stdout(contains("only each session's most recent connection"));
+
+    ssher_cmd(&store_path)
+        .args(["history", "--since", "last-tuesday"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid date 'last-tuesday'"));
+}
+
+#[test]
+fn history_log_lists_every_logged_connection() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args(["config", "set", "log_connections", "true"])
+        .assert()
+        .success();
+    ssher_cmd(&store_path)
+        .args(["config", "get", "log_connections"])
+        .assert()
+        .success()
+        .stdout(contains("true"));
+    std::fs::write(
+        store_path.with_file_name("sessions.connections.jsonl"),
+        concat!(
+            r#"{"session":"web","timestamp":1704456000,"kind":"ssh","duration_secs":192}"#,
+            "\n",
+            r#"{"session":"web","timestamp":1704542400,"kind":"scp","duration_secs":5}"#,
+            "\n",
+            r#"{"session":"db","timestamp":1707134400,"kind":"ssh","duration_secs":30}"#,
+            "\n",
+        ),
+    )
+    .expect("seed log");
+
+    ssher_cmd(&store_path)
+        .args(["history", "--log", "--until", "2024-01-31"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "2024-01-06 12:00  scp  web  5s\n2024-01-05 12:00  ssh  web  3m12s\n",
+        ))
+        .stdout(contains("db").not());
+}
+
+#[test]
+fn history_prune_trims_rotated_connection_log() {
+    let (_dir, store_path) = store_path();
+    ssher_cmd(&store_path)
+        .args(["config", "set", "log_max_entries", "100"])
+        .assert()
+        .success();
+    ssher_cmd(&store_path)
+        .args(["config", "list"])
+        .assert()
+        .success()
+        .stdout(contains("log_max_entries: 100"))
+        .stdout(contains("log_max_bytes: <not set>"));
+    ssher_cmd(&store_path)
+        .args(["config", "set", "log_max_bytes", "lots"])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid value 'lots' for log_max_bytes"));
+
+    let log_path = store_path.with_file_name("sessions.connections.jsonl");
+    let line = |session: &str, timestamp: i64| {
+        format!(
+            r#"{{"session":"{session}","timestamp":{timestamp},"kind":"ssh","duration_secs":1}}"#
+        ) + "\n"
+    };
+    std::fs::write(
+        store_path.with_file_name("sessions.connections.jsonl.1"),
+        line("older", 1704369600) + &line("old", 1704456000),
+    )
+    .expect("seed rotated log");
+    std::fs::write(&log_path, line("new", 1704542400)).expect("seed log");
+
+    ssher_cmd(&store_path)
+        .args(["history", "--log"])
+        .assert()
+        .success()
+        .stdout(contains("older"))
+        .stdout(contains("new"));
+
+    ssher_cmd(&store_path)
+        .args(["history", "prune", "--keep", "2"])
+        .assert()
+        .success()
+        .stdout(contains("Pruned 1 connection log entries"));
+    assert!(
+        !store_path
+            .with_file_name("sessions.connections.jsonl.1")
+            .exists()
+    );
+    ssher_cmd(&store_path)
+        .args(["history", "--log"])
+        .assert()
+        .success()
+        .stdout(contains("older").not())
+        .stdout(contains("old"))
+        .stdout(contains("new"));
+}
+
+#[test]
+fn add_without_name_derives_unique_name_from_host() {
+    let (_dir, store_path) = store_path();
+    for _ in 0..2 {
+        ssher_cmd(&store_path)
+            .args(["add", "--host", "web1.example.com", "--user", "me"])
+            .assert()
+            .success();
+    }
+
+    ssher_cmd(&store_path)
+        .args(["list", "--tabs"])
+        .assert()
+        .success()
+        .stdout(contains("web1\tme@web1.example.com"))
+        .stdout(contains("web1-2\tme@web1.example.com"));
+}
+
+#[test]
+fn add_require_reachable_refuses_closed_port() {
+    let (_dir, store_path) = store_path();
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .expect("bind")
+        .local_addr()
+        .expect("addr")
+        .port()
+        .to_string();
+
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "down",
+            "--host",
+            "127.0.0.1",
+            "--user",
+            "me",
+            "--port",
+            &port,
+            "--require-reachable",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("refusing to save unreachable session"));
+
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "down",
+            "--host",
+            "127.0.0.1",
+            "--user",
+            "me",
+            "--port",
+            &port,
+            "--test",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("Warning"));
+}
+
+#[test]
+fn profiles_of_a_sqlite_store_are_sqlite() {
+    let dir = tempdir().expect("tempdir");
+    let store_path = dir.path().join("sessions.db");
+    ssher_cmd(&store_path)
+        .args([
+            "--profile",
+            "work",
+            "add",
+            "--name",
+            "office",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+    assert!(dir.path().join("profiles/work.db").exists());
+
+    ssher_cmd(&store_path)
+        .args(["profile", "clone", "--from", "work", "--to", "backup"])
+        .assert()
+        .success();
+    ssher_cmd(&store_path)
+        .args(["--profile", "backup", "list"])
+        .assert()
+        .success()
+        .stdout(contains("office"));
+    assert!(dir.path().join("profiles/backup.db").exists());
+}
+
+#[test]
+fn profile_clone_copies_store_and_refuses_overwrite() {
+    let (_dir, store_path) = store_path();
+
+    ssher_cmd(&store_path)
+        .args([
+            "--profile",
+            "work",
+            "add",
+            "--name",
+            "office",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["profile", "clone", "--from", "work", "--to", "work-backup"])
+        .assert()
+        .success()
+        .stdout(contains("Cloned profile 'work' to 'work-backup'"));
+
+    ssher_cmd(&store_path)
+        .args(["--profile", "work-backup", "list"])
+        .assert()
+        .success()
+        .stdout(contains("office"));
+
+    ssher_cmd(&store_path)
+        .args(["profile", "clone", "--from", "work", "--to", "work-backup"])
+        .assert()
+        .failure()
+        .stderr(contains("already exists"));
+
+    ssher_cmd(&store_path)
+        .args([
+            "profile",
+            "clone",
+            "--from",
+            "work",
+            "--to",
+            "work-backup",
+            "--force",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn import_into_profile_leaves_active_store_alone() {
+    let (dir, store_path) = store_path();
+    let config = dir.path().join("ssh_config");
+    std::fs::write(&config, "Host shared\n    HostName shared.example.com\n").unwrap();
+
+    ssher_cmd(&store_path)
+        .args(["import", "--format", "ssh-config", "--into-profile", "team"])
+        .arg("--input")
+        .arg(&config)
+        .assert()
+        .success()
+        .stdout(contains("Wrote profile 'team'"));
+
+    // Skipping the only (conflicting) session writes nothing.
+    ssher_cmd(&store_path)
+        .args(["import", "--format", "ssh-config", "--into-profile", "team"])
+        .arg("--input")
+        .arg(&config)
+        .write_stdin("s\n")
+        .assert()
+        .success()
+        .stdout(contains("Skipped 1 conflict(s)"))
+        .stdout(contains("Wrote profile").not());
+
+    ssher_cmd(&store_path)
+        .args(["--profile", "team", "list"])
+        .assert()
+        .success()
+        .stdout(contains("shared.example.com"));
+    ssher_cmd(&store_path)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(contains("No sessions found."));
+}
+
+#[test]
+fn env_sessions_are_listed_and_read_only() {
+    let (_dir, store_path) = store_path();
+    let sessions = r#"[{"name":"envhost","host":"env.example.com","user":"me","port":22}]"#;
+
+    ssher_cmd(&store_path)
+        .env("SSHER_SESSIONS", sessions)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(contains("me@env.example.com"));
+
+    ssher_cmd(&store_path)
+        .env("SSHER_SESSIONS", sessions)
+        .args(["remove", "--name", "envhost"])
+        .assert()
+        .failure()
+        .stderr(contains("read-only"));
+    assert!(!store_path.exists());
+}
+
+#[test]
+fn quiet_suppresses_informational_output() {
+    let (_dir, store_path) = store_path();
+
+    ssher_cmd(&store_path)
+        .args([
+            "-q",
+            "add",
+            "--name",
+            "office",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::is_empty());
+
+    ssher_cmd(&store_path)
+        .args(["list", "--quiet"])
+        .assert()
+        .success()
+        .stdout(contains("office"));
+}
+
+#[test]
+fn log_level_debug_reports_store_path_on_stderr() {
+    let (_dir, store_path) = store_path();
+
+    ssher_cmd(&store_path)
+        .args(["--log-level", "debug", "list"])
+        .assert()
+        .success()
+        .stderr(contains("using store"))
+        .stderr(contains(store_path.display().to_string()));
+
+    ssher_cmd(&store_path)
+        .args(["list"])
+        .assert()
+        .success()
+        .stderr(predicates::str::is_empty());
+}
+
+#[test]
+fn config_dir_is_accepted_after_the_subcommand() {
+    let dir = tempdir().expect("tempdir");
+    let config_dir = dir.path().join("project-ssher");
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("ssher");
+    cmd.env_remove("SSHER_STORE")
+        .env_remove("SSHER_CONFIG_DIR")
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+            "--config-dir",
+        ])
+        .arg(&config_dir)
+        .assert()
+        .success();
+    assert!(config_dir.join("sessions.json").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("ssher");
+    cmd.env_remove("SSHER_STORE")
+        .env_remove("SSHER_CONFIG_DIR")
+        .args(["list", "--config-dir"])
+        .arg(&config_dir)
+        .assert()
+        .success()
+        .stdout(contains("me@example.com"));
+}
+
+#[test]
+fn fresh_install_follows_the_default_store_format() {
+    let dir = tempdir().expect("tempdir");
+    let add = |config_dir: &Path, format: Option<&str>| {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("ssher");
+        cmd.env_remove("SSHER_STORE")
+            .env_remove("SSHER_CLI_CONFIG")
+            .env_remove("SSHER_STORE_FORMAT")
+            .env("SSHER_CONFIG_DIR", config_dir);
+        if let Some(format) = format {
+            cmd.env("SSHER_STORE_FORMAT", format);
+        }
+        cmd.args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+    };
+
+    let from_env = dir.path().join("env");
+    add(&from_env, Some("sqlite"));
+    assert!(from_env.join("sessions.db").exists());
+    assert!(!from_env.join("sessions.json").exists());
+
+    let from_config = dir.path().join("config");
+    std::fs::create_dir(&from_config).expect("mkdir");
+    std::fs::write(
+        from_config.join("cli.json"),
+        r#"{"store_format": "sqlite"}"#,
+    )
+    .expect("write cli.json");
+    add(&from_config, None);
+    assert!(from_config.join("sessions.db").exists());
+
+    // An existing store keeps its format.
+    let existing = dir.path().join("existing");
+    add(&existing, None);
+    assert!(existing.join("sessions.json").exists());
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("ssher");
+    cmd.env_remove("SSHER_STORE")
+        .env("SSHER_CONFIG_DIR", &existing)
+        .env("SSHER_STORE_FORMAT", "sqlite")
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(contains("me@example.com"));
+    assert!(!existing.join("sessions.db").exists());
+}
+
+#[test]
+fn config_dir_relocates_store_and_configs() {
+    let dir = tempdir().expect("tempdir");
+    let config_dir = dir.path().join("project-ssher");
+
+    let cmd = || {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("ssher");
+        cmd.env_remove("SSHER_STORE")
+            .env_remove("SSHER_UI_CONFIG")
+            .env_remove("SSHER_CLI_CONFIG")
+            .env("SSHER_CONFIG_DIR", &config_dir);
+        cmd
+    };
+
+    cmd()
+        .args([
+            "add",
+            "--name",
+            "web",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+    assert!(config_dir.join("sessions.json").exists());
+
+    cmd()
+        .args(["paths"])
+        .assert()
+        .success()
+        .stdout(contains(config_dir.join("ui.json").display().to_string()))
+        .stdout(contains(config_dir.join("cli.json").display().to_string()));
+
+    let explicit = dir.path().join("explicit.json");
+    cmd()
+        .args(["--store-path"])
+        .arg(&explicit)
+        .args([
+            "add",
+            "--name",
+            "db",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+    assert!(explicit.exists());
+}
+
+#[test]
+fn paths_reports_store_and_existence() {
+    let (_dir, store_path) = store_path();
+
+    ssher_cmd(&store_path)
+        .args(["paths"])
+        .assert()
+        .success()
+        .stdout(contains(format!("{} (missing)", store_path.display())))
+        .stdout(contains("ui config:"))
+        .stdout(contains("cli config:"));
+
+    ssher_cmd(&store_path)
+        .args([
+            "add",
+            "--name",
+            "office",
+            "--host",
+            "example.com",
+            "--user",
+            "me",
+        ])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .args(["whereis"])
+        .assert()
+        .success()
+        .stdout(contains(format!("{} (exists)", store_path.display())));
+}
+
+#[test]
+fn lenient_import_fills_missing_user_and_port() {
+    let (dir, store_path) = store_path();
+    let input = dir.path().join("partial.json");
+    std::fs::write(&input, r#"[{"name":"shared","host":"shared.example.com"}]"#).expect("write");
+
+    ssher_cmd(&store_path)
+        .args(["import", "--input"])
+        .arg(&input)
+        .assert()
+        .failure();
+
+    ssher_cmd(&store_path)
+        .env("USER", "tester")
+        .args(["import", "--lenient", "--input"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stderr(contains("using user=tester, port=22"));
+
+    ssher_cmd(&store_path)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(contains("tester@shared.example.com"));
+}
+
+#[test]
+fn config_validate_reports_problems_and_fails() {
+    let (dir, store_path) = store_path();
+    let ui_config = dir.path().join("ui.json");
+    std::fs::write(
+        &ui_config,
+        r#"{"layout": {"shw_logo": false}, "theme": {"border": "Purple"}}"#,
+    )
+    .expect("write ui config");
+    let cli_config = dir.path().join("cli.json");
+    std::fs::write(&cli_config, r#"{"header": "Yellow"}"#).expect("write cli config");
+
+    ssher_cmd(&store_path)
+        .args(["config", "validate", "--ui-config"])
+        .arg(&ui_config)
+        .arg("--cli-config")
+        .arg(&cli_config)
+        .assert()
+        .failure()
+        .stdout(contains(
+            "layout.shw_logo: unknown key (did you mean 'show_logo'?)",
+        ))
+        .stdout(contains("theme.border: unknown color 'Purple'"))
+        .stdout(contains("cli config:").and(contains("ok")))
+        .stderr(contains("found 2 problem(s)"));
+
+    std::fs::write(&ui_config, r#"{"layout": {"show_logo": false}}"#).expect("fix ui config");
+    ssher_cmd(&store_path)
+        .args(["config", "validate", "--ui-config"])
+        .arg(&ui_config)
+        .arg("--cli-config")
+        .arg(&cli_config)
+        .assert()
+        .success();
+}
+
+#[test]
+fn strict_config_rejects_unknown_keys() {
+    let (dir, store_path) = store_path();
+    let cli_config = dir.path().join("cli.json");
+    std::fs::write(&cli_config, r#"{"haeder": "Red"}"#).expect("write cli config");
+
+    ssher_cmd(&store_path)
+        .env("SSHER_CLI_CONFIG", &cli_config)
+        .args(["list"])
+        .assert()
+        .success();
+
+    ssher_cmd(&store_path)
+        .env("SSHER_CLI_CONFIG", &cli_config)
+        .args(["--strict-config", "list"])
+        .assert()
+        .failure()
+        .stderr(contains("haeder: unknown key (did you mean 'header'?)"));
+
+    ssher_cmd(&store_path)
+        .env("SSHER_CLI_CONFIG", &cli_config)
+        .env("SSHER_STRICT_CONFIG", "1")
+        .args(["list"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,64 @@
+use predicates::str::contains;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn completions(shell: &str) -> String {
+    let output = assert_cmd::cargo::cargo_bin_cmd!("se")
+        .args(["completions", shell])
+        .output()
+        .expect("run completions");
+    assert!(output.status.success(), "completions {shell} failed");
+    let script = String::from_utf8(output.stdout).expect("utf8 completions");
+    assert!(!script.trim().is_empty(), "completions {shell} were empty");
+    script
+}
+
+#[test]
+fn bash_completions_have_expected_header_and_parse() {
+    let script = completions("bash");
+    assert!(script.contains("_se()"));
+    assert!(script.contains("complete -F _se"));
+
+    // Syntax-check the script when bash is available.
+    if let Ok(mut child) = Command::new("bash").arg("-n").stdin(Stdio::piped()).spawn() {
+        child
+            .stdin
+            .take()
+            .expect("stdin")
+            .write_all(script.as_bytes())
+            .expect("write script");
+        assert!(child.wait().expect("bash -n").success());
+    }
+}
+
+#[test]
+fn zsh_completions_have_expected_header() {
+    assert!(completions("zsh").starts_with("#compdef se"));
+}
+
+#[test]
+fn fish_completions_have_expected_header() {
+    assert!(completions("fish").contains("complete -c se"));
+}
+
+#[test]
+fn powershell_completions_have_expected_header() {
+    let script = completions("powershell");
+    assert!(script.contains("Register-ArgumentCompleter"));
+    assert!(script.contains("-CommandName 'se'"));
+}
+
+#[test]
+fn elvish_completions_have_expected_header() {
+    let script = completions("elvish");
+    assert!(script.contains("edit:completion:arg-completer[se]"));
+}
+
+#[test]
+fn unknown_shell_is_rejected() {
+    assert_cmd::cargo::cargo_bin_cmd!("se")
+        .args(["completions", "tcsh"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid value"));
+}
@@ -0,0 +1,138 @@
+//! Exporting sessions and importing the result back must not lose data beyond
+//! what each format is documented to drop.
+
+use ssher::{
+    ExportFormat, PasswdUnsafeMode, Session, import_from_json, import_from_json_lenient,
+    import_from_ssh_config, import_from_yaml, render_export,
+};
+use std::collections::BTreeMap;
+
+/// Sessions with every field set, so a field a format forgets shows up as a diff.
+fn full_sessions() -> Vec<Session> {
+    let mut web = Session::builder("web", "web.example.com", "deploy")
+        .port(2222)
+        .identity_file("/home/deploy/.ssh/id_ed25519")
+        .tags(vec!["prod".to_string(), "frontend".to_string()])
+        .last_connected_at(1_700_000_000)
+        .proxy_jump("me@bastion.example.com:2200")
+        .build();
+    web.has_stored_password = true;
+    web.passwd_unsafe_mode = Some(PasswdUnsafeMode::Bare);
+    web.scp_compress = true;
+    web.last_duration_secs = Some(95);
+    web.avg_duration_secs = Some(120);
+    web.default_local_dir = Some("~/deploys".to_string());
+    web.control_master = true;
+    web.control_path = Some("~/.ssh/cm-%r@%h:%p".to_string());
+    web.options = BTreeMap::from([
+        ("ServerAliveInterval".to_string(), "30".to_string()),
+        ("Compression".to_string(), "yes".to_string()),
+    ]);
+    web.local_forwards = vec![
+        "5432:db.internal:5432".to_string(),
+        "127.0.0.1:8080:[::1]:80".to_string(),
+    ];
+    web.remote_forwards = vec!["9000:localhost:3000".to_string()];
+    web.extra_args = ["-o", "ServerAliveCountMax=3", "-A"]
+        .map(String::from)
+        .to_vec();
+
+    let db = Session::builder("db", "db.internal", "postgres").build();
+    vec![web, db]
+}
+
+#[test]
+fn json_round_trip_keeps_every_field() {
+    let sessions = full_sessions();
+    let exported = render_export(&sessions, ExportFormat::Json).unwrap();
+    assert_eq!(import_from_json(&exported).unwrap(), sessions);
+}
+
+#[test]
+fn yaml_round_trip_keeps_every_field() {
+    let sessions = full_sessions();
+    let exported = render_export(&sessions, ExportFormat::Yaml).unwrap();
+    assert!(exported.contains("identity_file: /home/deploy/.ssh/id_ed25519"));
+    assert_eq!(import_from_yaml(&exported).unwrap(), sessions);
+}
+
+#[test]
+fn json_export_drops_stored_passwords() {
+    let mut sessions = full_sessions();
+    sessions[0].stored_password = Some("hunter2".to_string());
+    let exported = render_export(&sessions, ExportFormat::Json).unwrap();
+    assert!(!exported.contains("hunter2"));
+
+    let imported = import_from_json(&exported).unwrap();
+    assert_eq!(imported[0].stored_password, None);
+    sessions[0].stored_password = None;
+    assert_eq!(imported, sessions);
+}
+
+#[test]
+fn lenient_json_import_keeps_the_basic_fields() {
+    let sessions = full_sessions();
+    let exported = render_export(&sessions, ExportFormat::Json).unwrap();
+    let imported = import_from_json_lenient(&exported).unwrap();
+
+    // The lenient reader only knows the fields hand-written JSON tends to carry.
+    let expected: Vec<Session> = sessions
+        .iter()
+        .map(|session| {
+            let mut builder = Session::builder(&session.name, &session.host, &session.user)
+                .port(session.port)
+                .tags(session.tags.clone());
+            if let Some(identity) = &session.identity_file {
+                builder = builder.identity_file(identity.clone());
+            }
+            if let Some(at) = session.last_connected_at {
+                builder = builder.last_connected_at(at);
+            }
+            builder.build()
+        })
+        .collect();
+    assert_eq!(imported, expected);
+}
+
+#[test]
+fn ssh_config_round_trip_keeps_connection_settings_only() {
+    let sessions = full_sessions();
+    let exported = render_export(&sessions, ExportFormat::SshConfig).unwrap();
+    let imported = import_from_ssh_config(&exported).unwrap();
+
+    // ssh_config has no place for tags (they are written as a comment), history,
+    // password settings, scp preferences or connection durations. Extra `-o` args come
+    // back as options; other extra args are only written as a comment. Option
+    // keywords come back lowercased, as ssh reads them case-insensitively.
+    let expected: Vec<Session> = sessions
+        .into_iter()
+        .map(|mut session| {
+            if !session.extra_args.is_empty() {
+                session
+                    .options
+                    .insert("ServerAliveCountMax".to_string(), "3".to_string());
+            }
+            session.options = std::mem::take(&mut session.options)
+                .into_iter()
+                .map(|(key, value)| (key.to_lowercase(), value))
+                .collect();
+            session
+        })
+        .map(|session| Session {
+            tags: Vec::new(),
+            extra_args: Vec::new(),
+            last_connected_at: None,
+            has_stored_password: false,
+            passwd_unsafe_mode: None,
+            stored_password: None,
+            scp_compress: false,
+            last_duration_secs: None,
+            avg_duration_secs: None,
+            default_local_dir: None,
+            ..session
+        })
+        .collect();
+    assert_eq!(imported, expected);
+    assert!(exported.contains("    # Tags: prod, frontend\n"));
+    assert!(exported.contains("    # ssh args: -A\n"));
+}
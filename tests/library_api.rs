@@ -0,0 +1,31 @@
+use ssher::{ExportFormat, JsonFileStore, SessionStore, import_from_ssh_config, render_export};
+
+#[test]
+fn store_round_trips_sessions_through_the_public_api() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = JsonFileStore::new(dir.path().join("sessions.json"));
+    let config = "\
+Host web
+    HostName web.example.com
+    User deploy
+    Port 2222
+";
+    for session in import_from_ssh_config(config).unwrap() {
+        store.add(session).unwrap();
+    }
+
+    let sessions = store.list().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].target(), "deploy@web.example.com");
+
+    let store: &dyn SessionStore = &store;
+    store.touch_last_connected("web", 1_700_000_000).unwrap();
+    assert_eq!(
+        store.list().unwrap()[0].last_connected_at,
+        Some(1_700_000_000)
+    );
+
+    let exported = render_export(&store.list().unwrap(), ExportFormat::SshConfig).unwrap();
+    assert!(exported.contains("Host web\n    HostName web.example.com\n"));
+    assert!(exported.contains("    Port 2222\n"));
+}
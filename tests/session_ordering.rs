@@ -5,97 +5,47 @@ use ssher::ui::{SessionHighlight, sort_sessions};
 #[test]
 fn test_sort_sessions_latest_first() {
     let mut sessions = vec![
-        Session {
-            name: "old".to_string(),
-            host: "old.example.com".to_string(),
-            user: "user".to_string(),
-            port: 22,
-            identity_file: None,
-            tags: vec![],
-            last_connected_at: Some(100),
-            has_stored_password: false,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        },
-        Session {
-            name: "new".to_string(),
-            host: "new.example.com".to_string(),
-            user: "user".to_string(),
-            port: 22,
-            identity_file: None,
-            tags: vec![],
-            last_connected_at: Some(200),
-            has_stored_password: false,
-            passwd_unsafe_mode: None,
-            stored_password: None,
-        },
+        Session::builder("old", "old.example.com", "user")
+            .last_connected_at(100)
+            .build(),
+        Session::builder("new", "new.example.com", "user")
+            .last_connected_at(200)
+            .build(),
     ];
 
-    sort_sessions(&mut sessions, SessionOrderMode::LatestFirst);
+    sort_sessions(&mut sessions, SessionOrderMode::LatestFirst, 300);
     assert_eq!(sessions[0].name, "new");
     assert_eq!(sessions[1].name, "old");
 }
 
 #[test]
 fn test_session_highlight_classify() {
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
+    let now = 1_700_000_000;
 
     // Hot session (connected 1 hour ago)
-    let hot_session = Session {
-        name: "hot".to_string(),
-        host: "example.com".to_string(),
-        user: "user".to_string(),
-        port: 22,
-        identity_file: None,
-        tags: vec![],
-        last_connected_at: Some(now - 3600),
-        has_stored_password: false,
-        passwd_unsafe_mode: None,
-        stored_password: None,
-    };
+    let hot_session = Session::builder("hot", "example.com", "user")
+        .last_connected_at(now - 3600)
+        .build();
     assert_eq!(
-        SessionHighlight::classify(&hot_session, 7),
+        SessionHighlight::classify(&hot_session, 7, now),
         SessionHighlight::Hot
     );
 
     // Normal session (connected 3 days ago)
-    let normal_session = Session {
-        name: "normal".to_string(),
-        host: "example.com".to_string(),
-        user: "user".to_string(),
-        port: 22,
-        identity_file: None,
-        tags: vec![],
-        last_connected_at: Some(now - (3 * 24 * 3600)),
-        has_stored_password: false,
-        passwd_unsafe_mode: None,
-        stored_password: None,
-    };
+    let normal_session = Session::builder("normal", "example.com", "user")
+        .last_connected_at(now - (3 * 24 * 3600))
+        .build();
     assert_eq!(
-        SessionHighlight::classify(&normal_session, 7),
+        SessionHighlight::classify(&normal_session, 7, now),
         SessionHighlight::Normal
     );
 
     // Dying session (connected 10 days ago)
-    let dying_session = Session {
-        name: "dying".to_string(),
-        host: "example.com".to_string(),
-        user: "user".to_string(),
-        port: 22,
-        identity_file: None,
-        tags: vec![],
-        last_connected_at: Some(now - (10 * 24 * 3600)),
-        has_stored_password: false,
-        passwd_unsafe_mode: None,
-        stored_password: None,
-    };
+    let dying_session = Session::builder("dying", "example.com", "user")
+        .last_connected_at(now - (10 * 24 * 3600))
+        .build();
     assert_eq!(
-        SessionHighlight::classify(&dying_session, 7),
+        SessionHighlight::classify(&dying_session, 7, now),
         SessionHighlight::Dying
     );
 }